@@ -0,0 +1,218 @@
+//! Command line options for the `guessing_game` binary.
+
+use std::env;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use config::{Config, Difficulty};
+use messages::Locale;
+
+/// The environment variable consulted for a seed when `--seed` isn't given.
+pub const SEED_VAR: &str = "GUESSING_GAME_SEED";
+
+pub const USAGE: &str = "\
+Usage: guessing_game [options]
+       guessing_game scores [--scores-file PATH]
+       guessing_game reverse [options]
+       guessing_game auto [options]
+       guessing_game serve [--listen ADDR] [options]
+
+Commands:
+    scores                Print the leaderboards and player statistics.
+    reverse               You think of a number and the computer guesses it.
+    auto                  Watch the computer play a round by itself.
+    serve                 Host a round that players join over TCP.
+
+Options:
+    --seed N              Pick the secret from seed N, so the round can be
+                          replayed. Defaults to $GUESSING_GAME_SEED, or a
+                          random seed.
+    --difficulty LEVEL    Use the easy (1-20, 8 attempts), normal (1-100,
+                          10 attempts) or hard (1-1000, 10 attempts) preset.
+    --min N               Smallest possible secret. Defaults to 1.
+    --max N               Biggest possible secret. Defaults to 100.
+    --max-attempts N      Lose after N guesses without finding the number.
+                          Defaults to unlimited.
+    --hints N             Hint mode: say how close each guess was, and sell
+                          up to N clues (type `hint`) for 2 points each.
+    --format FORMAT       `text` (the default) to play interactively, or `json`
+                          to read guesses silently and print a transcript of
+                          the round as JSON. Works for `auto` too.
+    --name NAME           Who's playing, for the scores. Defaults to $USER.
+    --scores-file PATH    Where scores are kept. Defaults to
+                          guessing_game_scores.txt in the current directory.
+    --listen ADDR         Where `serve` listens. Defaults to 127.0.0.1:7878.
+    --lang CODE           Talk in English (en) or Spanish (es). Defaults to
+                          $LC_ALL, $LC_MESSAGES or $LANG, then English.
+    -h, --help            Print this message.
+
+--min, --max and --max-attempts override the values from --difficulty.
+
+Exit status:
+    0     The game finished, won or lost.
+    1     The input ended before the game did.
+    2     Bad command line arguments.
+    3     Caught cheating in the reverse game.
+    74    Reading or writing the terminal failed.";
+
+/// Where `serve` listens unless told otherwise.
+pub const DEFAULT_LISTEN: &str = "127.0.0.1:7878";
+
+pub const EXIT_EOF: i32 = 1;
+pub const EXIT_USAGE: i32 = 2;
+pub const EXIT_CHEATED: i32 = 3;
+pub const EXIT_IO: i32 = 74;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Command {
+    #[default]
+    Play,
+    Scores,
+    Reverse,
+    Auto,
+    Serve,
+}
+
+/// How the round is shown.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Format {
+    /// Chatty, for humans.
+    #[default]
+    Text,
+    /// A single JSON transcript at the end, for scripts.
+    Json,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Format, String> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            _ => Err(format!("unknown format `{}`, expected text or json", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Options {
+    pub command: Command,
+    pub seed: Option<u64>,
+    pub config: Config,
+    /// The clue budget, if hint mode is on.
+    pub hints: Option<u32>,
+    pub format: Format,
+    pub player: Option<String>,
+    pub scores_file: Option<PathBuf>,
+    pub listen: Option<String>,
+    /// The language asked for with `--lang`, otherwise it's up to the
+    /// environment.
+    pub lang: Option<Locale>,
+    pub help: bool,
+}
+
+impl Options {
+    /// Parses the process' own arguments and environment.
+    pub fn from_env() -> Result<Options, String> {
+        Options::parse(env::args().skip(1), env::var(SEED_VAR).ok())
+    }
+
+    /// Parses `args` (without the program name). `env_seed` is the value of
+    /// `$GUESSING_GAME_SEED`, used unless `--seed` is passed.
+    pub fn parse<I>(args: I, env_seed: Option<String>) -> Result<Options, String>
+        where I: IntoIterator<Item = String>
+    {
+        let mut options = Options::default();
+        let mut args = args.into_iter();
+
+        // Presets are applied first so the individual flags can tweak them,
+        // whatever order they were passed in
+        let mut difficulty = None;
+        let mut min = None;
+        let mut max = None;
+        let mut max_attempts = None;
+
+        if let Some(seed) = env_seed {
+            options.seed = Some(parse_number(&seed, SEED_VAR)?);
+        }
+
+        while let Some(arg) = args.next() {
+            // Accept both `--seed 42` and `--seed=42`
+            let (flag, inline) = match arg.find('=') {
+                Some(i) if arg.starts_with("--") => (arg[..i].to_string(), Some(arg[i + 1..].to_string())),
+                _ => (arg.clone(), None),
+            };
+
+            match flag.as_str() {
+                "--seed" => {
+                    let value = value_for(&flag, inline, &mut args)?;
+                    options.seed = Some(parse_number(&value, &flag)?);
+                }
+                "--difficulty" => {
+                    let value = value_for(&flag, inline, &mut args)?;
+                    difficulty = Some(value.parse::<Difficulty>()?);
+                }
+                "--min" => {
+                    let value = value_for(&flag, inline, &mut args)?;
+                    min = Some(parse_number(&value, &flag)?);
+                }
+                "--max" => {
+                    let value = value_for(&flag, inline, &mut args)?;
+                    max = Some(parse_number(&value, &flag)?);
+                }
+                "--max-attempts" => {
+                    let value = value_for(&flag, inline, &mut args)?;
+                    max_attempts = Some(parse_number(&value, &flag)?);
+                }
+                "--hints" => {
+                    let value = value_for(&flag, inline, &mut args)?;
+                    options.hints = Some(parse_number(&value, &flag)?);
+                }
+                "--format" => {
+                    options.format = value_for(&flag, inline, &mut args)?.parse()?;
+                }
+                "--name" => options.player = Some(value_for(&flag, inline, &mut args)?),
+                "--scores-file" => {
+                    options.scores_file = Some(PathBuf::from(value_for(&flag, inline, &mut args)?));
+                }
+                "--listen" => options.listen = Some(value_for(&flag, inline, &mut args)?),
+                "--lang" => options.lang = Some(value_for(&flag, inline, &mut args)?.parse()?),
+                "-h" | "--help" => options.help = true,
+                "scores" | "reverse" | "auto" | "serve" if options.command == Command::Play => {
+                    options.command = match flag.as_str() {
+                        "scores" => Command::Scores,
+                        "reverse" => Command::Reverse,
+                        "auto" => Command::Auto,
+                        _ => Command::Serve,
+                    };
+                }
+                _ => return Err(format!("unknown argument `{}`", arg)),
+            }
+        }
+
+        if let Some(difficulty) = difficulty {
+            options.config = difficulty.config();
+        }
+        options.config.min = min.unwrap_or(options.config.min);
+        options.config.max = max.unwrap_or(options.config.max);
+        options.config.max_attempts = max_attempts.or(options.config.max_attempts);
+        options.config.validate()?;
+
+        Ok(options)
+    }
+}
+
+fn value_for<I>(flag: &str, inline: Option<String>, args: &mut I) -> Result<String, String>
+    where I: Iterator<Item = String>
+{
+    match inline {
+        Some(value) => Ok(value),
+        None => args.next().ok_or_else(|| format!("`{}` needs a value", flag)),
+    }
+}
+
+fn parse_number<T: ::std::str::FromStr>(value: &str, source: &str) -> Result<T, String> {
+    value.trim().parse()
+        .map_err(|_| format!("`{}` must be a whole number, got `{}`", source, value))
+}
@@ -0,0 +1,89 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// The rules of a round: which numbers the secret can be, and how many
+/// guesses the player gets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Config {
+    pub min: u32,
+    pub max: u32,
+    /// `None` means the player can keep guessing forever.
+    pub max_attempts: Option<u32>,
+}
+
+impl Default for Config {
+    /// The classic game: 1 to 100 with as many guesses as you like.
+    fn default() -> Config {
+        Config { min: 1, max: 100, max_attempts: None }
+    }
+}
+
+impl Config {
+    /// Checks the range isn't empty and the attempt limit allows at least
+    /// one guess.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.min > self.max {
+            return Err(format!("the minimum ({}) can't be bigger than the maximum ({})",
+                               self.min, self.max));
+        }
+        if self.max_attempts == Some(0) {
+            return Err("the attempt limit has to allow at least one guess".to_string());
+        }
+        Ok(())
+    }
+
+    /// Whether `guess` could possibly be the secret.
+    pub fn contains(&self, guess: u32) -> bool {
+        self.min <= guess && guess <= self.max
+    }
+
+    /// The preset these rules came from, if any.
+    pub fn difficulty(&self) -> Option<Difficulty> {
+        Difficulty::ALL.iter().cloned().find(|difficulty| difficulty.config() == *self)
+    }
+}
+
+/// Named presets for the range and attempt limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Difficulty::Easy   => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard   => "hard",
+        }
+    }
+
+    pub fn config(&self) -> Config {
+        match *self {
+            Difficulty::Easy   => Config { min: 1, max: 20, max_attempts: Some(8) },
+            Difficulty::Normal => Config { min: 1, max: 100, max_attempts: Some(10) },
+            // Ten guesses is exactly what a perfect binary search needs here
+            Difficulty::Hard   => Config { min: 1, max: 1000, max_attempts: Some(10) },
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Difficulty, String> {
+        Difficulty::ALL.iter().cloned()
+            .find(|difficulty| difficulty.name() == s)
+            .ok_or_else(|| format!("unknown difficulty `{}`, expected easy, normal or hard", s))
+    }
+}
@@ -0,0 +1,144 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::io;
+use std::io::prelude::*;
+
+use messages::{Locale, Message};
+
+/// Where the game reads guesses from and writes its messages to.
+///
+/// The CLI talks to a terminal, tests and bots hand in a `Script`.
+pub trait Console {
+    /// Reads the next line of input, without the trailing newline.
+    /// Returns `Ok(None)` once the input is exhausted.
+    fn read_line(&mut self) -> io::Result<Option<String>>;
+
+    fn write_line(&mut self, line: &str) -> io::Result<()>;
+
+    /// The language to talk to the player in.
+    fn locale(&self) -> Locale {
+        Locale::default()
+    }
+
+    /// Writes `message` in the console's language.
+    fn say(&mut self, message: Message, args: &[&dyn fmt::Display]) -> io::Result<()> {
+        let line = self.locale().format(message, args);
+        self.write_line(&line)
+    }
+}
+
+/// A `Console` over the process' standard input and output.
+pub struct Terminal {
+    stdin: io::Stdin,
+    stdout: io::Stdout,
+    locale: Locale,
+}
+
+impl Terminal {
+    pub fn new() -> Terminal {
+        Terminal {
+            stdin: io::stdin(),
+            stdout: io::stdout(),
+            locale: Locale::default(),
+        }
+    }
+
+    pub fn with_locale(mut self, locale: Locale) -> Terminal {
+        self.locale = locale;
+        self
+    }
+}
+
+impl Default for Terminal {
+    fn default() -> Terminal {
+        Terminal::new()
+    }
+}
+
+impl Console for Terminal {
+    fn read_line(&mut self) -> io::Result<Option<String>> {
+        // `String::new()`
+        // String is a growable, UTF-8 encoded bit of text
+        // :: accesses the static method
+        let mut line = String::new();
+
+        // `read_line` populates the string we pass in and returns how many
+        // bytes it read, zero meaning we hit the end of the input.
+        if self.stdin.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+
+        Ok(Some(line.trim_end_matches(&['\r', '\n'][..]).to_string()))
+    }
+
+    fn write_line(&mut self, line: &str) -> io::Result<()> {
+        writeln!(self.stdout, "{}", line)
+    }
+
+    fn locale(&self) -> Locale {
+        self.locale
+    }
+}
+
+/// A `Console` fed from a fixed list of input lines, remembering everything
+/// written to it.
+#[derive(Debug, Clone, Default)]
+pub struct Script {
+    input: VecDeque<String>,
+    output: Vec<String>,
+    locale: Locale,
+}
+
+impl Script {
+    pub fn new<S: AsRef<str>>(input: &[S]) -> Script {
+        Script {
+            input: input.iter().map(|line| line.as_ref().to_string()).collect(),
+            output: Vec::new(),
+            locale: Locale::default(),
+        }
+    }
+
+    pub fn with_locale(mut self, locale: Locale) -> Script {
+        self.locale = locale;
+        self
+    }
+
+    /// Every line written so far, in order.
+    pub fn output(&self) -> &[String] {
+        &self.output
+    }
+}
+
+impl Console for Script {
+    fn read_line(&mut self) -> io::Result<Option<String>> {
+        Ok(self.input.pop_front())
+    }
+
+    fn write_line(&mut self, line: &str) -> io::Result<()> {
+        self.output.push(line.to_string());
+        Ok(())
+    }
+
+    fn locale(&self) -> Locale {
+        self.locale
+    }
+}
+
+/// Wraps another `Console`, passing its input through but throwing away
+/// everything written to it. For when the output goes somewhere else, like
+/// a JSON transcript.
+pub struct Quiet<C>(pub C);
+
+impl<C: Console> Console for Quiet<C> {
+    fn read_line(&mut self) -> io::Result<Option<String>> {
+        self.0.read_line()
+    }
+
+    fn write_line(&mut self, _line: &str) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn locale(&self) -> Locale {
+        self.0.locale()
+    }
+}
@@ -0,0 +1,88 @@
+use std::error;
+use std::fmt;
+use std::io;
+use std::num::IntErrorKind;
+
+use config::Config;
+use messages::{Locale, Message};
+
+/// Everything that can go wrong reading a guess.
+#[derive(Debug)]
+pub enum Error {
+    /// The input ended before the game did.
+    Eof,
+    /// Reading or writing the console failed.
+    Io(io::Error),
+    /// The line wasn't a whole number at all.
+    NotANumber(String),
+    /// The line was a number, but below zero.
+    Negative(String),
+    /// The line was a number too big to fit in a `u32`.
+    Overflow(String),
+}
+
+impl Error {
+    /// What to tell the player, in `locale`, so they can do better next
+    /// time.
+    pub fn hint(&self, locale: Locale, config: &Config) -> String {
+        let (min, max) = (&config.min, &config.max);
+
+        match *self {
+            Error::Eof => locale.format(Message::NoMoreInput, &[]),
+            Error::Io(ref err) => locale.format(Message::BrokenConsole, &[err]),
+            Error::NotANumber(ref input) if input.is_empty() => {
+                locale.format(Message::EmptyGuess, &[min, max])
+            }
+            Error::NotANumber(ref input) => locale.format(Message::NotANumber, &[input, min, max]),
+            Error::Negative(_) => locale.format(Message::NegativeGuess, &[min, max]),
+            Error::Overflow(_) => locale.format(Message::HugeGuess, &[min, max]),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Eof => write!(f, "unexpected end of input"),
+            Error::Io(ref err) => write!(f, "I/O error: {}", err),
+            Error::NotANumber(ref input) => write!(f, "`{}` is not a number", input),
+            Error::Negative(ref input) => write!(f, "`{}` is negative", input),
+            Error::Overflow(ref input) => write!(f, "`{}` is too big for a u32", input),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::Io(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+// Lets `?` turn console errors into our own
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Error {
+        Error::Io(err)
+    }
+}
+
+/// Parses a line of input into a guess, telling apart the different ways it
+/// can fail.
+pub fn parse_guess(input: &str) -> Result<u32, Error> {
+    let input = input.trim();
+
+    input.parse().map_err(|err: ::std::num::ParseIntError| {
+        let digits = input.strip_prefix('-').unwrap_or(input);
+        let is_number = !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit());
+
+        if *err.kind() == IntErrorKind::PosOverflow {
+            Error::Overflow(input.to_string())
+        } else if is_number && input.starts_with('-') {
+            Error::Negative(input.to_string())
+        } else {
+            Error::NotANumber(input.to_string())
+        }
+    })
+}
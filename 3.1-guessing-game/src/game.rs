@@ -0,0 +1,204 @@
+use std::cmp::Ordering;
+
+use rand::{Rng, SeedableRng, XorShiftRng};
+
+use config::Config;
+use hint::{Clue, Hints};
+
+/// What a single guess told the player about the secret number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    TooSmall,
+    TooBig,
+    Win,
+    /// The guess can't be the secret under the game's `Config`. It isn't
+    /// counted as an attempt.
+    OutOfRange,
+}
+
+impl From<Ordering> for Outcome {
+    // Ordering is an enum, looks like:
+    // enum Ordering {
+    //   Less,
+    //   Greater,
+    //   Equal,
+    // }
+    fn from(ordering: Ordering) -> Outcome {
+        match ordering {
+            Ordering::Less    => Outcome::TooSmall,
+            Ordering::Greater => Outcome::TooBig,
+            Ordering::Equal   => Outcome::Win,
+        }
+    }
+}
+
+/// One guess and what it told the player.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Turn {
+    pub guess: u32,
+    pub outcome: Outcome,
+    /// Which attempt this counted as, starting from 1. `None` for guesses
+    /// that were out of range.
+    pub attempt: Option<u32>,
+}
+
+/// Where a round is at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum State {
+    Playing,
+    Won,
+    /// The attempt limit was used up without finding the secret.
+    Lost,
+}
+
+/// A single round of the guessing game: the rules, the secret number and
+/// every guess made against it so far.
+#[derive(Debug, Clone)]
+pub struct Game {
+    config: Config,
+    secret: u32,
+    attempts: Vec<u32>,
+    turns: Vec<Turn>,
+    hints: Option<Hints>,
+}
+
+impl Game {
+    /// Starts a round with a known secret, handy for tests and bots.
+    ///
+    /// # Panics
+    ///
+    /// If `config` isn't valid or doesn't contain `secret`.
+    pub fn new(config: Config, secret: u32) -> Game {
+        if let Err(err) = config.validate() {
+            panic!("invalid game config: {}", err);
+        }
+        assert!(config.contains(secret), "secret {} is outside {}..={}",
+                secret, config.min, config.max);
+
+        Game {
+            config,
+            secret,
+            attempts: Vec::new(),
+            turns: Vec::new(),
+            hints: None,
+        }
+    }
+
+    /// Turns on hint mode, with up to `budget` clues for sale.
+    pub fn with_hints(mut self, budget: u32) -> Game {
+        self.hints = Some(Hints::new(budget));
+        self
+    }
+
+    /// The hints still for sale, or `None` outside of hint mode.
+    pub fn hints(&self) -> Option<&Hints> {
+        self.hints.as_ref()
+    }
+
+    /// How many clues have been bought.
+    pub fn hints_used(&self) -> u32 {
+        self.hints.as_ref().map(|hints| hints.given()).unwrap_or(0)
+    }
+
+    /// Buys a clue about the secret, if hint mode is on and the budget
+    /// allows.
+    pub fn hint(&mut self) -> Option<Clue> {
+        let secret = self.secret;
+        self.hints.as_mut().and_then(|hints| hints.next(secret))
+    }
+
+    /// Starts a round with a randomly picked secret.
+    pub fn random(config: Config) -> Game {
+        // Because we used `use rand::Rng`, the `gen_range` method is available.
+        // `thread_rng()` requires `rand::Rng` be in scope, as methods are defined
+        // on "traits" for the method to work, and the trait needs to be in scope
+        Game::new(config, pick_secret(&mut rand::thread_rng(), &config))
+    }
+
+    /// Starts a round whose secret is derived from `seed`, so the same seed
+    /// and config always produce the same round.
+    pub fn seeded(config: Config, seed: u64) -> Game {
+        Game::new(config, pick_secret(&mut seeded_rng(seed), &config))
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    pub fn secret(&self) -> u32 {
+        self.secret
+    }
+
+    /// Every counted guess made so far, oldest first.
+    pub fn attempts(&self) -> &[u32] {
+        &self.attempts
+    }
+
+    /// Every guess made so far, including rejected ones, oldest first.
+    pub fn turns(&self) -> &[Turn] {
+        &self.turns
+    }
+
+    /// How many guesses the player has left, if the game has a limit.
+    pub fn attempts_left(&self) -> Option<u32> {
+        self.config.max_attempts
+            .map(|max| max.saturating_sub(self.attempts.len() as u32))
+    }
+
+    pub fn state(&self) -> State {
+        if self.attempts.last() == Some(&self.secret) {
+            State::Won
+        } else if self.attempts_left() == Some(0) {
+            State::Lost
+        } else {
+            State::Playing
+        }
+    }
+
+    /// Records `guess` and compares it against the secret. Guesses outside
+    /// the configured range are rejected without using up an attempt.
+    ///
+    /// # Panics
+    ///
+    /// If the game is already over.
+    pub fn guess(&mut self, guess: u32) -> Outcome {
+        assert_eq!(self.state(), State::Playing, "the game is already over");
+
+        let (outcome, attempt) = if self.config.contains(guess) {
+            self.attempts.push(guess);
+            (Outcome::from(guess.cmp(&self.secret)), Some(self.attempts.len() as u32))
+        } else {
+            (Outcome::OutOfRange, None)
+        };
+
+        self.turns.push(Turn { guess, outcome, attempt });
+        outcome
+    }
+}
+
+/// Picks a fresh seed for `Game::seeded`, for when the player didn't ask for
+/// one. Print it so the round can be replayed later.
+pub fn random_seed() -> u64 {
+    rand::thread_rng().gen()
+}
+
+fn pick_secret<R: Rng>(rng: &mut R, config: &Config) -> u32 {
+    // Widen so a range ending at `u32::MAX` doesn't overflow
+    rng.gen_range(config.min as u64, config.max as u64 + 1) as u32
+}
+
+/// A deterministic generator for `seed`. Unlike `thread_rng()` this gives the
+/// same numbers on every run and every machine.
+fn seeded_rng(seed: u64) -> XorShiftRng {
+    let low = seed as u32;
+    let high = (seed >> 32) as u32;
+
+    // XorShift can't be seeded with all zeros, so mix the seed with some
+    // constants; no seed can cancel out all four of them.
+    XorShiftRng::from_seed([
+        low ^ 0x193a_6754,
+        high ^ 0xa8a7_d469,
+        low ^ 0x9783_0e05,
+        high ^ 0x113b_a7bb,
+    ])
+}
@@ -0,0 +1,111 @@
+//! Optional help for the player: how close a guess was, whether it was
+//! closer than the one before, and a few paid-for clues about the secret.
+
+use config::Config;
+
+/// How far a guess was from the secret, relative to the size of the range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Band {
+    /// Within 5% of the range.
+    Hot,
+    /// Within 20% of the range.
+    Warm,
+    Cold,
+}
+
+impl Band {
+    pub fn new(config: &Config, guess: u32, secret: u32) -> Band {
+        let span = config.max as u64 - config.min as u64 + 1;
+        let distance = distance(guess, secret);
+
+        // Even tiny ranges get a hot and warm zone
+        if distance <= (span / 20).max(1) {
+            Band::Hot
+        } else if distance <= (span / 5).max(2) {
+            Band::Warm
+        } else {
+            Band::Cold
+        }
+    }
+}
+
+/// Whether a guess got closer to the secret than the one before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trend {
+    Warmer,
+    Colder,
+    Same,
+}
+
+impl Trend {
+    pub fn new(previous: u32, guess: u32, secret: u32) -> Trend {
+        use std::cmp::Ordering;
+
+        match distance(guess, secret).cmp(&distance(previous, secret)) {
+            Ordering::Less    => Trend::Warmer,
+            Ordering::Greater => Trend::Colder,
+            Ordering::Equal   => Trend::Same,
+        }
+    }
+}
+
+fn distance(a: u32, b: u32) -> u64 {
+    (a as i64 - b as i64).unsigned_abs()
+}
+
+/// A fact about the secret, bought with points off the score.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Clue {
+    /// Whether the secret is even.
+    Even(bool),
+    /// Whether the secret is divisible by the number.
+    DivisibleBy(u32, bool),
+}
+
+/// How many points each clue adds to the score, where lower is better.
+pub const COST: u32 = 2;
+
+/// The divisors asked about after parity, in order.
+const DIVISORS: [u32; 5] = [3, 5, 7, 11, 13];
+
+/// The clues a player can still buy.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hints {
+    budget: u32,
+    given: u32,
+}
+
+impl Hints {
+    /// Allows at most `budget` clues.
+    pub fn new(budget: u32) -> Hints {
+        Hints { budget, given: 0 }
+    }
+
+    /// How many clues have been bought.
+    pub fn given(&self) -> u32 {
+        self.given
+    }
+
+    pub fn remaining(&self) -> u32 {
+        let available = DIVISORS.len() as u32 + 1;
+        self.budget.min(available) - self.given
+    }
+
+    /// The next clue about `secret`: parity first, then divisibility by
+    /// small primes. `None` once the budget is spent.
+    pub fn next(&mut self, secret: u32) -> Option<Clue> {
+        if self.remaining() == 0 {
+            return None;
+        }
+
+        let clue = match self.given {
+            0 => Clue::Even(secret.is_multiple_of(2)),
+            n => {
+                let divisor = DIVISORS[n as usize - 1];
+                Clue::DivisibleBy(divisor, secret.is_multiple_of(divisor))
+            }
+        };
+        self.given += 1;
+        Some(clue)
+    }
+}
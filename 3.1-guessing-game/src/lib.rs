@@ -0,0 +1,147 @@
+//! The guessing game from chapter 3.1, split into a `Game` that knows the
+//! rules and a `Console` that knows how to talk to the player, so the same
+//! round can be played from a terminal, a test or a bot.
+
+// Like `use rand`, so can use `rand::<method>` anywhere
+// and its in our Cargo.toml's dependencies
+extern crate rand;
+#[macro_use]
+extern crate serde_json;
+
+pub use config::{Config, Difficulty};
+pub use console::{Console, Quiet, Script, Terminal};
+pub use error::{Error, parse_guess};
+pub use game::{Game, Outcome, State, Turn, random_seed};
+pub use messages::{Locale, Message};
+
+use hint::{Band, Clue, Trend};
+
+pub mod cli;
+mod config;
+mod console;
+mod error;
+mod game;
+pub mod hint;
+pub mod messages;
+pub mod scores;
+pub mod server;
+pub mod solver;
+pub mod transcript;
+
+/// Plays `game` until the secret is found or the attempts run out, reading
+/// guesses from and writing messages to `console`. Returns how the round
+/// ended.
+///
+/// Lines that aren't a valid guess get a hint and don't count. In hint mode
+/// every wrong guess also says how close it was, and typing `hint` buys a
+/// clue.
+///
+/// # Failures
+///
+/// `Error::Eof` if the input runs out before the game is over, or
+/// `Error::Io` if the console fails.
+pub fn play<C: Console>(game: &mut Game, console: &mut C) -> Result<State, Error> {
+    let config = *game.config();
+    let locale = console.locale();
+
+    console.say(Message::GuessTheNumber, &[])?;
+    console.say(Message::Range, &[&config.min, &config.max])?;
+    match config.max_attempts {
+        Some(1) => console.say(Message::OneAttempt, &[])?,
+        Some(max_attempts) => console.say(Message::Attempts, &[&max_attempts])?,
+        None => {}
+    }
+
+    // Loops until the game is over
+    while game.state() == State::Playing {
+        console.say(Message::InputGuess, &[])?;
+
+        let line = match console.read_line()? {
+            Some(line) => line,
+            None => return Err(Error::Eof),
+        };
+
+        let hint_command = locale.format(Message::HintCommand, &[]);
+        if game.hints().is_some() && line.trim().to_lowercase() == hint_command {
+            buy_hint(game, console)?;
+            continue;
+        }
+
+        // `: u32` we are annotating `guess`'s type, with an unsigned, 32-bit integer,
+        // so we know exactly what `parse_guess` hands back
+        let guess: u32 = match parse_guess(&line) {
+            Ok(num) => num,
+            Err(err) => {
+                console.write_line(&err.hint(locale, &config))?;
+                continue;
+            }
+        };
+
+        console.say(Message::YouGuessed, &[&guess])?;
+
+        match game.guess(guess) {
+            Outcome::TooSmall   => console.say(Message::TooSmall, &[])?,
+            Outcome::TooBig     => console.say(Message::TooBig, &[])?,
+            Outcome::Win        => console.say(Message::YouWin, &[])?,
+            Outcome::OutOfRange => {
+                console.say(Message::OutOfRange, &[&config.min, &config.max])?;
+                continue;
+            }
+        }
+
+        if game.hints().is_some() && game.state() == State::Playing {
+            describe_closeness(game, console)?;
+        }
+
+        match (game.state(), game.attempts_left()) {
+            (State::Lost, _) => console.say(Message::YouLose, &[&game.secret()])?,
+            (State::Playing, Some(1)) => console.say(Message::OneAttemptLeft, &[])?,
+            (State::Playing, Some(left)) => console.say(Message::AttemptsLeft, &[&left])?,
+            _ => {}
+        }
+    }
+
+    Ok(game.state())
+}
+
+fn buy_hint<C: Console>(game: &mut Game, console: &mut C) -> Result<(), Error> {
+    let clue = match game.hint() {
+        Some(clue) => clue,
+        None => {
+            console.say(Message::NoHintsLeft, &[])?;
+            return Ok(());
+        }
+    };
+
+    match clue {
+        Clue::Even(true) => console.say(Message::HintEven, &[])?,
+        Clue::Even(false) => console.say(Message::HintOdd, &[])?,
+        Clue::DivisibleBy(n, true) => console.say(Message::HintDivisible, &[&n])?,
+        Clue::DivisibleBy(n, false) => console.say(Message::HintNotDivisible, &[&n])?,
+    }
+
+    let left = game.hints().map(|hints| hints.remaining()).unwrap_or(0);
+    console.say(Message::HintCost, &[&hint::COST, &left])?;
+    Ok(())
+}
+
+fn describe_closeness<C: Console>(game: &Game, console: &mut C) -> Result<(), Error> {
+    let attempts = game.attempts();
+    let guess = attempts[attempts.len() - 1];
+
+    console.say(match Band::new(game.config(), guess, game.secret()) {
+        Band::Hot  => Message::Hot,
+        Band::Warm => Message::Warm,
+        Band::Cold => Message::Cold,
+    }, &[])?;
+
+    if attempts.len() > 1 {
+        console.say(match Trend::new(attempts[attempts.len() - 2], guess, game.secret()) {
+            Trend::Warmer => Message::Warmer,
+            Trend::Colder => Message::Colder,
+            Trend::Same   => Message::SameDistance,
+        }, &[])?;
+    }
+
+    Ok(())
+}
@@ -1,77 +1,190 @@
-// Like `use rand`, so can use `rand::<method>` anywhere
-// and its in our Cargo.toml's dependencies
-extern crate rand;
+extern crate guessing_game;
 
-use std::io;
-use rand::Rng;
-// Bring `Ordering` into scope
-use std::cmp::Ordering;
+use std::env;
+use std::path::Path;
+use std::process;
+use std::time::Instant;
+
+use guessing_game::{Config, Console, Error, Game, Locale, Message, Quiet, State, Terminal};
+use guessing_game::cli::{self, Command, Format, Options};
+use guessing_game::scores::{self, Record};
+use guessing_game::server::Server;
+use guessing_game::solver::{self, Reverse};
+use guessing_game::transcript;
 
 fn main() {
-    println!("Guess the number!");
-
-    // Because we used `use rand::Rng`, the `gen_range` method is available
-    // TODO I don't quite get this.
-    // `thread_rng()` requires `rand::Rng` be in scope, as methods are defined
-    // on "traits" for the method to work, and the trait needs to be in scope
-    // ???
-    let secret_number = rand::thread_rng().gen_range(1, 101);
-
-    // Loops indefinitely until `break`
-    loop {
-        println!("Please input your guess.");
-
-        // `let mut guess`
-        // Use `mut` to define this variable as mutable, as opposed
-        // to the immutable default
-
-        // `String::new()`
-        // String is a growable, UTF-8 encoded bit of text
-        // :: accesses the static method
-        let mut guess = String::new();
-
-        // Use the `stdin` function from `io` library.
-        // If we didn't `use std::io`, we could access via `std::io::stdin`
-        io::stdin() // Returns a handle to standard input, `std::io::Stdin`
-            .read_line(&mut guess) // Pass in our handle to the method
-                                   // We need to make this reference mutable as well, via `mut` here.
-            .ok() // `.read_line(&mut String)` returns
-                  // a value as well as populating the reference, of type `io::Result`.
-                  // `io::Result` type has an `ok()` method, meaning assume this is successful
-            .expect("Failed to read line"); // The `ok()` method returns a value with an `.expect()`
-                                            // method, calling `panic!` with the message if the Result
-                                            // failed.
-
-        // Even though we already have a `guess` variable,
-        // we can "shadow" the previous `guess` with this one, used often
-        // in type casting scenarios.
-
-        // `: u32` we are annotating `guess`'s type, with an unsigned, 32-bit integer,
-        // needed so parse() knows what to return
-        let guess: u32 = match guess.trim().parse() {
-            // a match statement from `parse()`s Result enum, which has
-            // Ok() and Err() variants, each called with additional information,
-            // kind of like `(err, value) => {}` JavaScript callbacks?
-            Ok(num) => num,
-            Err(_) => continue,
-        };
-
-        println!("You guessed: {}", guess); // `{}` substitutes the values in the string
-
-        // Ordering is an enum, looks like:
-        // enum Ordering {
-        //   Less,
-        //   Greater,
-        //   Equal,
-        // }
-        match guess.cmp(&secret_number) {
-            Ordering::Less    => println!("Too small!"),
-            Ordering::Greater => println!("Too big!"),
-            // Break when you actually win
-            Ordering::Equal   => {
-                println!("You win!");
-                break;
-            }
+    let options = match Options::from_env() {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("error: {}\n\n{}", err, cli::USAGE);
+            process::exit(cli::EXIT_USAGE);
+        }
+    };
+
+    if options.help {
+        println!("{}", cli::USAGE);
+        return;
+    }
+
+    let scores_file = options.scores_file.clone()
+        .unwrap_or_else(|| Path::new(scores::DEFAULT_PATH).to_path_buf());
+
+    match options.command {
+        Command::Play => play(&options, &scores_file),
+        Command::Scores => print_scores(&scores_file, locale(&options)),
+        Command::Reverse => reverse(&options),
+        Command::Auto => auto(&options),
+        Command::Serve => serve(&options),
+    }
+}
+
+fn play(options: &Options, scores_file: &Path) {
+    // Every round is seeded, so printing the seed is enough to replay it
+    let seed = options.seed.unwrap_or_else(guessing_game::random_seed);
+    let mut game = Game::seeded(options.config, seed);
+    if let Some(budget) = options.hints {
+        game = game.with_hints(budget);
+    }
+
+    if options.format == Format::Json {
+        return play_batch(game, seed);
+    }
+
+    let mut terminal = terminal(options);
+
+    let started = Instant::now();
+    let state = terminal.say(Message::Seed, &[&seed])
+        .map_err(Error::from)
+        .and_then(|_| guessing_game::play(&mut game, &mut terminal))
+        // Giving up half way isn't a finished round, so it isn't scored
+        .unwrap_or_else(|err| quit(&err, &options.config, &mut terminal));
+
+    let record = Record {
+        player: options.player.clone()
+            .or_else(|| env::var("USER").ok())
+            .unwrap_or_else(|| "anonymous".to_string()),
+        config: options.config,
+        attempts: game.attempts().len() as u32,
+        hints: game.hints_used(),
+        elapsed: started.elapsed(),
+        seed,
+        won: state == State::Won,
+    };
+
+    // Losing the score isn't worth losing the game over
+    if let Err(err) = scores::append(scores_file, &record) {
+        eprintln!("warning: couldn't save your score to {}: {}", scores_file.display(), err);
+    }
+}
+
+/// Plays without any of the chatter, then prints the transcript. Batch runs
+/// are for testing, so they aren't scored.
+fn play_batch(mut game: Game, seed: u64) {
+    let result = guessing_game::play(&mut game, &mut Quiet(Terminal::new()));
+
+    println!("{}", transcript::to_json(&game, Some(seed)));
+
+    match result {
+        Ok(_) => {}
+        Err(Error::Eof) => process::exit(cli::EXIT_EOF),
+        Err(err) => {
+            eprintln!("error: {}", err);
+            process::exit(cli::EXIT_IO);
+        }
+    }
+}
+
+fn print_scores(scores_file: &Path, locale: Locale) {
+    let records = match scores::load(scores_file) {
+        Ok(records) => records,
+        Err(err) => {
+            eprintln!("error: couldn't read {}: {}", scores_file.display(), err);
+            process::exit(1);
+        }
+    };
+
+    for line in scores::report(&records, locale) {
+        println!("{}", line);
+    }
+}
+
+fn reverse(options: &Options) {
+    let mut terminal = terminal(options);
+
+    match solver::reverse(&options.config, &mut terminal) {
+        Ok(Reverse::Guessed(_)) => {}
+        Ok(Reverse::Cheated(_)) => process::exit(cli::EXIT_CHEATED),
+        Err(err) => quit(&err, &options.config, &mut terminal),
+    }
+}
+
+fn auto(options: &Options) {
+    let seed = options.seed.unwrap_or_else(guessing_game::random_seed);
+    let mut game = Game::seeded(options.config, seed);
+
+    let state = solver::auto_play(&mut game);
+
+    if options.format == Format::Json {
+        println!("{}", transcript::to_json(&game, Some(seed)));
+        return;
+    }
+
+    let locale = locale(options);
+    println!("{}", locale.format(Message::Seed, &[&seed]));
+    for guess in game.attempts() {
+        println!("{}", locale.format(Message::AutoGuess, &[guess]));
+    }
+
+    match state {
+        State::Won => println!("{}", locale.format(Message::AutoFound, &[
+            &game.secret(),
+            &game.attempts().len(),
+            &solver::worst_case(&options.config),
+        ])),
+        _ => println!("{}", locale.format(Message::AutoLost, &[&game.secret()])),
+    }
+}
+
+fn locale(options: &Options) -> Locale {
+    options.lang.unwrap_or_else(Locale::from_env)
+}
+
+fn terminal(options: &Options) -> Terminal {
+    Terminal::new().with_locale(locale(options))
+}
+
+/// Ends the program after the console ran out or broke.
+fn quit(err: &Error, config: &Config, terminal: &mut Terminal) -> ! {
+    match *err {
+        Error::Eof => {
+            let _ = terminal.write_line(&err.hint(terminal.locale(), config));
+            process::exit(cli::EXIT_EOF);
+        }
+        _ => {
+            eprintln!("error: {}", err);
+            process::exit(cli::EXIT_IO);
+        }
+    }
+}
+
+fn serve(options: &Options) {
+    let seed = options.seed.unwrap_or_else(guessing_game::random_seed);
+    let listen = options.listen.as_deref().unwrap_or(cli::DEFAULT_LISTEN);
+
+    let locale = locale(options);
+
+    let result = Server::bind(listen, Game::seeded(options.config, seed)).and_then(|server| {
+        println!("{}", locale.format(Message::Seed, &[&seed]));
+        println!("{}", locale.format(Message::Listening, &[&server.local_addr()?]));
+        server.with_locale(locale).run()
+    });
+
+    match result {
+        Ok(Some(winner)) => println!("{}", locale.format(Message::ServerWinner, &[&winner])),
+        Ok(None) => println!("{}", locale.format(Message::ServerNobody, &[])),
+        Err(err) => {
+            eprintln!("error: {}", err);
+            process::exit(cli::EXIT_IO);
         }
     }
 }
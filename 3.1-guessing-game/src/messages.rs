@@ -0,0 +1,334 @@
+//! Everything the game says to players, in every language it speaks.
+//!
+//! Each `Message` has a template per `Locale`. Templates refer to their
+//! arguments by position, `{0}`, `{1}` and so on, so a translation can put
+//! them in whatever order its grammar wants.
+
+use std::env;
+use std::fmt;
+use std::str::FromStr;
+
+macro_rules! messages {
+    ($($name:ident),* $(,)*) => {
+        /// A key into the message catalog.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum Message {
+            $($name),*
+        }
+
+        impl Message {
+            /// Every message, so nothing can be left out of a catalog
+            /// without the tests noticing.
+            pub const ALL: &'static [Message] = &[$(Message::$name),*];
+
+            pub fn key(&self) -> &'static str {
+                match *self {
+                    $(Message::$name => stringify!($name)),*
+                }
+            }
+        }
+    }
+}
+
+messages! {
+    // Playing a round
+    GuessTheNumber,
+    Range,
+    OneAttempt,
+    Attempts,
+    InputGuess,
+    YouGuessed,
+    TooSmall,
+    TooBig,
+    YouWin,
+    OutOfRange,
+    YouLose,
+    OneAttemptLeft,
+    AttemptsLeft,
+    Seed,
+
+    // Hint mode
+    HintCommand,
+    NoHintsLeft,
+    HintEven,
+    HintOdd,
+    HintDivisible,
+    HintNotDivisible,
+    HintCost,
+    Hot,
+    Warm,
+    Cold,
+    Warmer,
+    Colder,
+    SameDistance,
+
+    // Bad input
+    NoMoreInput,
+    BrokenConsole,
+    EmptyGuess,
+    NotANumber,
+    NegativeGuess,
+    HugeGuess,
+
+    // The reverse game
+    ReverseIntro,
+    ReverseAsk,
+    ReverseBadAnswer,
+    ReverseCheater,
+    ReverseGotIt,
+    AnswerHigher,
+    AnswerLower,
+    AnswerCorrect,
+
+    // The auto-player
+    AutoGuess,
+    AutoFound,
+    AutoLost,
+
+    // Multiplayer
+    WhatsYourName,
+    Welcome,
+    PlayerWins,
+    NobodyWins,
+    Listening,
+    ServerWinner,
+    ServerNobody,
+
+    // Scores
+    NoRounds,
+    PlayersHeading,
+    LeaderboardRow,
+    PlayerStats,
+}
+
+/// A language the game can be played in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Locale {
+    #[default]
+    English,
+    Spanish,
+}
+
+impl Locale {
+    pub const ALL: [Locale; 2] = [Locale::English, Locale::Spanish];
+
+    /// The language code, as used by `--lang` and `$LANG`.
+    pub fn code(&self) -> &'static str {
+        match *self {
+            Locale::English => "en",
+            Locale::Spanish => "es",
+        }
+    }
+
+    /// Picks the locale for a POSIX locale name like `es_ES.UTF-8`, going by
+    /// its language alone.
+    pub fn from_posix(name: &str) -> Option<Locale> {
+        let language = name.split(&['_', '.', '@', '-'][..])
+            .next()
+            .unwrap_or("");
+
+        Locale::ALL.iter().cloned()
+            .find(|locale| locale.code().eq_ignore_ascii_case(language))
+    }
+
+    /// The locale the environment asks for, checking `$LC_ALL`,
+    /// `$LC_MESSAGES` and `$LANG` in the usual order of precedence. Falls
+    /// back to English for anything we don't speak.
+    pub fn from_env() -> Locale {
+        ["LC_ALL", "LC_MESSAGES", "LANG"].iter()
+            .filter_map(|var| env::var(var).ok())
+            .find(|value| !value.is_empty())
+            .and_then(|value| Locale::from_posix(&value))
+            .unwrap_or_default()
+    }
+
+    /// This locale's template for `message`, if it has one.
+    pub fn template(&self, message: Message) -> Option<&'static str> {
+        let catalog = match *self {
+            Locale::English => ENGLISH,
+            Locale::Spanish => SPANISH,
+        };
+
+        catalog.iter()
+            .find(|&&(key, _)| key == message)
+            .map(|&(_, template)| template)
+    }
+
+    /// `message` in this locale with `args` filled in. Anything missing from
+    /// a translation falls back to English.
+    pub fn format(&self, message: Message, args: &[&dyn fmt::Display]) -> String {
+        let template = self.template(message)
+            .or_else(|| Locale::English.template(message))
+            .unwrap_or_else(|| message.key());
+
+        fill(template, args)
+    }
+}
+
+impl FromStr for Locale {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Locale, String> {
+        Locale::from_posix(s).ok_or_else(|| {
+            let codes: Vec<&str> = Locale::ALL.iter().map(|locale| locale.code()).collect();
+            format!("unknown language `{}`, expected one of {}", s, codes.join(", "))
+        })
+    }
+}
+
+/// Replaces each `{n}` in `template` with `args[n]`, wherever in the
+/// template it turns up.
+pub fn fill(template: &str, args: &[&dyn fmt::Display]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let arg = rest.find('}')
+            .and_then(|end| rest[1..end].parse::<usize>().ok().map(|index| (index, end)));
+        match arg {
+            Some((index, end)) if index < args.len() => {
+                out.push_str(&args[index].to_string());
+                rest = &rest[end + 1..];
+            }
+            // Not a placeholder we can fill, so leave it be
+            _ => {
+                out.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    out.push_str(rest);
+    out
+}
+
+const ENGLISH: &[(Message, &str)] = &[
+    (Message::GuessTheNumber, "Guess the number!"),
+    (Message::Range, "It's between {0} and {1}."),
+    (Message::OneAttempt, "You have 1 attempt."),
+    (Message::Attempts, "You have {0} attempts."),
+    (Message::InputGuess, "Please input your guess."),
+    (Message::YouGuessed, "You guessed: {0}"),
+    (Message::TooSmall, "Too small!"),
+    (Message::TooBig, "Too big!"),
+    (Message::YouWin, "You win!"),
+    (Message::OutOfRange, "Please guess a number between {0} and {1}."),
+    (Message::YouLose, "You lose! The number was {0}."),
+    (Message::OneAttemptLeft, "1 attempt left."),
+    (Message::AttemptsLeft, "{0} attempts left."),
+    (Message::Seed, "Seed: {0}"),
+
+    (Message::HintCommand, "hint"),
+    (Message::NoHintsLeft, "Sorry, no hints left."),
+    (Message::HintEven, "Hint: the number is even."),
+    (Message::HintOdd, "Hint: the number is odd."),
+    (Message::HintDivisible, "Hint: the number is divisible by {0}."),
+    (Message::HintNotDivisible, "Hint: the number is not divisible by {0}."),
+    (Message::HintCost, "That cost you {0} points, {1} hints left."),
+    (Message::Hot, "You're hot!"),
+    (Message::Warm, "You're warm."),
+    (Message::Cold, "You're cold."),
+    (Message::Warmer, "Warmer than your last guess."),
+    (Message::Colder, "Colder than your last guess."),
+    (Message::SameDistance, "Just as far off as your last guess."),
+
+    (Message::NoMoreInput, "No more input, giving up."),
+    (Message::BrokenConsole, "Something went wrong talking to you: {0}"),
+    (Message::EmptyGuess, "Please type a number between {0} and {1}."),
+    (Message::NotANumber, "\"{0}\" isn't a number, please type a whole number between {1} and {2} using digits."),
+    (Message::NegativeGuess, "The number is never negative, it's between {0} and {1}."),
+    (Message::HugeGuess, "That's way too big, the number is between {0} and {1}."),
+
+    (Message::ReverseIntro, "Think of a number between {0} and {1} and I'll guess it."),
+    (Message::ReverseAsk, "Is it {0}? (higher/lower/correct)"),
+    (Message::ReverseBadAnswer, "Please answer higher, lower or correct."),
+    (Message::ReverseCheater, "That can't be right, your number would have to be at least {0} and at most {1}. Cheater!"),
+    (Message::ReverseGotIt, "Got it in {0} guesses!"),
+    (Message::AnswerHigher, "higher|h"),
+    (Message::AnswerLower, "lower|l"),
+    (Message::AnswerCorrect, "correct|c|yes|y"),
+
+    (Message::AutoGuess, "I guess {0}."),
+    (Message::AutoFound, "Found {0} in {1} guesses, it never takes more than {2}."),
+    (Message::AutoLost, "Ran out of attempts, the number was {0}."),
+
+    (Message::WhatsYourName, "What's your name?"),
+    (Message::Welcome, "Welcome, {0}!"),
+    (Message::PlayerWins, "{0} wins! The number was {1}."),
+    (Message::NobodyWins, "Out of attempts, nobody wins! The number was {0}."),
+    (Message::Listening, "Listening on {0}"),
+    (Message::ServerWinner, "{0} won!"),
+    (Message::ServerNobody, "Nobody won."),
+
+    (Message::NoRounds, "No rounds played yet."),
+    (Message::PlayersHeading, "players"),
+    (Message::LeaderboardRow, "{0}. {1} {2} points ({3} attempts, {4} hints) {5}s  (seed {6})"),
+    (Message::PlayerStats, "{0} {1} won of {2} played, {3} attempts on average, best streak {4}"),
+];
+
+const SPANISH: &[(Message, &str)] = &[
+    (Message::GuessTheNumber, "¡Adivina el número!"),
+    (Message::Range, "Está entre {0} y {1}."),
+    (Message::OneAttempt, "Tienes 1 intento."),
+    (Message::Attempts, "Tienes {0} intentos."),
+    (Message::InputGuess, "Escribe tu número."),
+    (Message::YouGuessed, "Has dicho: {0}"),
+    (Message::TooSmall, "¡Demasiado pequeño!"),
+    (Message::TooBig, "¡Demasiado grande!"),
+    (Message::YouWin, "¡Has ganado!"),
+    (Message::OutOfRange, "Elige un número entre {0} y {1}."),
+    (Message::YouLose, "¡Has perdido! El número era {0}."),
+    (Message::OneAttemptLeft, "Te queda 1 intento."),
+    (Message::AttemptsLeft, "Te quedan {0} intentos."),
+    (Message::Seed, "Semilla: {0}"),
+
+    (Message::HintCommand, "pista"),
+    (Message::NoHintsLeft, "Lo siento, no quedan pistas."),
+    (Message::HintEven, "Pista: el número es par."),
+    (Message::HintOdd, "Pista: el número es impar."),
+    (Message::HintDivisible, "Pista: el número es divisible entre {0}."),
+    (Message::HintNotDivisible, "Pista: el número no es divisible entre {0}."),
+    (Message::HintCost, "Te ha costado {0} puntos, quedan {1} pistas."),
+    (Message::Hot, "¡Te quemas!"),
+    (Message::Warm, "Caliente."),
+    (Message::Cold, "Frío."),
+    (Message::Warmer, "Más cerca que la última vez."),
+    (Message::Colder, "Más lejos que la última vez."),
+    (Message::SameDistance, "Igual de lejos que la última vez."),
+
+    (Message::NoMoreInput, "No hay más entrada, me rindo."),
+    (Message::BrokenConsole, "Algo ha fallado al hablar contigo: {0}"),
+    (Message::EmptyGuess, "Escribe un número entre {0} y {1}."),
+    (Message::NotANumber, "\"{0}\" no es un número, escribe un número entero entre {1} y {2} con cifras."),
+    (Message::NegativeGuess, "El número nunca es negativo, está entre {0} y {1}."),
+    (Message::HugeGuess, "Eso es demasiado grande, el número está entre {0} y {1}."),
+
+    (Message::ReverseIntro, "Piensa un número entre {0} y {1} y yo lo adivinaré."),
+    (Message::ReverseAsk, "¿Es el {0}? (mayor/menor/correcto)"),
+    (Message::ReverseBadAnswer, "Responde mayor, menor o correcto."),
+    (Message::ReverseCheater, "Eso no puede ser, tu número tendría que ser como mínimo {0} y como máximo {1}. ¡Tramposo!"),
+    (Message::ReverseGotIt, "¡Lo he adivinado en {0} intentos!"),
+    (Message::AnswerHigher, "mayor|más|mas"),
+    (Message::AnswerLower, "menor|menos"),
+    (Message::AnswerCorrect, "correcto|sí|si"),
+
+    (Message::AutoGuess, "Digo {0}."),
+    (Message::AutoFound, "He encontrado el {0} en {1} intentos, nunca hacen falta más de {2}."),
+    (Message::AutoLost, "Se acabaron los intentos, el número era {0}."),
+
+    (Message::WhatsYourName, "¿Cómo te llamas?"),
+    (Message::Welcome, "¡Bienvenido, {0}!"),
+    (Message::PlayerWins, "¡{0} gana! El número era {1}."),
+    (Message::NobodyWins, "Se acabaron los intentos, ¡nadie gana! El número era {0}."),
+    (Message::Listening, "Escuchando en {0}"),
+    (Message::ServerWinner, "¡{0} ha ganado!"),
+    (Message::ServerNobody, "Nadie ha ganado."),
+
+    (Message::NoRounds, "Todavía no se ha jugado ninguna partida."),
+    (Message::PlayersHeading, "jugadores"),
+    (Message::LeaderboardRow, "{0}. {1} {2} puntos ({3} intentos, {4} pistas) {5}s  (semilla {6})"),
+    (Message::PlayerStats, "{0} {1} ganadas de {2} jugadas, {3} intentos de media, mejor racha {4}"),
+];
@@ -0,0 +1,338 @@
+//! The high-score table, kept in a plain text file next to wherever the game
+//! is run from.
+//!
+//! The first line of the file says which format version the rest of it is
+//! in, so files written by older versions of the game can still be read after
+//! the format changes. Each line after that is one finished round, with
+//! tab-separated fields.
+
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io;
+use std::io::prelude::*;
+use std::io::BufReader;
+use std::path::Path;
+use std::time::Duration;
+
+use config::Config;
+use hint;
+use messages::{Locale, Message};
+
+/// Where scores are kept unless told otherwise.
+pub const DEFAULT_PATH: &str = "guessing_game_scores.txt";
+
+/// The version written into new files.
+pub const VERSION: u32 = 2;
+
+const HEADER_PREFIX: &str = "guessing_game scores v";
+
+/// How many rounds each leaderboard shows.
+const LEADERBOARD_SIZE: usize = 10;
+
+/// One finished round.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    pub player: String,
+    pub config: Config,
+    /// How many counted guesses were made.
+    pub attempts: u32,
+    /// How many clues were bought in hint mode.
+    pub hints: u32,
+    pub elapsed: Duration,
+    pub seed: u64,
+    pub won: bool,
+}
+
+impl Record {
+    /// The difficulty preset the round was played on, or "custom".
+    pub fn difficulty(&self) -> &'static str {
+        self.config.difficulty().map(|difficulty| difficulty.name()).unwrap_or("custom")
+    }
+
+    /// Attempts plus the cost of any clues. Lower is better.
+    pub fn score(&self) -> u32 {
+        self.attempts + self.hints * hint::COST
+    }
+}
+
+/// Reads every record from the scores file at `path`. A missing file just
+/// means nobody has played yet.
+pub fn load(path: &Path) -> io::Result<Vec<Record>> {
+    match File::open(path) {
+        Ok(file) => parse(BufReader::new(file)),
+        Err(ref err) if err.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(err) => Err(err),
+    }
+}
+
+/// Adds `record` to the end of the scores file at `path`, creating it if
+/// needed. A file written by an older version of the game is upgraded to the
+/// current format first.
+pub fn append(path: &Path, record: &Record) -> io::Result<()> {
+    if file_version(path)?.is_some_and(|version| version < VERSION) {
+        let records = load(path)?;
+        save(path, &records)?;
+    }
+
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+
+    if file.metadata()?.len() == 0 {
+        writeln!(file, "{}{}", HEADER_PREFIX, VERSION)?;
+    }
+
+    writeln!(file, "{}", format_record(record))
+}
+
+/// Replaces the scores file at `path` with `records` in the current format.
+pub fn save(path: &Path, records: &[Record]) -> io::Result<()> {
+    // Write everything out next to the real file and swap it in at the end,
+    // so a crash half way through can't lose anybody's scores
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+
+    {
+        let mut file = File::create(&temp)?;
+        writeln!(file, "{}{}", HEADER_PREFIX, VERSION)?;
+        for record in records {
+            writeln!(file, "{}", format_record(record))?;
+        }
+        file.sync_all()?;
+    }
+
+    fs::rename(&temp, path)
+}
+
+/// The format version of the scores file at `path`, if there is one.
+fn file_version(path: &Path) -> io::Result<Option<u32>> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(ref err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err),
+    };
+
+    match BufReader::new(file).lines().next() {
+        Some(header) => parse_header(&header?).map(Some),
+        None => Ok(None),
+    }
+}
+
+fn parse_header(header: &str) -> io::Result<u32> {
+    header.trim().strip_prefix(HEADER_PREFIX)
+        .and_then(|version| version.parse().ok())
+        .ok_or_else(|| invalid(format!("not a scores file, it starts with `{}`", header)))
+}
+
+/// Reads records from anything in the scores file format.
+pub fn parse<R: BufRead>(reader: R) -> io::Result<Vec<Record>> {
+    let mut lines = reader.lines();
+
+    let header = match lines.next() {
+        Some(header) => header?,
+        None => return Ok(Vec::new()),
+    };
+    let version = parse_header(&header)?;
+
+    // Each version gets its own parser. When the format changes, bump
+    // `VERSION`, add a parser for it and keep the old ones around so
+    // existing files still load.
+    let parse_line = match version {
+        1 => parse_v1,
+        2 => parse_v2,
+        _ => return Err(invalid(format!("scores file is version {}, but this game only \
+                                         understands up to version {}", version, VERSION))),
+    };
+
+    let mut records = Vec::new();
+    for (i, line) in lines.enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        // + 2 for the header and because people count from one
+        let record = parse_line(&line)
+            .map_err(|err| invalid(format!("line {}: {}", i + 2, err)))?;
+        records.push(record);
+    }
+
+    Ok(records)
+}
+
+fn format_record(record: &Record) -> String {
+    // Names are free text, so keep them from breaking the line up
+    let player: String = record.player.chars()
+        .map(|c| if c.is_control() { ' ' } else { c })
+        .collect();
+
+    format!("{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            player.trim(),
+            record.config.min,
+            record.config.max,
+            record.config.max_attempts.map(|max| max.to_string()).unwrap_or_else(|| "-".to_string()),
+            record.attempts,
+            record.hints,
+            record.elapsed.as_millis(),
+            record.seed,
+            if record.won { "won" } else { "lost" })
+}
+
+/// Version 1 is version 2 from before hint mode, so nobody bought clues.
+fn parse_v1(line: &str) -> Result<Record, String> {
+    let mut fields: Vec<&str> = line.split('\t').collect();
+    if fields.len() != 8 {
+        return Err(format!("expected 8 fields, found {}", fields.len()));
+    }
+
+    fields.insert(5, "0");
+    parse_v2_fields(&fields)
+}
+
+/// `player, min, max, max attempts (or -), attempts, hints, elapsed ms, seed,
+/// won|lost`
+fn parse_v2(line: &str) -> Result<Record, String> {
+    let fields: Vec<&str> = line.split('\t').collect();
+    if fields.len() != 9 {
+        return Err(format!("expected 9 fields, found {}", fields.len()));
+    }
+
+    parse_v2_fields(&fields)
+}
+
+fn parse_v2_fields(fields: &[&str]) -> Result<Record, String> {
+    Ok(Record {
+        player: fields[0].to_string(),
+        config: Config {
+            min: parse_field(fields[1], "min")?,
+            max: parse_field(fields[2], "max")?,
+            max_attempts: match fields[3] {
+                "-" => None,
+                max => Some(parse_field(max, "max attempts")?),
+            },
+        },
+        attempts: parse_field(fields[4], "attempts")?,
+        hints: parse_field(fields[5], "hints")?,
+        elapsed: Duration::from_millis(parse_field(fields[6], "elapsed time")?),
+        seed: parse_field(fields[7], "seed")?,
+        won: match fields[8] {
+            "won" => true,
+            "lost" => false,
+            other => return Err(format!("expected `won` or `lost`, found `{}`", other)),
+        },
+    })
+}
+
+fn parse_field<T: ::std::str::FromStr>(field: &str, name: &str) -> Result<T, String> {
+    field.parse().map_err(|_| format!("bad {} `{}`", name, field))
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// The best won rounds for each difficulty: lowest score first, then
+/// fastest.
+pub fn leaderboards(records: &[Record]) -> BTreeMap<&'static str, Vec<&Record>> {
+    let mut boards = BTreeMap::new();
+
+    for record in records.iter().filter(|record| record.won) {
+        boards.entry(record.difficulty()).or_insert_with(Vec::new).push(record);
+    }
+
+    for board in boards.values_mut() {
+        board.sort_by_key(|record| (record.score(), record.elapsed));
+        board.truncate(LEADERBOARD_SIZE);
+    }
+
+    boards
+}
+
+/// Aggregate statistics over some rounds, in the order they were played.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stats {
+    pub rounds: u32,
+    pub wins: u32,
+    /// Average attempts across the rounds that were won.
+    pub average_attempts: Option<f64>,
+    /// The most rounds won in a row.
+    pub best_streak: u32,
+}
+
+impl Stats {
+    pub fn new<'a, I: IntoIterator<Item = &'a Record>>(records: I) -> Stats {
+        let mut stats = Stats { rounds: 0, wins: 0, average_attempts: None, best_streak: 0 };
+        let mut total_attempts = 0;
+        let mut streak = 0;
+
+        for record in records {
+            stats.rounds += 1;
+            if record.won {
+                stats.wins += 1;
+                total_attempts += record.attempts;
+                streak += 1;
+                stats.best_streak = stats.best_streak.max(streak);
+            } else {
+                streak = 0;
+            }
+        }
+
+        if stats.wins > 0 {
+            stats.average_attempts = Some(total_attempts as f64 / stats.wins as f64);
+        }
+
+        stats
+    }
+}
+
+/// Statistics for each player, by name.
+pub fn stats_by_player(records: &[Record]) -> BTreeMap<&str, Stats> {
+    let mut by_player = BTreeMap::new();
+    for record in records {
+        by_player.entry(record.player.as_str()).or_insert_with(Vec::new).push(record);
+    }
+
+    by_player.into_iter()
+        .map(|(player, records)| (player, Stats::new(records)))
+        .collect()
+}
+
+/// The leaderboards and statistics, ready to print.
+pub fn report(records: &[Record], locale: Locale) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    if records.is_empty() {
+        lines.push(locale.format(Message::NoRounds, &[]));
+        return lines;
+    }
+
+    // Padding is done up front, so translations don't have to know about it
+    for (difficulty, board) in leaderboards(records) {
+        lines.push(format!("== {} ==", difficulty));
+        for (rank, record) in board.iter().enumerate() {
+            lines.push(locale.format(Message::LeaderboardRow, &[
+                &format!("{:>2}", rank + 1),
+                &format!("{:<16}", record.player),
+                &format!("{:>3}", record.score()),
+                &record.attempts,
+                &record.hints,
+                &format!("{:>7.1}", record.elapsed.as_secs_f64()),
+                &record.seed,
+            ]));
+        }
+        lines.push(String::new());
+    }
+
+    lines.push(format!("== {} ==", locale.format(Message::PlayersHeading, &[])));
+    for (player, stats) in stats_by_player(records) {
+        let average = stats.average_attempts
+            .map(|average| format!("{:.1}", average))
+            .unwrap_or_else(|| "-".to_string());
+        lines.push(locale.format(Message::PlayerStats, &[
+            &format!("{:<16}", player),
+            &stats.wins,
+            &stats.rounds,
+            &average,
+            &stats.best_streak,
+        ]));
+    }
+
+    lines
+}
@@ -0,0 +1,239 @@
+//! Multiplayer over TCP: everyone who connects races to guess the same
+//! secret, and the whole table hears about it when somebody wins.
+//!
+//! The protocol is plain lines of text, so `nc localhost 7878` is a perfectly
+//! good client. The first line a player sends is their name, every line after
+//! that is a guess.
+
+use std::io;
+use std::io::prelude::*;
+use std::io::BufReader;
+use std::mem;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, Shutdown, SocketAddr, TcpListener, TcpStream,
+               ToSocketAddrs};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use console::Console;
+use error::{Error, parse_guess};
+use game::{Game, Outcome, State};
+use messages::{Locale, Message};
+
+/// A `Console` over one player's connection.
+pub struct Connection {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+    locale: Locale,
+}
+
+impl Connection {
+    pub fn new(stream: TcpStream, locale: Locale) -> io::Result<Connection> {
+        // Lines are tiny and someone is waiting on each one
+        stream.set_nodelay(true)?;
+        Ok(Connection {
+            reader: BufReader::new(stream.try_clone()?),
+            writer: stream,
+            locale,
+        })
+    }
+}
+
+impl Console for Connection {
+    fn read_line(&mut self) -> io::Result<Option<String>> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        Ok(Some(line.trim_end_matches(&['\r', '\n'][..]).to_string()))
+    }
+
+    fn write_line(&mut self, line: &str) -> io::Result<()> {
+        writeln!(self.writer, "{}", line)
+    }
+
+    fn locale(&self) -> Locale {
+        self.locale
+    }
+}
+
+/// Everything the players share.
+struct Table {
+    game: Game,
+    /// Every connection, so we can tell everyone when the round ends.
+    players: Vec<TcpStream>,
+    locale: Locale,
+    over: bool,
+    winner: Option<String>,
+}
+
+impl Table {
+    /// Ends the round, and gives back everyone who needs telling. Tell them
+    /// with `hang_up` once the table is unlocked, so nobody slow to read
+    /// holds everyone else up.
+    fn end(&mut self) -> Vec<TcpStream> {
+        self.over = true;
+        mem::take(&mut self.players)
+    }
+}
+
+/// Sends `line` to every player and hangs up on them.
+fn hang_up(players: Vec<TcpStream>, line: &str) {
+    for mut player in players {
+        // Whoever already left doesn't need telling
+        let _ = writeln!(player, "{}", line);
+        let _ = player.shutdown(Shutdown::Both);
+    }
+}
+
+/// A round of the guessing game that players join over TCP.
+pub struct Server {
+    listener: TcpListener,
+    table: Arc<Mutex<Table>>,
+}
+
+impl Server {
+    /// Listens on `addr`, ready to host `game`. Use port 0 to let the OS
+    /// pick a free port, then ask `local_addr` which one it was.
+    ///
+    /// Any attempt limit in the game's `Config` is shared by the whole table.
+    pub fn bind<A: ToSocketAddrs>(addr: A, game: Game) -> io::Result<Server> {
+        Ok(Server {
+            listener: TcpListener::bind(addr)?,
+            table: Arc::new(Mutex::new(Table {
+                game,
+                players: Vec::new(),
+                locale: Locale::default(),
+                over: false,
+                winner: None,
+            })),
+        })
+    }
+
+    /// Talks to every player in `locale`.
+    pub fn with_locale(self, locale: Locale) -> Server {
+        self.table.lock().unwrap().locale = locale;
+        self
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Lets players in until the round is over, then returns the winner's
+    /// name, or `None` if the table ran out of attempts.
+    pub fn run(self) -> io::Result<Option<String>> {
+        let mut addr = self.local_addr()?;
+        // We connect to ourselves to stop accepting, which needs an address
+        // we can actually reach
+        if addr.ip().is_unspecified() {
+            addr.set_ip(match addr.ip() {
+                IpAddr::V4(_) => IpAddr::V4(Ipv4Addr::LOCALHOST),
+                IpAddr::V6(_) => IpAddr::V6(Ipv6Addr::LOCALHOST),
+            });
+        }
+
+        let mut handles = Vec::new();
+        for stream in self.listener.incoming() {
+            if self.table.lock().unwrap().over {
+                break;
+            }
+
+            // One player failing to connect shouldn't stop everyone else
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(_) => continue,
+            };
+
+            let table = self.table.clone();
+            handles.push(thread::spawn(move || {
+                let ended = serve_player(stream, &table).unwrap_or(false);
+                if ended {
+                    // Wake up the `incoming()` loop so it notices
+                    let _ = TcpStream::connect(addr);
+                }
+            }));
+        }
+
+        for handle in handles {
+            let _ = handle.join();
+        }
+
+        let table = self.table.lock().unwrap();
+        Ok(table.winner.clone())
+    }
+}
+
+/// Plays one player's side of the round. Returns whether it was this player
+/// who ended it.
+fn serve_player(stream: TcpStream, table: &Mutex<Table>) -> Result<bool, Error> {
+    let (config, locale) = {
+        let mut table = table.lock().unwrap();
+        if table.over {
+            return Ok(false);
+        }
+        table.players.push(stream.try_clone()?);
+        (*table.game.config(), table.locale)
+    };
+    let mut connection = Connection::new(stream, locale)?;
+
+    connection.say(Message::GuessTheNumber, &[])?;
+    connection.say(Message::Range, &[&config.min, &config.max])?;
+    connection.say(Message::WhatsYourName, &[])?;
+
+    let name = match connection.read_line()? {
+        Some(name) => name.trim().to_string(),
+        None => return Err(Error::Eof),
+    };
+    connection.say(Message::Welcome, &[&name])?;
+
+    loop {
+        connection.say(Message::InputGuess, &[])?;
+
+        let guess = match connection.read_line()? {
+            Some(line) => parse_guess(&line),
+            None => return Err(Error::Eof),
+        };
+        let guess = match guess {
+            Ok(guess) => guess,
+            Err(err) => {
+                connection.write_line(&err.hint(locale, &config))?;
+                continue;
+            }
+        };
+
+        // Only hold the table for as long as the guess takes, never while
+        // writing to somebody
+        let (outcome, ending) = {
+            let mut table = table.lock().unwrap();
+            if table.over {
+                return Ok(false);
+            }
+
+            let outcome = table.game.guess(guess);
+            let secret = table.game.secret();
+            let ending = if outcome == Outcome::Win {
+                table.winner = Some(name.clone());
+                Some((table.end(), locale.format(Message::PlayerWins, &[&name, &secret])))
+            } else if table.game.state() == State::Lost {
+                Some((table.end(), locale.format(Message::NobodyWins, &[&secret])))
+            } else {
+                None
+            };
+            (outcome, ending)
+        };
+
+        match outcome {
+            Outcome::TooSmall => connection.say(Message::TooSmall, &[])?,
+            Outcome::TooBig => connection.say(Message::TooBig, &[])?,
+            Outcome::OutOfRange => {
+                connection.say(Message::OutOfRange, &[&config.min, &config.max])?
+            }
+            Outcome::Win => {}
+        }
+
+        if let Some((players, line)) = ending {
+            hang_up(players, &line);
+            return Ok(true);
+        }
+    }
+}
@@ -0,0 +1,158 @@
+//! A player that always wins: binary search over the numbers that are still
+//! possible. Used both as an auto-player for `Game` and for the reverse mode,
+//! where the human thinks of a number and the computer guesses it.
+
+use config::Config;
+use console::Console;
+use error::Error;
+use game::{Game, Outcome, State};
+use messages::Message;
+
+/// The answers given so far can't all be true.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Inconsistent {
+    /// The secret would have to be at least this...
+    pub at_least: i64,
+    /// ...and at most this, which is smaller.
+    pub at_most: i64,
+}
+
+/// Narrows down the secret one guess at a time.
+#[derive(Debug, Clone)]
+pub struct Solver {
+    // Signed and wider than `u32` so `max + 1` and `min - 1` can't overflow
+    low: i64,
+    high: i64,
+}
+
+impl Solver {
+    pub fn new(config: &Config) -> Solver {
+        Solver {
+            low: config.min as i64,
+            high: config.max as i64,
+        }
+    }
+
+    /// The guess that splits the remaining candidates in half, or `None` if
+    /// there aren't any left.
+    pub fn next_guess(&self) -> Option<u32> {
+        if self.low > self.high {
+            None
+        } else {
+            Some((self.low + (self.high - self.low) / 2) as u32)
+        }
+    }
+
+    /// Narrows the candidates down using what `guess` told us.
+    ///
+    /// # Failures
+    ///
+    /// `Inconsistent` if no number fits every answer so far, i.e. whoever
+    /// is answering is cheating.
+    pub fn record(&mut self, guess: u32, outcome: Outcome) -> Result<(), Inconsistent> {
+        let guess = guess as i64;
+
+        match outcome {
+            Outcome::TooSmall => self.low = self.low.max(guess + 1),
+            Outcome::TooBig => self.high = self.high.min(guess - 1),
+            Outcome::Win => {
+                self.low = self.low.max(guess);
+                self.high = self.high.min(guess);
+            }
+            // Says nothing about the secret
+            Outcome::OutOfRange => return Ok(()),
+        }
+
+        if self.low > self.high {
+            Err(Inconsistent { at_least: self.low, at_most: self.high })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// The most guesses binary search can need to find any secret allowed by
+/// `config`: ⌈log2(n + 1)⌉ for `n` possible secrets, which is ⌈log2(n)⌉
+/// unless `n` is a power of two.
+pub fn worst_case(config: &Config) -> u32 {
+    let candidates = config.max as u64 - config.min as u64 + 1;
+    64 - candidates.leading_zeros()
+}
+
+/// Plays `game` to the end with binary search. Wins whenever the game allows
+/// at least `worst_case` attempts.
+pub fn auto_play(game: &mut Game) -> State {
+    let mut solver = Solver::new(game.config());
+
+    while game.state() == State::Playing {
+        let guess = solver.next_guess().expect("the secret is always a candidate");
+        let outcome = game.guess(guess);
+        solver.record(guess, outcome).expect("the game never lies");
+    }
+
+    game.state()
+}
+
+/// How a reverse round ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reverse {
+    /// Found the number after this many guesses.
+    Guessed(u32),
+    /// The answers contradicted each other.
+    Cheated(Inconsistent),
+}
+
+/// The reverse game: the player thinks of a number allowed by `config` and
+/// answers "higher", "lower" or "correct" (in the console's language) to the
+/// computer's guesses.
+///
+/// # Failures
+///
+/// `Error::Eof` if the input runs out before the number is found, or
+/// `Error::Io` if the console fails.
+pub fn reverse<C: Console>(config: &Config, console: &mut C) -> Result<Reverse, Error> {
+    let mut solver = Solver::new(config);
+    let mut guesses = 0;
+    let locale = console.locale();
+    let answers = |message| locale.format(message, &[]);
+    let (higher, lower, correct) = (answers(Message::AnswerHigher),
+                                    answers(Message::AnswerLower),
+                                    answers(Message::AnswerCorrect));
+
+    console.say(Message::ReverseIntro, &[&config.min, &config.max])?;
+
+    loop {
+        let guess = solver.next_guess().expect("inconsistent answers end the game");
+        guesses += 1;
+        console.say(Message::ReverseAsk, &[&guess])?;
+
+        let outcome = loop {
+            let answer = match console.read_line()? {
+                Some(line) => line.trim().to_lowercase(),
+                None => return Err(Error::Eof),
+            };
+            let is = |words: &str| words.split('|').any(|word| word == answer);
+
+            // Outcomes are from the guesser's point of view, so "higher"
+            // means the guess was too small
+            if is(&higher) {
+                break Outcome::TooSmall;
+            } else if is(&lower) {
+                break Outcome::TooBig;
+            } else if is(&correct) {
+                break Outcome::Win;
+            }
+            console.say(Message::ReverseBadAnswer, &[])?;
+        };
+
+        if let Err(inconsistent) = solver.record(guess, outcome) {
+            console.say(Message::ReverseCheater, &[&inconsistent.at_least, &inconsistent.at_most])?;
+            return Ok(Reverse::Cheated(inconsistent));
+        }
+
+        if outcome == Outcome::Win {
+            console.say(Message::ReverseGotIt, &[&guesses])?;
+            return Ok(Reverse::Guessed(guesses));
+        }
+    }
+}
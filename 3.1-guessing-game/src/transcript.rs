@@ -0,0 +1,82 @@
+//! A machine-readable record of a round, for scripts and CI to assert on
+//! instead of scraping the text the game prints.
+
+use serde_json::Value;
+
+use game::{Game, Outcome, State};
+
+/// How a round ended, as far as a transcript is concerned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ending {
+    Won,
+    Lost,
+    /// The input ran out before the game was over.
+    Abandoned,
+}
+
+impl Ending {
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Ending::Won       => "won",
+            Ending::Lost      => "lost",
+            Ending::Abandoned => "abandoned",
+        }
+    }
+}
+
+impl From<State> for Ending {
+    fn from(state: State) -> Ending {
+        match state {
+            State::Won     => Ending::Won,
+            State::Lost    => Ending::Lost,
+            State::Playing => Ending::Abandoned,
+        }
+    }
+}
+
+fn verdict(outcome: Outcome) -> &'static str {
+    match outcome {
+        Outcome::TooSmall   => "too_small",
+        Outcome::TooBig     => "too_big",
+        Outcome::Win        => "correct",
+        Outcome::OutOfRange => "out_of_range",
+    }
+}
+
+/// Every guess made in `game` with its verdict, plus how the round ended.
+///
+/// ```json
+/// {
+///   "seed": 7,
+///   "min": 1, "max": 100, "max_attempts": null,
+///   "secret": 42,
+///   "guesses": [
+///     {"attempt": 1, "guess": 50, "verdict": "too_big"},
+///     {"attempt": null, "guess": 500, "verdict": "out_of_range"},
+///     {"attempt": 2, "guess": 42, "verdict": "correct"}
+///   ],
+///   "attempts": 2,
+///   "hints": 0,
+///   "outcome": "won"
+/// }
+/// ```
+pub fn to_json(game: &Game, seed: Option<u64>) -> Value {
+    let config = game.config();
+    let guesses: Vec<Value> = game.turns().iter().map(|turn| json!({
+        "attempt": turn.attempt,
+        "guess": turn.guess,
+        "verdict": verdict(turn.outcome),
+    })).collect();
+
+    json!({
+        "seed": seed,
+        "min": config.min,
+        "max": config.max,
+        "max_attempts": config.max_attempts,
+        "secret": game.secret(),
+        "guesses": guesses,
+        "attempts": game.attempts().len(),
+        "hints": game.hints_used(),
+        "outcome": Ending::from(game.state()).name(),
+    })
+}
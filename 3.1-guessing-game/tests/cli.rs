@@ -0,0 +1,235 @@
+extern crate guessing_game;
+extern crate serde_json;
+
+use std::env;
+use std::fs;
+use std::io::prelude::*;
+use std::path::PathBuf;
+use std::process::{self, Stdio};
+
+use guessing_game::{Config, Difficulty, Locale};
+use guessing_game::cli::{Command, Format, Options};
+
+fn args(args: &[&str]) -> Vec<String> {
+    args.iter().map(|arg| arg.to_string()).collect()
+}
+
+#[test]
+fn no_arguments() {
+    assert_eq!(Options::parse(args(&[]), None), Ok(Options::default()));
+}
+
+#[test]
+fn seed_flag() {
+    assert_eq!(Options::parse(args(&["--seed", "42"]), None).unwrap().seed, Some(42));
+    assert_eq!(Options::parse(args(&["--seed=42"]), None).unwrap().seed, Some(42));
+}
+
+#[test]
+fn seed_from_environment() {
+    assert_eq!(Options::parse(args(&[]), Some("7".to_string())).unwrap().seed, Some(7));
+}
+
+#[test]
+fn seed_flag_beats_environment() {
+    let options = Options::parse(args(&["--seed", "42"]), Some("7".to_string())).unwrap();
+    assert_eq!(options.seed, Some(42));
+}
+
+#[test]
+fn bad_seeds_are_rejected() {
+    assert!(Options::parse(args(&["--seed"]), None).is_err());
+    assert!(Options::parse(args(&["--seed", "-1"]), None).is_err());
+    assert!(Options::parse(args(&["--seed", "pony"]), None).is_err());
+    assert!(Options::parse(args(&[]), Some("pony".to_string())).is_err());
+}
+
+#[test]
+fn unknown_arguments_are_rejected() {
+    assert!(Options::parse(args(&["--sed", "42"]), None).is_err());
+}
+
+#[test]
+fn range_and_attempts() {
+    let options = Options::parse(args(&["--min", "5", "--max=50", "--max-attempts", "4"]), None)
+        .unwrap();
+
+    assert_eq!(options.config, Config { min: 5, max: 50, max_attempts: Some(4) });
+}
+
+#[test]
+fn difficulty_presets() {
+    for difficulty in Difficulty::ALL.iter() {
+        let options = Options::parse(args(&["--difficulty", difficulty.name()]), None).unwrap();
+        assert_eq!(options.config, difficulty.config());
+    }
+}
+
+#[test]
+fn flags_override_the_preset_in_any_order() {
+    let options = Options::parse(args(&["--max-attempts", "3", "--difficulty", "hard"]), None)
+        .unwrap();
+
+    assert_eq!(options.config, Config { max_attempts: Some(3), ..Difficulty::Hard.config() });
+}
+
+#[test]
+fn invalid_configs_are_rejected() {
+    assert!(Options::parse(args(&["--min", "10", "--max", "9"]), None).is_err());
+    assert!(Options::parse(args(&["--max-attempts", "0"]), None).is_err());
+    assert!(Options::parse(args(&["--difficulty", "nightmare"]), None).is_err());
+}
+
+#[test]
+fn scores_command() {
+    let options = Options::parse(args(&["scores", "--scores-file", "elsewhere.txt"]), None).unwrap();
+
+    assert_eq!(options.command, Command::Scores);
+    assert_eq!(options.scores_file, Some(PathBuf::from("elsewhere.txt")));
+    assert_eq!(Options::parse(args(&[]), None).unwrap().command, Command::Play);
+    assert!(Options::parse(args(&["scores", "scores"]), None).is_err());
+}
+
+#[test]
+fn player_name() {
+    let options = Options::parse(args(&["--name", "Ferris"]), None).unwrap();
+    assert_eq!(options.player, Some("Ferris".to_string()));
+}
+
+#[test]
+fn format() {
+    assert_eq!(Options::parse(args(&[]), None).unwrap().format, Format::Text);
+    assert_eq!(Options::parse(args(&["--format", "json"]), None).unwrap().format, Format::Json);
+    assert_eq!(Options::parse(args(&["--format=text"]), None).unwrap().format, Format::Text);
+    assert!(Options::parse(args(&["--format", "yaml"]), None).is_err());
+}
+
+#[test]
+fn lang() {
+    assert_eq!(Options::parse(args(&[]), None).unwrap().lang, None);
+    assert_eq!(Options::parse(args(&["--lang", "es"]), None).unwrap().lang, Some(Locale::Spanish));
+    assert_eq!(Options::parse(args(&["--lang=en_GB.UTF-8"]), None).unwrap().lang,
+               Some(Locale::English));
+    assert!(Options::parse(args(&["--lang", "fr"]), None).is_err());
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    let path = env::temp_dir().join(format!("guessing_game_cli_{}_{}", process::id(), name));
+    let _ = fs::remove_file(&path);
+    path
+}
+
+fn run(args: &[&str], input: &str) -> Vec<u8> {
+    let output = run_with_status(args, input);
+    assert!(output.status.success());
+    output.stdout
+}
+
+fn run_with_status(args: &[&str], input: &str) -> process::Output {
+    let mut child = process::Command::new(env!("CARGO_BIN_EXE_guessing_game"))
+        .args(args)
+        .env_remove(guessing_game::cli::SEED_VAR)
+        // Keep the tests in English whatever the machine speaks
+        .env_remove("LC_ALL")
+        .env_remove("LC_MESSAGES")
+        .env("LANG", "C")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+#[test]
+fn binary_replays_a_seeded_session_byte_for_byte() {
+    let input: String = (1..101).map(|n| format!("{}\n", n)).collect();
+    let scores = temp_path("replay.txt");
+    let scores = scores.to_str().unwrap();
+
+    let first = run(&["--seed", "2016", "--scores-file", scores], &input);
+    let second = run(&["--seed", "2016", "--scores-file", scores], &input);
+
+    assert_eq!(first, second);
+    assert!(String::from_utf8(first).unwrap().starts_with("Seed: 2016\n"));
+}
+
+#[test]
+fn binary_records_scores() {
+    let scores = temp_path("scores.txt");
+    let scores = scores.to_str().unwrap();
+    let input: String = (1..101).map(|n| format!("{}\n", n)).collect();
+
+    run(&["--seed", "1", "--name", "Ferris", "--scores-file", scores], &input);
+    run(&["--seed", "1", "--name", "Ferris", "--scores-file", scores], &input);
+    let report = String::from_utf8(run(&["scores", "--scores-file", scores], "")).unwrap();
+
+    assert!(report.contains("== custom =="));
+    assert!(report.contains("Ferris           2 won of 2 played"));
+    fs::remove_file(scores).unwrap();
+}
+
+#[test]
+fn binary_exits_cleanly_when_input_ends() {
+    let scores = temp_path("eof.txt");
+
+    let output = run_with_status(&["--seed", "3", "--scores-file", scores.to_str().unwrap()],
+                                 "50\nfifty\n");
+    let stdout = String::from_utf8(output.stdout).unwrap();
+
+    assert_eq!(output.status.code(), Some(guessing_game::cli::EXIT_EOF));
+    assert!(stdout.contains("isn't a number"));
+    assert!(stdout.ends_with("No more input, giving up.\n"));
+    assert!(output.stderr.is_empty());
+    // Abandoned rounds aren't scored
+    assert!(!scores.exists());
+}
+
+#[test]
+fn binary_prints_a_json_transcript() {
+    let scores = temp_path("json.txt");
+    let input: String = (1..101).map(|n| format!("{}\n", n)).collect();
+
+    let stdout = run(&["--seed", "5", "--format", "json", "--scores-file", scores.to_str().unwrap()],
+                     &input);
+    let transcript: serde_json::Value = serde_json::from_slice(&stdout).unwrap();
+
+    let secret = transcript["secret"].as_u64().unwrap();
+    assert_eq!(transcript["seed"], 5);
+    assert_eq!(transcript["outcome"], "won");
+    assert_eq!(transcript["attempts"], secret);
+    assert_eq!(transcript["guesses"].as_array().unwrap().len() as u64, secret);
+    assert_eq!(transcript["guesses"][secret as usize - 1]["verdict"], "correct");
+    // Nothing but the JSON, and batch runs aren't scored
+    assert_eq!(stdout.iter().filter(|&&b| b == b'\n').count(), 1);
+    assert!(!scores.exists());
+}
+
+#[test]
+fn binary_reports_abandoned_batches() {
+    let output = run_with_status(&["--seed", "5", "--format", "json"], "1\n");
+    let transcript: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+
+    assert_eq!(output.status.code(), Some(guessing_game::cli::EXIT_EOF));
+    assert_eq!(transcript["outcome"], "abandoned");
+}
+
+#[test]
+fn auto_player_can_print_json() {
+    let stdout = run(&["auto", "--seed", "5", "--difficulty", "hard", "--format", "json"], "");
+    let transcript: serde_json::Value = serde_json::from_slice(&stdout).unwrap();
+
+    assert_eq!(transcript["outcome"], "won");
+    assert!(transcript["attempts"].as_u64().unwrap() <= 10);
+}
+
+#[test]
+fn binary_speaks_the_language_asked_for() {
+    let stdout = run(&["auto", "--seed", "5", "--lang", "es"], "");
+    let stdout = String::from_utf8(stdout).unwrap();
+
+    assert!(stdout.starts_with("Semilla: 5\n"));
+    assert!(stdout.contains("He encontrado el "));
+}
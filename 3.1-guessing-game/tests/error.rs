@@ -0,0 +1,46 @@
+extern crate guessing_game;
+
+use std::error::Error as StdError;
+use std::io;
+
+use guessing_game::{Error, parse_guess};
+
+#[test]
+fn parses_guesses() {
+    assert_eq!(parse_guess("42").unwrap(), 42);
+    assert_eq!(parse_guess("  7 \t").unwrap(), 7);
+    assert_eq!(parse_guess("+7").unwrap(), 7);
+    assert_eq!(parse_guess("4294967295").unwrap(), u32::MAX);
+}
+
+#[test]
+fn not_a_number() {
+    for input in &["", "   ", "forty two", "4.2", "0x2a", "-", "--1", "1-"] {
+        match parse_guess(input) {
+            Err(Error::NotANumber(ref rest)) => assert_eq!(rest, input.trim()),
+            other => panic!("{:?} parsed as {:?}", input, other),
+        }
+    }
+}
+
+#[test]
+fn negative() {
+    for input in &["-1", "-0", "-99999999999999999999"] {
+        assert!(matches!(parse_guess(input), Err(Error::Negative(_))), "{}", input);
+    }
+}
+
+#[test]
+fn overflow() {
+    for input in &["4294967296", "99999999999999999999", "+4294967296"] {
+        assert!(matches!(parse_guess(input), Err(Error::Overflow(_))), "{}", input);
+    }
+}
+
+#[test]
+fn io_errors_keep_their_cause() {
+    let err = Error::from(io::Error::other("disk on fire"));
+
+    assert!(matches!(err, Error::Io(_)));
+    assert_eq!(err.source().unwrap().to_string(), "disk on fire");
+}
@@ -0,0 +1,73 @@
+extern crate guessing_game;
+
+use guessing_game::Config;
+use guessing_game::hint::{Band, Clue, Hints, Trend};
+
+fn range(min: u32, max: u32) -> Config {
+    Config { min, max, max_attempts: None }
+}
+
+#[test]
+fn bands_scale_with_the_range() {
+    let config = range(1, 100);
+    assert_eq!(Band::new(&config, 50, 50), Band::Hot);
+    assert_eq!(Band::new(&config, 45, 50), Band::Hot);
+    assert_eq!(Band::new(&config, 44, 50), Band::Warm);
+    assert_eq!(Band::new(&config, 70, 50), Band::Warm);
+    assert_eq!(Band::new(&config, 71, 50), Band::Cold);
+
+    let config = range(1, 1000);
+    assert_eq!(Band::new(&config, 450, 500), Band::Hot);
+    assert_eq!(Band::new(&config, 700, 500), Band::Warm);
+    assert_eq!(Band::new(&config, 701, 500), Band::Cold);
+}
+
+#[test]
+fn tiny_ranges_still_have_bands() {
+    let config = range(1, 10);
+    assert_eq!(Band::new(&config, 4, 5), Band::Hot);
+    assert_eq!(Band::new(&config, 3, 5), Band::Warm);
+    assert_eq!(Band::new(&config, 1, 5), Band::Cold);
+}
+
+#[test]
+fn bands_at_the_extremes() {
+    let config = range(0, u32::MAX);
+    assert_eq!(Band::new(&config, 0, u32::MAX), Band::Cold);
+    assert_eq!(Band::new(&config, u32::MAX, u32::MAX), Band::Hot);
+}
+
+#[test]
+fn trends() {
+    assert_eq!(Trend::new(10, 40, 50), Trend::Warmer);
+    assert_eq!(Trend::new(40, 10, 50), Trend::Colder);
+    assert_eq!(Trend::new(40, 60, 50), Trend::Same);
+    assert_eq!(Trend::new(90, 20, 50), Trend::Warmer);
+}
+
+#[test]
+fn clues_come_in_order_until_the_budget_runs_out() {
+    let mut hints = Hints::new(3);
+
+    assert_eq!(hints.remaining(), 3);
+    assert_eq!(hints.next(30), Some(Clue::Even(true)));
+    assert_eq!(hints.next(30), Some(Clue::DivisibleBy(3, true)));
+    assert_eq!(hints.next(30), Some(Clue::DivisibleBy(5, true)));
+    assert_eq!(hints.next(30), None);
+    assert_eq!(hints.given(), 3);
+    assert_eq!(hints.remaining(), 0);
+
+    let mut hints = Hints::new(2);
+    assert_eq!(hints.next(7), Some(Clue::Even(false)));
+    assert_eq!(hints.next(7), Some(Clue::DivisibleBy(3, false)));
+}
+
+#[test]
+fn budget_is_capped_by_the_clues_available() {
+    let mut hints = Hints::new(100);
+    let clues: Vec<Clue> = (0..100).filter_map(|_| hints.next(1)).collect();
+
+    assert_eq!(clues.len() as u32, hints.given());
+    assert_eq!(clues.len(), 6);
+    assert_eq!(hints.remaining(), 0);
+}
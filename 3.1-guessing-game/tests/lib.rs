@@ -0,0 +1,251 @@
+// Tests dir is an entirely different crate,
+// so we have to pull in `guessing_game`
+extern crate guessing_game;
+
+use guessing_game::{Config, Difficulty, Error, Game, Outcome, Script, State};
+
+fn limited(min: u32, max: u32, max_attempts: u32) -> Config {
+    Config { min, max, max_attempts: Some(max_attempts) }
+}
+
+#[test]
+fn guess_compares_against_the_secret() {
+    let mut game = Game::new(Config::default(), 42);
+
+    assert_eq!(game.guess(10), Outcome::TooSmall);
+    assert_eq!(game.guess(90), Outcome::TooBig);
+    assert_eq!(game.state(), State::Playing);
+    assert_eq!(game.guess(42), Outcome::Win);
+    assert_eq!(game.state(), State::Won);
+    assert_eq!(game.attempts(), &[10, 90, 42]);
+}
+
+#[test]
+fn random_secret_is_within_the_range() {
+    for _ in 0..1000 {
+        let secret = Game::random(Config::default()).secret();
+        assert!((1..101).contains(&secret));
+    }
+    for _ in 0..1000 {
+        let secret = Game::random(limited(7, 9, 1)).secret();
+        assert!((7..10).contains(&secret));
+    }
+}
+
+#[test]
+fn out_of_range_guesses_are_not_counted() {
+    let mut game = Game::new(limited(10, 20, 3), 15);
+
+    assert_eq!(game.guess(9), Outcome::OutOfRange);
+    assert_eq!(game.guess(21), Outcome::OutOfRange);
+    assert_eq!(game.guess(0), Outcome::OutOfRange);
+    assert!(game.attempts().is_empty());
+    assert_eq!(game.attempts_left(), Some(3));
+}
+
+#[test]
+fn running_out_of_attempts_loses() {
+    let mut game = Game::new(limited(1, 10, 2), 7);
+
+    assert_eq!(game.guess(1), Outcome::TooSmall);
+    assert_eq!(game.attempts_left(), Some(1));
+    assert_eq!(game.state(), State::Playing);
+    assert_eq!(game.guess(10), Outcome::TooBig);
+    assert_eq!(game.state(), State::Lost);
+}
+
+#[test]
+fn winning_on_the_last_attempt_wins() {
+    let mut game = Game::new(limited(1, 10, 1), 7);
+
+    assert_eq!(game.guess(7), Outcome::Win);
+    assert_eq!(game.state(), State::Won);
+}
+
+#[test]
+#[should_panic(expected = "already over")]
+fn guessing_after_the_game_is_over_panics() {
+    let mut game = Game::new(Config::default(), 42);
+    game.guess(42);
+    game.guess(42);
+}
+
+#[test]
+#[should_panic(expected = "outside")]
+fn secret_must_be_in_range() {
+    Game::new(limited(1, 10, 1), 11);
+}
+
+#[test]
+fn presets() {
+    for difficulty in Difficulty::ALL.iter() {
+        let config = difficulty.config();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.difficulty(), Some(*difficulty));
+        assert_eq!(difficulty.name().parse::<Difficulty>(), Ok(*difficulty));
+    }
+    assert_eq!(Config::default().difficulty(), None);
+    assert!("impossible".parse::<Difficulty>().is_err());
+}
+
+#[test]
+fn scripted_session() {
+    let mut game = Game::new(Config::default(), 42);
+    let mut script = Script::new(&["50", "25", "42"]);
+
+    assert_eq!(guessing_game::play(&mut game, &mut script).unwrap(), State::Won);
+
+    assert_eq!(script.output(), &[
+        "Guess the number!",
+        "It's between 1 and 100.",
+        "Please input your guess.",
+        "You guessed: 50",
+        "Too big!",
+        "Please input your guess.",
+        "You guessed: 25",
+        "Too small!",
+        "Please input your guess.",
+        "You guessed: 42",
+        "You win!",
+    ]);
+    assert_eq!(game.attempts(), &[50, 25, 42]);
+}
+
+#[test]
+fn scripted_loss() {
+    let mut game = Game::new(limited(1, 10, 2), 7);
+    let mut script = Script::new(&["11", "5", "9", "7"]);
+
+    assert_eq!(guessing_game::play(&mut game, &mut script).unwrap(), State::Lost);
+
+    assert_eq!(script.output(), &[
+        "Guess the number!",
+        "It's between 1 and 10.",
+        "You have 2 attempts.",
+        "Please input your guess.",
+        "You guessed: 11",
+        "Please guess a number between 1 and 10.",
+        "Please input your guess.",
+        "You guessed: 5",
+        "Too small!",
+        "1 attempt left.",
+        "Please input your guess.",
+        "You guessed: 9",
+        "Too big!",
+        "You lose! The number was 7.",
+    ]);
+}
+
+#[test]
+fn running_out_of_input_is_an_error() {
+    let mut game = Game::new(Config::default(), 42);
+    let mut script = Script::new(&["50"]);
+
+    let err = guessing_game::play(&mut game, &mut script).unwrap_err();
+
+    assert!(matches!(err, Error::Eof));
+    assert_eq!(game.state(), State::Playing);
+}
+
+#[test]
+fn bad_input_gets_a_hint_and_does_not_count() {
+    let mut game = Game::new(Config { min: 1, max: 10, max_attempts: Some(1) }, 3);
+    let mut script = Script::new(&["", "three", "-3", "99999999999", "3"]);
+
+    assert_eq!(guessing_game::play(&mut game, &mut script).unwrap(), State::Won);
+
+    assert_eq!(script.output(), &[
+        "Guess the number!",
+        "It's between 1 and 10.",
+        "You have 1 attempt.",
+        "Please input your guess.",
+        "Please type a number between 1 and 10.",
+        "Please input your guess.",
+        "\"three\" isn't a number, please type a whole number between 1 and 10 using digits.",
+        "Please input your guess.",
+        "The number is never negative, it's between 1 and 10.",
+        "Please input your guess.",
+        "That's way too big, the number is between 1 and 10.",
+        "Please input your guess.",
+        "You guessed: 3",
+        "You win!",
+    ]);
+}
+
+#[test]
+fn same_seed_same_secret() {
+    for seed in 0..100 {
+        let config = Config::default();
+        assert_eq!(Game::seeded(config, seed).secret(), Game::seeded(config, seed).secret());
+    }
+}
+
+#[test]
+fn seeded_secrets_are_within_the_range_and_vary() {
+    let secrets: Vec<u32> = (0..100)
+        .map(|seed| Game::seeded(Config::default(), seed).secret())
+        .collect();
+
+    assert!(secrets.iter().all(|secret| (1..101).contains(secret)));
+    assert!(secrets.iter().any(|&secret| secret != secrets[0]));
+}
+
+#[test]
+fn seeded_session_replays_identically() {
+    let guesses: Vec<String> = (1..101).map(|n| n.to_string()).collect();
+    let mut first = Script::new(&guesses);
+    let mut second = Script::new(&guesses);
+
+    guessing_game::play(&mut Game::seeded(Config::default(), 1234), &mut first).unwrap();
+    guessing_game::play(&mut Game::seeded(Config::default(), 1234), &mut second).unwrap();
+
+    assert_eq!(first.output(), second.output());
+}
+
+#[test]
+fn hint_mode_session() {
+    let mut game = Game::new(Config::default(), 42).with_hints(1);
+    let mut script = Script::new(&["90", "hint", "30", "HINT", "45", "42"]);
+
+    assert_eq!(guessing_game::play(&mut game, &mut script).unwrap(), State::Won);
+
+    assert_eq!(script.output(), &[
+        "Guess the number!",
+        "It's between 1 and 100.",
+        "Please input your guess.",
+        "You guessed: 90",
+        "Too big!",
+        "You're cold.",
+        "Please input your guess.",
+        "Hint: the number is even.",
+        "That cost you 2 points, 0 hints left.",
+        "Please input your guess.",
+        "You guessed: 30",
+        "Too small!",
+        "You're warm.",
+        "Warmer than your last guess.",
+        "Please input your guess.",
+        "Sorry, no hints left.",
+        "Please input your guess.",
+        "You guessed: 45",
+        "Too big!",
+        "You're hot!",
+        "Warmer than your last guess.",
+        "Please input your guess.",
+        "You guessed: 42",
+        "You win!",
+    ]);
+    assert_eq!(game.attempts(), &[90, 30, 45, 42]);
+    assert_eq!(game.hints_used(), 1);
+}
+
+#[test]
+fn hint_is_just_bad_input_outside_hint_mode() {
+    let mut game = Game::new(Config::default(), 42);
+    let mut script = Script::new(&["hint", "42"]);
+
+    guessing_game::play(&mut game, &mut script).unwrap();
+
+    assert!(script.output()[3].contains("isn't a number"));
+    assert_eq!(game.hints_used(), 0);
+}
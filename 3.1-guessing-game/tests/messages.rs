@@ -0,0 +1,75 @@
+extern crate guessing_game;
+
+use std::collections::HashSet;
+
+use guessing_game::messages;
+use guessing_game::{Config, Error, Game, Locale, Message, Script, State};
+
+/// The `{n}` placeholders in `template`.
+fn placeholders(template: &str) -> HashSet<String> {
+    template.split('{')
+        .skip(1)
+        .filter_map(|rest| rest.find('}').map(|end| rest[..end].to_string()))
+        .collect()
+}
+
+#[test]
+fn every_locale_defines_every_message() {
+    for &locale in Locale::ALL.iter() {
+        for &message in Message::ALL {
+            assert!(locale.template(message).is_some(),
+                    "{} is missing {}", locale.code(), message.key());
+        }
+    }
+}
+
+#[test]
+fn translations_use_the_same_placeholders_as_english() {
+    for &locale in Locale::ALL.iter() {
+        for &message in Message::ALL {
+            let english = Locale::English.template(message).unwrap();
+            let translated = locale.template(message).unwrap();
+            assert_eq!(placeholders(translated), placeholders(english),
+                       "{} {} has the wrong placeholders", locale.code(), message.key());
+        }
+    }
+}
+
+#[test]
+fn format_fills_in_arguments_in_any_order() {
+    assert_eq!(Locale::English.format(Message::Range, &[&1, &100]), "It's between 1 and 100.");
+    assert_eq!(Locale::Spanish.format(Message::Range, &[&1, &100]), "Está entre 1 y 100.");
+
+    // A translation doesn't have to keep them in the same order
+    assert_eq!(messages::fill("{1} before {0}", &[&"second", &"first"]), "first before second");
+    assert_eq!(messages::fill("{1}, {0}, {1}", &[&1, &2]), "2, 1, 2");
+}
+
+#[test]
+fn locales_from_posix_names() {
+    assert_eq!(Locale::from_posix("es_ES.UTF-8"), Some(Locale::Spanish));
+    assert_eq!(Locale::from_posix("en_US"), Some(Locale::English));
+    assert_eq!(Locale::from_posix("es"), Some(Locale::Spanish));
+    assert_eq!(Locale::from_posix("C"), None);
+    assert_eq!(Locale::from_posix(""), None);
+    assert_eq!("es".parse(), Ok(Locale::Spanish));
+    assert!("fr".parse::<Locale>().is_err());
+}
+
+#[test]
+fn errors_are_translated() {
+    let config = Config::default();
+
+    assert_eq!(Error::Eof.hint(Locale::English, &config), "No more input, giving up.");
+    assert_ne!(Error::Eof.hint(Locale::Spanish, &config), "No more input, giving up.");
+}
+
+#[test]
+fn play_a_round_in_spanish() {
+    let mut game = Game::new(Config::default(), 42);
+    let mut console = Script::new(&["50", "42"]).with_locale(Locale::Spanish);
+
+    assert_eq!(guessing_game::play(&mut game, &mut console).unwrap(), State::Won);
+    assert_eq!(console.output()[0], "¡Adivina el número!");
+    assert!(console.output().iter().all(|line| !line.contains("guess")));
+}
@@ -0,0 +1,204 @@
+extern crate guessing_game;
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::process;
+use std::time::Duration;
+
+use guessing_game::{Config, Difficulty};
+use guessing_game::scores::{self, Record, Stats};
+
+fn record(player: &str, config: Config, attempts: u32, won: bool) -> Record {
+    Record {
+        player: player.to_string(),
+        config,
+        attempts,
+        hints: 0,
+        elapsed: Duration::from_millis(1500 + attempts as u64),
+        seed: 42,
+        won,
+    }
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    let path = env::temp_dir().join(format!("guessing_game_scores_{}_{}", process::id(), name));
+    let _ = fs::remove_file(&path);
+    path
+}
+
+#[test]
+fn missing_file_has_no_scores() {
+    assert_eq!(scores::load(&temp_path("missing.txt")).unwrap(), vec![]);
+}
+
+#[test]
+fn append_and_load_round_trip() {
+    let path = temp_path("round_trip.txt");
+    let records = vec![
+        record("Ferris", Difficulty::Hard.config(), 10, false),
+        record("Ferris", Config::default(), 7, true),
+        record("Corro\tthe\nUnsafe", Difficulty::Easy.config(), 3, true),
+    ];
+
+    for record in &records {
+        scores::append(&path, record).unwrap();
+    }
+    let loaded = scores::load(&path).unwrap();
+
+    assert_eq!(loaded.len(), 3);
+    assert_eq!(loaded[..2], records[..2]);
+    assert_eq!(loaded[2].player, "Corro the Unsafe");
+    assert!(fs::read_to_string(&path).unwrap().starts_with("guessing_game scores v2\n"));
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn reads_version_1() {
+    let file = "guessing_game scores v1\n\
+                Ferris\t1\t100\t10\t6\t12345\t99\twon\n\
+                \n\
+                Ferris\t1\t100\t-\t4\t500\t7\tlost\n";
+
+    let records = scores::parse(io::Cursor::new(file)).unwrap();
+
+    assert_eq!(records, vec![
+        Record {
+            player: "Ferris".to_string(),
+            config: Difficulty::Normal.config(),
+            attempts: 6,
+            hints: 0,
+            elapsed: Duration::from_millis(12345),
+            seed: 99,
+            won: true,
+        },
+        Record {
+            player: "Ferris".to_string(),
+            config: Config::default(),
+            attempts: 4,
+            hints: 0,
+            elapsed: Duration::from_millis(500),
+            seed: 7,
+            won: false,
+        },
+    ]);
+}
+
+#[test]
+fn reads_version_2() {
+    let file = "guessing_game scores v2\n\
+                Ferris\t1\t100\t10\t6\t2\t12345\t99\twon\n";
+
+    let records = scores::parse(io::Cursor::new(file)).unwrap();
+
+    assert_eq!(records[0].attempts, 6);
+    assert_eq!(records[0].hints, 2);
+    assert_eq!(records[0].score(), 10);
+    assert_eq!(records[0].elapsed, Duration::from_millis(12345));
+}
+
+#[test]
+fn appending_upgrades_old_files() {
+    let path = temp_path("upgrade.txt");
+    fs::write(&path, "guessing_game scores v1\nFerris\t1\t100\t-\t4\t500\t7\tlost\n").unwrap();
+
+    let mut hinted = record("Corro", Config::default(), 3, true);
+    hinted.hints = 1;
+    scores::append(&path, &hinted).unwrap();
+
+    let contents = fs::read_to_string(&path).unwrap();
+    assert_eq!(contents, "guessing_game scores v2\n\
+                          Ferris\t1\t100\t-\t4\t0\t500\t7\tlost\n\
+                          Corro\t1\t100\t-\t3\t1\t1503\t42\twon\n");
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn rejects_unknown_versions_and_garbage() {
+    let newer = scores::parse(io::Cursor::new("guessing_game scores v999\n")).unwrap_err();
+    assert_eq!(newer.kind(), io::ErrorKind::InvalidData);
+    assert!(newer.to_string().contains("version 999"));
+
+    assert!(scores::parse(io::Cursor::new("high scores!\n")).is_err());
+
+    let bad_line = scores::parse(io::Cursor::new("guessing_game scores v1\nFerris\t1\n"))
+        .unwrap_err();
+    assert!(bad_line.to_string().contains("line 2"));
+}
+
+#[test]
+fn leaderboards_are_per_difficulty_and_only_count_wins() {
+    let records = vec![
+        record("a", Difficulty::Easy.config(), 5, true),
+        record("b", Difficulty::Easy.config(), 2, true),
+        record("c", Difficulty::Easy.config(), 1, false),
+        record("d", Difficulty::Hard.config(), 9, true),
+        record("e", Config::default(), 30, true),
+    ];
+
+    let boards = scores::leaderboards(&records);
+
+    assert_eq!(boards.keys().cloned().collect::<Vec<_>>(), vec!["custom", "easy", "hard"]);
+    let easy: Vec<&str> = boards["easy"].iter().map(|record| record.player.as_str()).collect();
+    assert_eq!(easy, vec!["b", "a"]);
+}
+
+#[test]
+fn hints_count_against_the_leaderboard() {
+    let mut hinted = record("hinted", Config::default(), 2, true);
+    hinted.hints = 2;
+    let records = vec![hinted, record("honest", Config::default(), 5, true)];
+
+    let boards = scores::leaderboards(&records);
+
+    let order: Vec<&str> = boards["custom"].iter().map(|record| record.player.as_str()).collect();
+    assert_eq!(order, vec!["honest", "hinted"]);
+}
+
+#[test]
+fn leaderboards_keep_the_top_ten() {
+    let records: Vec<Record> = (1..20).rev()
+        .map(|attempts| record("a", Config::default(), attempts, true))
+        .collect();
+
+    let boards = scores::leaderboards(&records);
+
+    let attempts: Vec<u32> = boards["custom"].iter().map(|record| record.attempts).collect();
+    assert_eq!(attempts, (1..11).collect::<Vec<_>>());
+}
+
+#[test]
+fn stats() {
+    let config = Config::default();
+    let records = vec![
+        record("a", config, 4, true),
+        record("a", config, 10, false),
+        record("a", config, 6, true),
+        record("a", config, 8, true),
+        record("a", config, 3, false),
+    ];
+
+    assert_eq!(Stats::new(&records), Stats {
+        rounds: 5,
+        wins: 3,
+        average_attempts: Some(6.0),
+        best_streak: 2,
+    });
+    assert_eq!(Stats::new(&[]).average_attempts, None);
+}
+
+#[test]
+fn stats_are_kept_per_player() {
+    let config = Config::default();
+    let records = vec![
+        record("a", config, 4, true),
+        record("b", config, 10, false),
+        record("a", config, 6, true),
+    ];
+
+    let by_player = scores::stats_by_player(&records);
+
+    assert_eq!(by_player["a"].best_streak, 2);
+    assert_eq!(by_player["b"].wins, 0);
+}
@@ -0,0 +1,104 @@
+extern crate guessing_game;
+
+use std::io::prelude::*;
+use std::io::BufReader;
+use std::net::{SocketAddr, TcpStream};
+use std::thread;
+
+use guessing_game::{Config, Game};
+use guessing_game::server::Server;
+
+/// A scripted player on the other end of a TCP connection.
+struct Client {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Client {
+    fn connect(addr: SocketAddr, name: &str) -> Client {
+        let stream = TcpStream::connect(addr).unwrap();
+        stream.set_nodelay(true).unwrap();
+        let mut client = Client {
+            reader: BufReader::new(stream.try_clone().unwrap()),
+            writer: stream,
+        };
+
+        client.expect("Guess the number!");
+        client.expect("It's between 1 and 100.");
+        client.expect("What's your name?");
+        client.send(name);
+        client.expect(&format!("Welcome, {}!", name));
+        client
+    }
+
+    fn send(&mut self, line: &str) {
+        writeln!(self.writer, "{}", line).unwrap();
+    }
+
+    fn expect(&mut self, expected: &str) {
+        let mut line = String::new();
+        self.reader.read_line(&mut line).unwrap();
+        assert_eq!(line.trim_end(), expected);
+    }
+
+    fn expect_hang_up(&mut self) {
+        let mut line = String::new();
+        assert_eq!(self.reader.read_line(&mut line).unwrap_or(0), 0, "got {:?}", line);
+    }
+}
+
+fn start(game: Game) -> (SocketAddr, thread::JoinHandle<Option<String>>) {
+    let server = Server::bind("127.0.0.1:0", game).unwrap();
+    let addr = server.local_addr().unwrap();
+    (addr, thread::spawn(move || server.run().unwrap()))
+}
+
+#[test]
+fn two_players_race_for_the_secret() {
+    let (addr, server) = start(Game::new(Config::default(), 42));
+    let mut alice = Client::connect(addr, "Alice");
+    let mut bob = Client::connect(addr, "Bob");
+
+    alice.expect("Please input your guess.");
+    bob.expect("Please input your guess.");
+
+    alice.send("10");
+    alice.expect("Too small!");
+    alice.expect("Please input your guess.");
+
+    bob.send("lots");
+    bob.expect("\"lots\" isn't a number, please type a whole number between 1 and 100 using digits.");
+    bob.expect("Please input your guess.");
+    bob.send("50");
+    bob.expect("Too big!");
+    bob.expect("Please input your guess.");
+    bob.send("42");
+
+    bob.expect("Bob wins! The number was 42.");
+    alice.expect("Bob wins! The number was 42.");
+    bob.expect_hang_up();
+    alice.expect_hang_up();
+
+    assert_eq!(server.join().unwrap(), Some("Bob".to_string()));
+}
+
+#[test]
+fn the_table_shares_the_attempt_limit() {
+    let config = Config { min: 1, max: 100, max_attempts: Some(2) };
+    let (addr, server) = start(Game::new(config, 42));
+    let mut alice = Client::connect(addr, "Alice");
+    let mut bob = Client::connect(addr, "Bob");
+
+    alice.expect("Please input your guess.");
+    bob.expect("Please input your guess.");
+    alice.send("1");
+    alice.expect("Too small!");
+    alice.expect("Please input your guess.");
+    bob.send("100");
+    bob.expect("Too big!");
+
+    alice.expect("Out of attempts, nobody wins! The number was 42.");
+    bob.expect("Out of attempts, nobody wins! The number was 42.");
+
+    assert_eq!(server.join().unwrap(), None);
+}
@@ -0,0 +1,129 @@
+extern crate guessing_game;
+
+use guessing_game::{Config, Difficulty, Game, Outcome, Script, State};
+use guessing_game::solver::{self, Inconsistent, Reverse, Solver};
+
+fn range(min: u32, max: u32) -> Config {
+    Config { min, max, max_attempts: None }
+}
+
+/// ⌈log2(x)⌉, the hard way
+fn ceil_log2(x: u64) -> u32 {
+    let mut bits = 0;
+    while (1u64 << bits) < x {
+        bits += 1;
+    }
+    bits
+}
+
+#[test]
+fn worst_case_is_ceil_log2() {
+    for n in 1..5000u32 {
+        assert_eq!(solver::worst_case(&range(1, n)), ceil_log2(n as u64 + 1));
+        if !n.is_power_of_two() {
+            assert_eq!(solver::worst_case(&range(1, n)), ceil_log2(n as u64));
+        }
+    }
+    assert_eq!(solver::worst_case(&range(1, 100)), 7);
+    assert_eq!(solver::worst_case(&range(0, u32::MAX)), 33);
+}
+
+#[test]
+fn every_game_is_solved_within_the_bound() {
+    let configs = [range(1, 1), range(1, 2), range(1, 100), range(1, 127),
+                   range(1, 128), range(37, 1000), range(0, 4096)];
+
+    for config in configs.iter() {
+        let bound = solver::worst_case(config);
+        let mut hardest = 0;
+
+        for secret in config.min..config.max + 1 {
+            let mut game = Game::new(*config, secret);
+            assert_eq!(solver::auto_play(&mut game), State::Won);
+            assert!(game.attempts().len() as u32 <= bound,
+                    "{} took {} guesses in {:?}", secret, game.attempts().len(), config);
+            hardest = hardest.max(game.attempts().len() as u32);
+        }
+
+        // And the bound is tight
+        assert_eq!(hardest, bound);
+    }
+}
+
+#[test]
+fn presets_are_always_winnable() {
+    for difficulty in Difficulty::ALL.iter() {
+        let config = difficulty.config();
+        for secret in config.min..config.max + 1 {
+            assert_eq!(solver::auto_play(&mut Game::new(config, secret)), State::Won);
+        }
+    }
+}
+
+#[test]
+fn extreme_secrets() {
+    let config = range(0, u32::MAX);
+    for &secret in &[0, 1, u32::MAX / 2, u32::MAX - 1, u32::MAX] {
+        let mut game = Game::new(config, secret);
+        assert_eq!(solver::auto_play(&mut game), State::Won);
+        assert!(game.attempts().len() <= 33);
+    }
+}
+
+#[test]
+fn detects_contradictions() {
+    let mut solver = Solver::new(&range(1, 100));
+
+    assert_eq!(solver.record(50, Outcome::TooSmall), Ok(()));
+    assert_eq!(solver.record(60, Outcome::TooBig), Ok(()));
+    assert_eq!(solver.record(55, Outcome::TooBig), Ok(()));
+    assert_eq!(solver.record(51, Outcome::TooBig),
+               Err(Inconsistent { at_least: 51, at_most: 50 }));
+}
+
+#[test]
+fn detects_contradictions_at_the_edges() {
+    let mut solver = Solver::new(&range(0, 10));
+    assert!(solver.record(0, Outcome::TooBig).is_err());
+
+    let mut solver = Solver::new(&range(0, u32::MAX));
+    assert!(solver.record(u32::MAX, Outcome::TooSmall).is_err());
+
+    let mut solver = Solver::new(&range(1, 10));
+    assert!(solver.record(5, Outcome::TooSmall).is_ok());
+    assert!(solver.record(3, Outcome::Win).is_err());
+}
+
+#[test]
+fn reverse_game() {
+    // The player is thinking of 30
+    let mut script = Script::new(&["lower", "what?", "HIGHER", "l", "l", "h", "h", "correct"]);
+
+    let result = solver::reverse(&range(1, 100), &mut script).unwrap();
+
+    assert_eq!(result, Reverse::Guessed(7));
+    assert_eq!(script.output(), &[
+        "Think of a number between 1 and 100 and I'll guess it.",
+        "Is it 50? (higher/lower/correct)",
+        "Is it 25? (higher/lower/correct)",
+        "Please answer higher, lower or correct.",
+        "Is it 37? (higher/lower/correct)",
+        "Is it 31? (higher/lower/correct)",
+        "Is it 28? (higher/lower/correct)",
+        "Is it 29? (higher/lower/correct)",
+        "Is it 30? (higher/lower/correct)",
+        "Got it in 7 guesses!",
+    ]);
+}
+
+#[test]
+fn reverse_game_catches_cheaters() {
+    let mut script = Script::new(&["higher", "higher"]);
+
+    let result = solver::reverse(&range(1, 3), &mut script).unwrap();
+
+    assert_eq!(result, Reverse::Cheated(Inconsistent { at_least: 4, at_most: 3 }));
+    assert_eq!(script.output().last().unwrap(),
+               "That can't be right, your number would have to be at least 4 and at most 3. \
+                Cheater!");
+}
@@ -0,0 +1,63 @@
+#[macro_use]
+extern crate serde_json;
+extern crate guessing_game;
+
+use guessing_game::{Config, Game, Outcome, Quiet, Script, Turn};
+use guessing_game::transcript;
+
+#[test]
+fn turns_include_rejected_guesses() {
+    let mut game = Game::new(Config::default(), 42);
+    game.guess(50);
+    game.guess(0);
+    game.guess(42);
+
+    assert_eq!(game.turns(), &[
+        Turn { guess: 50, outcome: Outcome::TooBig, attempt: Some(1) },
+        Turn { guess: 0, outcome: Outcome::OutOfRange, attempt: None },
+        Turn { guess: 42, outcome: Outcome::Win, attempt: Some(2) },
+    ]);
+}
+
+#[test]
+fn won_round() {
+    let mut game = Game::new(Config::default(), 42);
+    let mut console = Quiet(Script::new(&["50", "nope", "500", "42"]));
+
+    guessing_game::play(&mut game, &mut console).unwrap();
+
+    assert!(console.0.output().is_empty());
+    assert_eq!(transcript::to_json(&game, Some(7)), json!({
+        "seed": 7,
+        "min": 1,
+        "max": 100,
+        "max_attempts": null,
+        "secret": 42,
+        "guesses": [
+            {"attempt": 1, "guess": 50, "verdict": "too_big"},
+            {"attempt": null, "guess": 500, "verdict": "out_of_range"},
+            {"attempt": 2, "guess": 42, "verdict": "correct"},
+        ],
+        "attempts": 2,
+        "hints": 0,
+        "outcome": "won",
+    }));
+}
+
+#[test]
+fn lost_and_abandoned_rounds() {
+    let config = Config { min: 1, max: 10, max_attempts: Some(1) };
+    let mut lost = Game::new(config, 3);
+    lost.guess(4);
+    let abandoned = Game::new(config, 3);
+
+    let lost = transcript::to_json(&lost, None);
+    let abandoned = transcript::to_json(&abandoned, None);
+
+    assert_eq!(lost["outcome"], "lost");
+    assert_eq!(lost["max_attempts"], 1);
+    assert_eq!(lost["seed"], serde_json::Value::Null);
+    assert_eq!(lost["guesses"][0]["verdict"], "too_big");
+    assert_eq!(abandoned["outcome"], "abandoned");
+    assert_eq!(abandoned["guesses"], json!([]));
+}
@@ -0,0 +1,53 @@
+//! How long dinner really takes, on the wall clock, depending on how the
+//! philosophers take turns. Run with `cargo bench`.
+//!
+//! Serial dinner has every meal one after another, while threads and tasks
+//! let anybody whose neighbours aren't eating eat at the same time, so the
+//! bigger the table, the further behind serial falls. Every strategy that
+//! can't deadlock gets a go in every mode.
+
+extern crate dining_philosophers;
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use dining_philosophers::{Kind, Length, Mode, Roster, Simulation, Strategy, Table, tasks};
+
+const RUNS: u32 = 5;
+
+/// One dinner in `mode` with `kind`, and how long it really took.
+fn dinner(mode: Mode, kind: Kind, roster: &Roster, simulation: &Simulation) -> Duration {
+    let table = Arc::new(Table::new(roster.len()));
+    let strategy: Arc<dyn Strategy> = Arc::from(kind.build(table.seats()));
+    let started = Instant::now();
+
+    let report = match mode {
+        Mode::Serial => simulation.run_serial(roster.seat(), &table, &*strategy),
+        Mode::Threaded => simulation.run(roster.seat(), table, strategy),
+        Mode::Async => tasks::run(simulation, roster.seat(), &table, &*strategy).unwrap(),
+    };
+    assert_eq!(report.meals(), roster.len() as u32 * 3);
+
+    started.elapsed()
+}
+
+fn main() {
+    let simulation = Simulation {
+        length: Length::Rounds(3),
+        eat_time: Duration::from_millis(10),
+    };
+
+    println!("{:<14} {:<10} {:>6} {:>12} {:>12}", "strategy", "mode", "seats", "best", "average");
+    for &seats in &[5, 20] {
+        let roster = Roster::generated(seats);
+        for &kind in &Kind::SAFE {
+            for &mode in &Mode::ALL {
+                let times: Vec<Duration> = (0..RUNS).map(|_| dinner(mode, kind, &roster, &simulation)).collect();
+                let best = times.iter().min().unwrap();
+                let average = times.iter().sum::<Duration>() / RUNS;
+                println!("{:<14} {:<10} {:>6} {:>10.1}ms {:>10.1}ms", kind.name(), mode.name(), seats,
+                         best.as_secs_f64() * 1000.0, average.as_secs_f64() * 1000.0);
+            }
+        }
+    }
+}
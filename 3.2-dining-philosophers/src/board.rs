@@ -0,0 +1,144 @@
+//! A picture of the table as it is right now, kept up to date from its
+//! events, and laid out round a circle for `tui` to draw.
+
+use std::f64::consts::PI;
+use std::sync::Mutex;
+
+use events::{Event, EventSink, Kind};
+
+/// What a philosopher is up to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mood {
+    Thinking,
+    Hungry,
+    Eating,
+    /// Just finished eating.
+    Full,
+}
+
+impl Mood {
+    pub const ALL: [Mood; 4] = [Mood::Thinking, Mood::Hungry, Mood::Eating, Mood::Full];
+
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Mood::Thinking => "thinking",
+            Mood::Hungry => "hungry",
+            Mood::Eating => "eating",
+            Mood::Full => "full",
+        }
+    }
+}
+
+/// How to colour a label.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Paint {
+    Mood(Mood),
+    /// A fork nobody's holding.
+    Free,
+}
+
+/// Some text to draw at a spot, counting from 1 like the terminal does.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Label {
+    pub x: u16,
+    pub y: u16,
+    pub text: String,
+    pub paint: Paint,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Scene {
+    pub names: Vec<String>,
+    pub moods: Vec<Mood>,
+    /// Who's holding each fork.
+    pub holders: Vec<Option<usize>>,
+    pub meals: Vec<u32>,
+}
+
+impl Scene {
+    /// Everybody thinking, and all the forks on the table.
+    pub fn new(names: Vec<String>) -> Scene {
+        let seats = names.len();
+        Scene {
+            names,
+            moods: vec![Mood::Thinking; seats],
+            holders: vec![None; seats],
+            meals: vec![0; seats],
+        }
+    }
+
+    pub fn apply(&mut self, event: &Event) {
+        let seat = event.philosopher;
+        match event.kind {
+            Kind::Thinking => self.moods[seat] = Mood::Thinking,
+            Kind::Hungry => self.moods[seat] = Mood::Hungry,
+            Kind::PickedUp { fork, .. } => self.holders[fork] = Some(seat),
+            Kind::Eating => {
+                self.moods[seat] = Mood::Eating;
+                self.meals[seat] += 1;
+            }
+            Kind::Done => self.moods[seat] = Mood::Full,
+            // Never got to finish
+            Kind::Dropped => self.moods[seat] = Mood::Hungry,
+            Kind::PutDown { fork, .. } => self.holders[fork] = None,
+            // There's no drawing a cellar
+            Kind::Took { .. } | Kind::Returned { .. } => {}
+        }
+    }
+
+    /// Everybody round the edge of a `width` by `height` table, starting at
+    /// the top and going clockwise, with fork `n` just before seat `n`.
+    pub fn layout(&self, width: u16, height: u16) -> Vec<Label> {
+        let seats = self.names.len();
+        let (cx, cy) = (f64::from(width) / 2.0, f64::from(height) / 2.0);
+        // Characters are about twice as tall as they are wide
+        let ry = (cy - 1.5).max(1.0);
+        let rx = (ry * 2.0).min(cx - 12.0).max(1.0);
+
+        let at = |turns: f64, scale: f64, text: String, paint: Paint| {
+            let angle = 2.0 * PI * turns - PI / 2.0;
+            let x = cx + rx * scale * angle.cos() - text.chars().count() as f64 / 2.0;
+            let y = cy + ry * scale * angle.sin();
+            Label { x: x.round().max(1.0) as u16, y: y.round().max(1.0) as u16, text, paint }
+        };
+
+        let mut labels = Vec::new();
+        for seat in 0..seats {
+            let text = format!("{} {} ({})", seat, self.names[seat], self.meals[seat]);
+            labels.push(at(seat as f64 / seats as f64, 1.0, text, Paint::Mood(self.moods[seat])));
+        }
+        for fork in 0..seats {
+            let (text, paint) = match self.holders[fork] {
+                Some(holder) => (format!("ψ{}", holder), Paint::Mood(self.moods[holder])),
+                None => ("ψ".to_string(), Paint::Free),
+            };
+            labels.push(at((fork as f64 - 0.5) / seats as f64, 0.6, text, paint));
+        }
+        labels
+    }
+}
+
+/// Keeps a `Scene` up to date as events come in.
+#[derive(Debug)]
+pub struct Board {
+    scene: Mutex<Scene>,
+}
+
+impl Board {
+    pub fn new(names: Vec<String>) -> Board {
+        Board {
+            scene: Mutex::new(Scene::new(names)),
+        }
+    }
+
+    /// The table as it is right now.
+    pub fn scene(&self) -> Scene {
+        self.scene.lock().unwrap().clone()
+    }
+}
+
+impl EventSink for Board {
+    fn record(&self, event: &Event) {
+        self.scene.lock().unwrap().apply(event);
+    }
+}
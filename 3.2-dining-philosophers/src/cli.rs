@@ -0,0 +1,256 @@
+//! Command line options for the `dining_philosophers` binary.
+
+use std::env;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+use clock;
+use simulation::{Length, Mode, Simulation};
+use strategy::Kind;
+
+pub const USAGE: &str = "\
+Usage: dining_philosophers [options]
+
+Options:
+    --strategy NAME       How philosophers avoid deadlock: ordering (the
+                          default), waiter, chandy-misra or backoff. Or
+                          naive, which doesn't.
+    --roster PATH         Read who's coming from a .toml or .json file.
+    --count N             Seat N made up philosophers instead.
+    --graph PATH          Have the agents in the .toml or .json graph at
+                          PATH drink from shared bottles instead of
+                          philosophers eating. Only the report is
+                          printed, and `--events` and `--trace` say who
+                          took and returned which bottle. There's no
+                          `--tui`, `--supervise`, `--fumble` or `--mode`
+                          other than threaded.
+    --rounds N            Everybody eats their appetite N times over.
+                          Defaults to 1.
+    --duration MS         Keep thinking and eating for MS milliseconds
+                          instead of a set number of rounds.
+    --eat-time MS         How long a meal takes. Defaults to 1000.
+    --starvation MS       Flag anybody who waits longer than MS
+                          milliseconds for their forks. Defaults to 5000.
+    --supervise           Restart anybody who panics instead of letting it
+                          spread round the table, and say who did and how
+                          many meals it cost. Only with `--mode threaded`.
+    --fumble SEAT:MEAL    Have the philosopher at SEAT panic halfway
+                          through their MEALth meal. Can be given more
+                          than once.
+    --quiet               Only print the report at the end.
+    --tui                 Watch dinner round the table as it happens. Space
+                          pauses, s steps, + and - change the speed, q
+                          quits.
+    --events PATH         Write everything that happens to PATH, one JSON
+                          object per line.
+    --trace PATH          Write a Chrome trace of dinner to PATH, to look
+                          at in chrome://tracing or ui.perfetto.dev.
+    --clock CLOCK         `real` (the default) to actually wait, or `virtual`
+                          to only pretend, so dinner is over right away.
+                          `discrete` pretends too, and takes turns so that
+                          dinner goes exactly the same way every time.
+    --mode MODE           `threaded` (the default) gives every philosopher a
+                          thread. `serial` has them eat one at a time, in
+                          turn. `async` makes them tasks sharing one
+                          thread.
+    -h, --help            Print this message.
+
+Exit status:
+    0     Everybody finished dinner.
+    1     The roster or graph couldn't be read.
+    2     Bad command line arguments.
+    3     The table deadlocked.
+    4     The event log or trace couldn't be written.";
+
+pub const EXIT_ROSTER: i32 = 1;
+pub const EXIT_USAGE: i32 = 2;
+pub const EXIT_DEADLOCK: i32 = 3;
+pub const EXIT_EVENTS: i32 = 4;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Options {
+    pub strategy: Kind,
+    pub roster: Option<PathBuf>,
+    /// How many philosophers to make up, if there's no roster.
+    pub count: Option<usize>,
+    /// Who drinks what, for the drinking philosophers.
+    pub graph: Option<PathBuf>,
+    pub simulation: Simulation,
+    /// How long is too long to wait for forks.
+    pub starvation: Duration,
+    pub clock: clock::Kind,
+    pub mode: Mode,
+    /// Run dinner under a `Supervisor`.
+    pub supervise: bool,
+    /// Seats and the meals they panic during.
+    pub fumbles: Vec<(usize, u32)>,
+    /// Don't say when everybody starts and finishes eating.
+    pub quiet: bool,
+    /// Draw the table in the terminal instead.
+    pub tui: bool,
+    /// Where to write the JSON Lines event log.
+    pub events: Option<PathBuf>,
+    /// Where to write the Chrome trace.
+    pub trace: Option<PathBuf>,
+    pub help: bool,
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            strategy: Kind::default(),
+            roster: None,
+            count: None,
+            graph: None,
+            simulation: Simulation::default(),
+            starvation: Duration::from_millis(5000),
+            clock: clock::Kind::default(),
+            mode: Mode::default(),
+            supervise: false,
+            fumbles: Vec::new(),
+            quiet: false,
+            tui: false,
+            events: None,
+            trace: None,
+            help: false,
+        }
+    }
+}
+
+impl Options {
+    /// Parses the process' own arguments.
+    pub fn from_env() -> Result<Options, String> {
+        Options::parse(env::args().skip(1))
+    }
+
+    /// Parses `args`, without the program name.
+    pub fn parse<I>(args: I) -> Result<Options, String>
+        where I: IntoIterator<Item = String>
+    {
+        let mut options = Options::default();
+        let mut args = args.into_iter();
+        let mut rounds = None;
+        let mut duration = None;
+
+        while let Some(arg) = args.next() {
+            // Accept both `--strategy waiter` and `--strategy=waiter`
+            let (flag, inline) = match arg.find('=') {
+                Some(i) if arg.starts_with("--") => (arg[..i].to_string(), Some(arg[i + 1..].to_string())),
+                _ => (arg.clone(), None),
+            };
+
+            match flag.as_str() {
+                "--strategy" => options.strategy = value_for(&flag, inline, &mut args)?.parse()?,
+                "--roster" => {
+                    options.roster = Some(PathBuf::from(value_for(&flag, inline, &mut args)?));
+                }
+                "--graph" => {
+                    options.graph = Some(PathBuf::from(value_for(&flag, inline, &mut args)?));
+                }
+                "--count" => {
+                    let value = value_for(&flag, inline, &mut args)?;
+                    options.count = Some(parse_number(&value, &flag)?);
+                }
+                "--rounds" => {
+                    let value = value_for(&flag, inline, &mut args)?;
+                    rounds = Some(parse_number(&value, &flag)?);
+                }
+                "--duration" => {
+                    let value = value_for(&flag, inline, &mut args)?;
+                    duration = Some(Duration::from_millis(parse_number(&value, &flag)?));
+                }
+                "--eat-time" => {
+                    let value = value_for(&flag, inline, &mut args)?;
+                    options.simulation.eat_time = Duration::from_millis(parse_number(&value, &flag)?);
+                }
+                "--starvation" => {
+                    let value = value_for(&flag, inline, &mut args)?;
+                    options.starvation = Duration::from_millis(parse_number(&value, &flag)?);
+                }
+                "--supervise" => options.supervise = true,
+                "--fumble" => {
+                    let value = value_for(&flag, inline, &mut args)?;
+                    options.fumbles.push(parse_fumble(&value)?);
+                }
+                "--quiet" => options.quiet = true,
+                "--tui" => options.tui = true,
+                "--events" => {
+                    options.events = Some(PathBuf::from(value_for(&flag, inline, &mut args)?));
+                }
+                "--trace" => {
+                    options.trace = Some(PathBuf::from(value_for(&flag, inline, &mut args)?));
+                }
+                "--clock" => options.clock = value_for(&flag, inline, &mut args)?.parse()?,
+                "--mode" => options.mode = value_for(&flag, inline, &mut args)?.parse()?,
+                "-h" | "--help" => options.help = true,
+                _ => return Err(format!("unknown argument `{}`", arg)),
+            }
+        }
+
+        if options.roster.is_some() && options.count.is_some() {
+            return Err("`--roster` and `--count` can't be used together".to_string());
+        }
+        options.simulation.length = match (rounds, duration) {
+            (Some(_), Some(_)) => {
+                return Err("`--rounds` and `--duration` can't be used together".to_string());
+            }
+            (Some(rounds), None) => Length::Rounds(rounds),
+            (None, Some(duration)) => Length::Duration(duration),
+            (None, None) => Length::default(),
+        };
+        if options.graph.is_some() {
+            if options.roster.is_some() || options.count.is_some() {
+                return Err("`--graph` says who's coming, so it can't have `--roster` or `--count`".to_string());
+            }
+            if options.tui {
+                return Err("`--tui` can only draw a table, not `--graph`".to_string());
+            }
+            if options.supervise || options.mode != Mode::Threaded || !options.fumbles.is_empty() {
+                return Err("`--graph` only works with `--mode threaded`, unsupervised".to_string());
+            }
+        }
+        if options.tui && options.clock != clock::Kind::Real {
+            return Err(format!("`--tui` keeps its own time, so it can't use `--clock {}`", options.clock));
+        }
+        if options.supervise && options.mode != Mode::Threaded {
+            return Err(format!("`--supervise` only works with `--mode threaded`, not {}", options.mode));
+        }
+        if let Some(count) = options.count {
+            if count < 2 {
+                return Err(format!("a table needs at least two philosophers, not {}", count));
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+fn value_for<I>(flag: &str, inline: Option<String>, args: &mut I) -> Result<String, String>
+    where I: Iterator<Item = String>
+{
+    match inline {
+        Some(value) => Ok(value),
+        None => args.next().ok_or_else(|| format!("`{}` needs a value", flag)),
+    }
+}
+
+/// `SEAT:MEAL`, where meals count from 1.
+fn parse_fumble(value: &str) -> Result<(usize, u32), String> {
+    let mut parts = value.splitn(2, ':');
+    match (parts.next(), parts.next()) {
+        (Some(seat), Some(meal)) => {
+            let seat = parse_number(seat, "--fumble")?;
+            match parse_number(meal, "--fumble")? {
+                0 => Err("`--fumble` meals count from 1".to_string()),
+                meal => Ok((seat, meal)),
+            }
+        }
+        _ => Err(format!("`--fumble` expects SEAT:MEAL, got `{}`", value)),
+    }
+}
+
+fn parse_number<T: FromStr>(value: &str, source: &str) -> Result<T, String> {
+    value.trim().parse()
+        .map_err(|_| format!("`{}` must be a whole number, got `{}`", source, value))
+}
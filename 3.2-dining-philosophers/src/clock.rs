@@ -0,0 +1,531 @@
+//! Where philosophers get the time from, and how they wait.
+//!
+//! `RealClock` is the wall clock. `VirtualClock` only pretends: sleeping
+//! just moves the sleeping thread's own clock forward, so a dinner that
+//! would take hours is over as fast as the threads can run.
+//!
+//! Every thread keeps its own virtual time, and threads only agree on the
+//! time when they hand something over. A fork remembers when it was put
+//! down, and whoever picks it up next catches up to then with
+//! `Clock::catch_up`. That's enough to keep cause before effect: nobody
+//! eats with a fork before its last user was done with it. It isn't enough
+//! to make two dinners the same, though, since the OS still decides who
+//! gets to a fork first.
+//!
+//! `DiscreteClock` decides that too. Only one thread runs at a time, in an
+//! order that only depends on what happened before, and time jumps straight
+//! to the next thing due once everybody's asleep or waiting. The same
+//! dinner goes exactly the same way every time. Deciding who runs is its
+//! job as a `Scheduler`, which a table is only given `with_scheduler`, so
+//! the other clocks only have to keep time.
+//!
+//! `PacedClock` is somewhere in between: real time, but sped up, slowed
+//! down or stopped whenever somebody watching says so.
+
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::fmt;
+use std::mem;
+use std::str::FromStr;
+use std::sync::{Arc, Condvar, LockResult, Mutex, MutexGuard};
+use std::thread::{self, ThreadId};
+use std::time::{Duration, Instant};
+
+/// A source of time that can be shared between threads.
+pub trait Clock: Send + Sync + fmt::Debug {
+    /// How long since the clock started, as far as the calling thread
+    /// knows.
+    fn now(&self) -> Duration;
+
+    fn sleep(&self, duration: Duration);
+
+    /// Tells the calling thread that another thread has already seen
+    /// `moment`, so it can't be any earlier than that.
+    fn catch_up(&self, moment: Duration);
+}
+
+/// Decides which of the threads taking part runs when, instead of leaving
+/// it to the OS. Since only one of them runs at a time, they can't just
+/// block waiting for each other, or nobody else would get to run, so they
+/// `stall` instead.
+pub trait Scheduler: Send + Sync + fmt::Debug {
+    /// Says `threads` more threads are about to `arrive`.
+    fn expect(&self, threads: usize);
+
+    /// The calling thread is taking part, as number `id`, until it
+    /// `leave`s. Threads that were `expect`ed all start together, in order.
+    /// Use `Seated` rather than calling this yourself.
+    fn arrive(&self, id: usize);
+
+    fn leave(&self);
+
+    /// Gives way to everybody else until something has `changed`, for a
+    /// thread that found what it needs taken. It'll still have to check
+    /// whether it's the change it was waiting for.
+    fn stall(&self);
+
+    /// Something a stalled thread might be waiting for has changed.
+    fn changed(&self);
+
+    /// Whether everybody still taking part has stalled, so nothing any of
+    /// them is waiting for can ever change. A `Watchdog` believes it
+    /// straight away.
+    fn stuck(&self) -> bool;
+
+    /// The calling thread is about to touch something the others share,
+    /// like a fork, so somebody else could go first instead.
+    fn turn(&self);
+}
+
+/// A thread taking part in whatever its scheduler, if there is one,
+/// decides. Leaves when dropped, even by a panic, so nobody's left waiting
+/// for its turn.
+#[derive(Debug)]
+pub struct Seated<'a> {
+    scheduler: Option<&'a dyn Scheduler>,
+}
+
+impl<'a> Seated<'a> {
+    /// Arrives at `scheduler` as number `id`, once it's the calling
+    /// thread's turn.
+    pub fn new(scheduler: Option<&'a dyn Scheduler>, id: usize) -> Seated<'a> {
+        if let Some(scheduler) = scheduler {
+            scheduler.arrive(id);
+        }
+        Seated { scheduler }
+    }
+}
+
+impl<'a> Drop for Seated<'a> {
+    fn drop(&mut self) {
+        if let Some(scheduler) = self.scheduler {
+            scheduler.leave();
+        }
+    }
+}
+
+/// Waits on `condvar` for `guard`, which is a lock on `mutex`, unless
+/// there's a `scheduler` deciding who runs, in which case it `stall`s
+/// instead. Wake the waiters with `Scheduler::changed` as well as the
+/// condvar.
+pub fn wait<'a, T>(scheduler: Option<&dyn Scheduler>, mutex: &'a Mutex<T>, condvar: &Condvar,
+                   guard: MutexGuard<'a, T>) -> LockResult<MutexGuard<'a, T>> {
+    match scheduler {
+        None => condvar.wait(guard),
+        Some(scheduler) => {
+            drop(guard);
+            scheduler.stall();
+            mutex.lock()
+        }
+    }
+}
+
+/// The wall clock, counting from when it was made.
+#[derive(Debug, Clone, Copy)]
+pub struct RealClock {
+    started: Instant,
+}
+
+impl Default for RealClock {
+    fn default() -> RealClock {
+        RealClock::new()
+    }
+}
+
+impl RealClock {
+    pub fn new() -> RealClock {
+        RealClock {
+            started: Instant::now(),
+        }
+    }
+}
+
+impl Clock for RealClock {
+    fn now(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        thread::sleep(duration);
+    }
+
+    fn catch_up(&self, _moment: Duration) {
+        // Everybody's already looking at the same clock
+    }
+}
+
+/// Pretend time, kept separately for every thread that uses the clock.
+/// Threads start at zero.
+#[derive(Debug, Default)]
+pub struct VirtualClock {
+    times: Mutex<HashMap<ThreadId, Duration>>,
+}
+
+impl VirtualClock {
+    pub fn new() -> VirtualClock {
+        VirtualClock::default()
+    }
+
+    fn update<F: FnOnce(&mut Duration)>(&self, f: F) {
+        let mut times = self.times.lock().unwrap();
+        f(times.entry(thread::current().id()).or_default());
+    }
+}
+
+impl Clock for VirtualClock {
+    fn now(&self) -> Duration {
+        let times = self.times.lock().unwrap();
+        times.get(&thread::current().id()).cloned().unwrap_or_default()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        self.update(|now| *now += duration);
+        // Nothing to wait for, but give everybody else a go, or a
+        // philosopher backing off for a fork would just spin
+        thread::yield_now();
+    }
+
+    fn catch_up(&self, moment: Duration) {
+        self.update(|now| *now = (*now).max(moment));
+    }
+}
+
+/// Pretend time that everybody shares, moved on one thing at a time.
+///
+/// Threads that `arrive` take turns: only one of them runs at once, and it
+/// carries on until it sleeps or stalls. Then whoever's first in line gets
+/// a go, and once nobody is left in line, the clock jumps to when the first
+/// sleeper is due and wakes them. Anybody else who uses the clock, like an
+/// `Executor` or dinner served one at a time, has it to themselves, so
+/// their sleeps just move it on.
+#[derive(Debug, Default)]
+pub struct DiscreteClock {
+    schedule: Mutex<Schedule>,
+    /// Signalled whenever it's somebody else's turn.
+    turn: Condvar,
+}
+
+#[derive(Debug, Default)]
+struct Schedule {
+    now: Duration,
+    /// How many threads have been `expect`ed that haven't arrived yet.
+    expected: usize,
+    /// Who's arrived, by number, while they wait for the others.
+    arrived: Vec<(usize, ThreadId)>,
+    /// Everybody who's arrived and hasn't left.
+    seated: HashSet<ThreadId>,
+    running: Option<ThreadId>,
+    /// Whose turn it is next, in order.
+    ready: VecDeque<ThreadId>,
+    /// Who's asleep, by when they're due, then by when they dozed off.
+    sleeping: BTreeMap<(Duration, u64), ThreadId>,
+    naps: u64,
+    /// Who's waiting for something to change.
+    stalled: Vec<ThreadId>,
+    /// Whether everybody left has stalled.
+    stuck: bool,
+}
+
+impl DiscreteClock {
+    pub fn new() -> DiscreteClock {
+        DiscreteClock::default()
+    }
+
+    /// Hands the turn on to whoever's next in line, or failing that, the
+    /// first to wake up. If everybody left has stalled, nobody gets it,
+    /// since nothing will ever change, and the clock is `stuck` until
+    /// somebody from outside changes something.
+    fn give_way(&self, schedule: &mut Schedule) {
+        schedule.running = schedule.ready.pop_front().or_else(|| {
+            let ((due, _), sleeper) = schedule.sleeping.pop_first()?;
+            schedule.now = schedule.now.max(due);
+            Some(sleeper)
+        });
+        schedule.stuck = schedule.running.is_none() && !schedule.stalled.is_empty();
+        self.turn.notify_all();
+    }
+
+    fn wait_turn(&self, mut schedule: MutexGuard<'_, Schedule>) {
+        let me = thread::current().id();
+        while schedule.running != Some(me) {
+            schedule = self.turn.wait(schedule).unwrap();
+        }
+    }
+}
+
+impl Clock for DiscreteClock {
+    fn now(&self) -> Duration {
+        self.schedule.lock().unwrap().now
+    }
+
+    fn sleep(&self, duration: Duration) {
+        let me = thread::current().id();
+        let mut schedule = self.schedule.lock().unwrap();
+        if !schedule.seated.contains(&me) {
+            // Nobody to take turns with
+            schedule.now += duration;
+            return;
+        }
+
+        let (due, nap) = (schedule.now + duration, schedule.naps);
+        schedule.naps += 1;
+        schedule.sleeping.insert((due, nap), me);
+        self.give_way(&mut schedule);
+        self.wait_turn(schedule);
+    }
+
+    fn catch_up(&self, _moment: Duration) {
+        // Everybody's already looking at the same clock
+    }
+}
+
+impl Scheduler for DiscreteClock {
+    fn expect(&self, threads: usize) {
+        self.schedule.lock().unwrap().expected += threads;
+    }
+
+    fn arrive(&self, id: usize) {
+        let me = thread::current().id();
+        let mut schedule = self.schedule.lock().unwrap();
+        schedule.seated.insert(me);
+        schedule.arrived.push((id, me));
+        schedule.expected = schedule.expected.saturating_sub(1);
+
+        if schedule.expected == 0 {
+            // However the OS got them here, they start in order
+            let mut arrived = mem::take(&mut schedule.arrived);
+            arrived.sort_by_key(|&(id, _)| id);
+            schedule.ready.extend(arrived.into_iter().map(|(_, thread)| thread));
+            if schedule.running.is_none() {
+                self.give_way(&mut schedule);
+            }
+        }
+        self.wait_turn(schedule);
+    }
+
+    fn leave(&self) {
+        let me = thread::current().id();
+        let mut schedule = self.schedule.lock().unwrap();
+        if schedule.seated.remove(&me) && schedule.running == Some(me) {
+            self.give_way(&mut schedule);
+        }
+    }
+
+    fn stall(&self) {
+        let me = thread::current().id();
+        let mut schedule = self.schedule.lock().unwrap();
+        if !schedule.seated.contains(&me) {
+            drop(schedule);
+            thread::yield_now();
+            return;
+        }
+
+        schedule.stalled.push(me);
+        self.give_way(&mut schedule);
+        self.wait_turn(schedule);
+    }
+
+    fn changed(&self) {
+        let mut schedule = self.schedule.lock().unwrap();
+        let stalled = mem::take(&mut schedule.stalled);
+        schedule.ready.extend(stalled);
+        if schedule.running.is_none() {
+            self.give_way(&mut schedule);
+        }
+    }
+
+    fn stuck(&self) -> bool {
+        self.schedule.lock().unwrap().stuck
+    }
+
+    fn turn(&self) {
+        // Whoever's running carries on until they sleep or stall
+    }
+}
+
+/// Real time that can be sped up, slowed down and paused, for watching
+/// dinner as it happens. Everybody shares the same time.
+#[derive(Debug)]
+pub struct PacedClock {
+    pace: Mutex<Pace>,
+    /// Signalled whenever the pace changes.
+    changed: Condvar,
+}
+
+#[derive(Debug)]
+struct Pace {
+    /// What the time was when the pace last changed, and when that was.
+    base: Duration,
+    since: Instant,
+    speed: f64,
+    paused: bool,
+    /// Stop the clock once it gets this far.
+    until: Option<Duration>,
+}
+
+impl Pace {
+    fn now(&self) -> Duration {
+        if self.paused {
+            return self.base;
+        }
+        let now = self.base + self.since.elapsed().mul_f64(self.speed);
+        match self.until {
+            Some(until) => now.min(until),
+            None => now,
+        }
+    }
+
+    /// Whether time's standing still.
+    fn stopped(&self) -> bool {
+        self.paused || self.until.is_some_and(|until| self.now() >= until)
+    }
+
+    /// Starts counting again from now, so a change of pace doesn't change
+    /// the time that has already gone by.
+    fn rebase(&mut self) {
+        self.base = self.now();
+        self.since = Instant::now();
+    }
+}
+
+impl Default for PacedClock {
+    fn default() -> PacedClock {
+        PacedClock::new()
+    }
+}
+
+impl PacedClock {
+    /// A clock going at normal speed.
+    pub fn new() -> PacedClock {
+        PacedClock {
+            pace: Mutex::new(Pace {
+                base: Duration::default(),
+                since: Instant::now(),
+                speed: 1.0,
+                paused: false,
+                until: None,
+            }),
+            changed: Condvar::new(),
+        }
+    }
+
+    fn change<F: FnOnce(&mut Pace)>(&self, f: F) {
+        let mut pace = self.pace.lock().unwrap();
+        pace.rebase();
+        f(&mut pace);
+        self.changed.notify_all();
+    }
+
+    pub fn pause(&self) {
+        self.change(|pace| pace.paused = true);
+    }
+
+    pub fn resume(&self) {
+        self.change(|pace| {
+            pace.paused = false;
+            pace.until = None;
+        });
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.pace.lock().unwrap().stopped()
+    }
+
+    /// Lets time run on by `duration`, then stops it again.
+    pub fn step(&self, duration: Duration) {
+        self.change(|pace| {
+            pace.paused = false;
+            pace.until = Some(pace.base + duration);
+        });
+    }
+
+    /// How many times faster than real time the clock goes.
+    pub fn speed(&self) -> f64 {
+        self.pace.lock().unwrap().speed
+    }
+
+    pub fn set_speed(&self, speed: f64) {
+        assert!(speed > 0.0, "the clock can't go at {}x", speed);
+        self.change(|pace| pace.speed = speed);
+    }
+}
+
+impl Clock for PacedClock {
+    fn now(&self) -> Duration {
+        self.pace.lock().unwrap().now()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        let mut pace = self.pace.lock().unwrap();
+        let until = pace.now() + duration;
+
+        loop {
+            let now = pace.now();
+            if now >= until {
+                return;
+            }
+            pace = if pace.stopped() {
+                self.changed.wait(pace).unwrap()
+            } else {
+                // Wakes up early if the pace changes, and works out how much
+                // longer to go from there
+                let left = (until - now).div_f64(pace.speed);
+                self.changed.wait_timeout(pace, left).unwrap().0
+            };
+        }
+    }
+
+    fn catch_up(&self, _moment: Duration) {
+        // Everybody's already looking at the same clock
+    }
+}
+
+/// The clocks to pick from on the command line.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Kind {
+    #[default]
+    Real,
+    Virtual,
+    Discrete,
+}
+
+impl Kind {
+    pub const ALL: [Kind; 3] = [Kind::Real, Kind::Virtual, Kind::Discrete];
+
+    /// The name used by `--clock`.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Kind::Real => "real",
+            Kind::Virtual => "virtual",
+            Kind::Discrete => "discrete",
+        }
+    }
+
+    /// The clock, and if it decides who runs when too, the same clock as
+    /// a `Scheduler`.
+    pub fn build(&self) -> (Arc<dyn Clock>, Option<Arc<dyn Scheduler>>) {
+        match *self {
+            Kind::Real => (Arc::new(RealClock::new()), None),
+            Kind::Virtual => (Arc::new(VirtualClock::new()), None),
+            Kind::Discrete => {
+                let clock = Arc::new(DiscreteClock::new());
+                (clock.clone(), Some(clock))
+            }
+        }
+    }
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Kind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Kind, String> {
+        Kind::ALL.iter().cloned()
+            .find(|kind| kind.name() == s)
+            .ok_or_else(|| format!("unknown clock `{}`, expected real, virtual or discrete", s))
+    }
+}
@@ -0,0 +1,323 @@
+//! The drinking philosophers: the dining philosophers, except nobody is
+//! stuck with the two forks either side of them.
+//!
+//! There's a cellar of bottles instead, and every agent drinks in
+//! sessions, each needing its own set of bottles, any number of which
+//! can be shared with anybody else. Bottles are `Fork`s, laid on a
+//! `Table::cellar`, so the clocks, the `Watchdog`, the `Report` and the
+//! event sinks work the same as they do for dinner, except that bottles
+//! are `Took` and `Returned` rather than picked up and put down. It's a way
+//! to try out a set of locks and the order things take them in before
+//! finding out the hard way.
+//!
+//! Every dining `Strategy` works here too, since they'll pick up any set
+//! of forks. `build` gives the waiter's job to a `Sommelier`, and lays
+//! `ChandyMisra` with a fork between everybody who shares a bottle.
+//!
+//! Not everything dinner has is here yet:
+//!
+//! * Drinking as async tasks, or one at a time. Agents always get a thread
+//!   each.
+//! * A `Supervisor`, or fumbles to give it something to do.
+//! * The `Board`, which only knows how to draw forks round a table.
+//!
+//! Who drinks what is read from a graph description, either TOML or JSON,
+//! like a roster:
+//!
+//! ```toml
+//! bottles = ["accounts", "ledger", "audit"]
+//!
+//! [[agent]]
+//! name = "transfer"
+//! sessions = [["accounts", "ledger"], ["ledger", "audit"]]
+//! think_time_ms = 5
+//!
+//! [[agent]]
+//! name = "report"
+//! sessions = [["audit", "accounts"]]
+//! ```
+//!
+//! Every agent goes through their sessions in turn, starting again from the
+//! first once they get to the end. A round is once through all of them.
+
+use std::error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use serde_json;
+use toml;
+
+use clock::Seated;
+use events::Kind as Event;
+use simulation::{Diner, Length, Report, Simulation};
+use strategy::{ChandyMisra, Kind, Sommelier, Strategy};
+use Table;
+
+/// Everybody who's drinking, and what they drink.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Graph {
+    pub bottles: Vec<String>,
+    #[serde(rename = "agent")]
+    pub agents: Vec<Agent>,
+}
+
+/// Somebody in the graph, before they've been given a seat.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Agent {
+    pub name: String,
+    /// The bottles they need for each session, by name, in the order they
+    /// reach for them.
+    pub sessions: Vec<Vec<String>>,
+    /// How long they think before each session, in milliseconds.
+    #[serde(default)]
+    pub think_time_ms: u64,
+}
+
+impl Agent {
+    pub fn new(name: &str, sessions: &[&[&str]]) -> Agent {
+        Agent {
+            name: name.to_string(),
+            sessions: sessions.iter()
+                .map(|session| session.iter().map(|bottle| bottle.to_string()).collect())
+                .collect(),
+            think_time_ms: 0,
+        }
+    }
+}
+
+/// An agent with a seat, and their bottles looked up by number.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Drinker {
+    pub name: String,
+    pub seat: usize,
+    pub sessions: Vec<Vec<usize>>,
+    pub think_time: Duration,
+}
+
+impl Graph {
+    /// The dining philosophers, as drinkers: `count` agents round a table,
+    /// each with the bottle on their left and the one on their right.
+    pub fn ring(count: usize) -> Graph {
+        let bottles: Vec<String> = (0..count).map(|n| format!("fork {}", n)).collect();
+        let agents = (0..count)
+            .map(|seat| {
+                let (left, right) = (&bottles[seat], &bottles[(seat + 1) % count]);
+                Agent::new(&format!("Philosopher {}", seat + 1), &[&[left, right]])
+            })
+            .collect();
+        Graph { bottles, agents }
+    }
+
+    /// Reads a graph from `path`, which must end in `.toml` or `.json`.
+    pub fn load(path: &Path) -> Result<Graph, Error> {
+        let text = fs::read_to_string(path).map_err(|err| Error::Io(path.to_path_buf(), err))?;
+
+        let graph: Graph = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("toml") => toml::from_str(&text).map_err(Error::Toml)?,
+            Some("json") => serde_json::from_str(&text).map_err(Error::Json)?,
+            _ => return Err(Error::UnknownFormat(path.to_path_buf())),
+        };
+
+        graph.validate()?;
+        Ok(graph)
+    }
+
+    /// Everybody has to drink something, out of bottles that are in the
+    /// cellar, and can't need the same bottle twice at once.
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.agents.is_empty() {
+            return Err(Error::NoAgents);
+        }
+        for (i, bottle) in self.bottles.iter().enumerate() {
+            if self.bottles[..i].contains(bottle) {
+                return Err(Error::DuplicateBottle(bottle.clone()));
+            }
+        }
+
+        for agent in &self.agents {
+            if agent.sessions.is_empty() {
+                return Err(Error::NoSessions(agent.name.clone()));
+            }
+            for session in &agent.sessions {
+                if session.is_empty() {
+                    return Err(Error::EmptySession(agent.name.clone()));
+                }
+                for (i, bottle) in session.iter().enumerate() {
+                    if !self.bottles.contains(bottle) {
+                        return Err(Error::UnknownBottle(agent.name.clone(), bottle.clone()));
+                    }
+                    if session[..i].contains(bottle) {
+                        return Err(Error::SameBottleTwice(agent.name.clone(), bottle.clone()));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Gives everybody a seat, in the order they're listed, and looks up
+    /// their bottles.
+    ///
+    /// # Panics
+    ///
+    /// If somebody needs a bottle that isn't in the cellar. `load` has
+    /// already checked, otherwise `validate` first.
+    pub fn seat(&self) -> Vec<Drinker> {
+        let bottle = |name: &String| {
+            self.bottles.iter().position(|bottle| bottle == name)
+                .unwrap_or_else(|| panic!("there's no {} in the cellar", name))
+        };
+
+        self.agents.iter().enumerate()
+            .map(|(seat, agent)| Drinker {
+                name: agent.name.clone(),
+                seat,
+                sessions: agent.sessions.iter()
+                    .map(|session| session.iter().map(&bottle).collect())
+                    .collect(),
+                think_time: Duration::from_millis(agent.think_time_ms),
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Io(PathBuf, io::Error),
+    Toml(toml::de::Error),
+    Json(serde_json::Error),
+    /// Neither `.toml` nor `.json`.
+    UnknownFormat(PathBuf),
+    NoAgents,
+    DuplicateBottle(String),
+    /// This agent doesn't have any sessions.
+    NoSessions(String),
+    /// This agent has a session without any bottles.
+    EmptySession(String),
+    /// This agent wants a bottle that isn't in the cellar.
+    UnknownBottle(String, String),
+    /// This agent wants the same bottle twice in one session.
+    SameBottleTwice(String, String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Io(ref path, ref err) => write!(f, "couldn't read {}: {}", path.display(), err),
+            Error::Toml(ref err) => write!(f, "bad TOML graph: {}", err),
+            Error::Json(ref err) => write!(f, "bad JSON graph: {}", err),
+            Error::UnknownFormat(ref path) => {
+                write!(f, "don't know how to read {}, expected a .toml or .json file",
+                       path.display())
+            }
+            Error::NoAgents => write!(f, "the graph doesn't have any agents"),
+            Error::DuplicateBottle(ref bottle) => write!(f, "there's more than one {} bottle", bottle),
+            Error::NoSessions(ref agent) => write!(f, "{} doesn't have any sessions", agent),
+            Error::EmptySession(ref agent) => write!(f, "{} has a session without any bottles", agent),
+            Error::UnknownBottle(ref agent, ref bottle) => {
+                write!(f, "{} wants {}, which isn't one of the bottles", agent, bottle)
+            }
+            Error::SameBottleTwice(ref agent, ref bottle) => {
+                write!(f, "{} wants {} twice in the same session", agent, bottle)
+            }
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::Io(_, ref err) => Some(err),
+            Error::Toml(ref err) => Some(err),
+            Error::Json(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// The strategy `kind` stands for, for drinking the way `graph` says.
+///
+/// # Panics
+///
+/// The same as `Graph::seat`, if the graph isn't valid.
+pub fn build(kind: Kind, graph: &Graph) -> Box<dyn Strategy> {
+    match kind {
+        Kind::Waiter => Box::new(Sommelier::new(graph.bottles.len())),
+        Kind::ChandyMisra => {
+            let bottles: Vec<Vec<usize>> = graph.seat().iter()
+                .map(|d| d.sessions.iter().flatten().cloned().collect())
+                .collect();
+            Box::new(ChandyMisra::sharing(&bottles))
+        }
+        _ => kind.build(graph.agents.len()),
+    }
+}
+
+/// Gives every drinker a thread and lets them get through their sessions,
+/// the same as `Simulation::run` does for dinner. `eat_time` is how long a
+/// session takes, and every session counts as a meal in the report.
+pub fn run(simulation: &Simulation, drinkers: Vec<Drinker>, table: Arc<Table>,
+           strategy: Arc<dyn Strategy>) -> Report {
+    let simulation = *simulation;
+
+    if let Some(scheduler) = table.scheduler() {
+        scheduler.expect(drinkers.len());
+    }
+    let handles: Vec<_> = drinkers.into_iter().map(|d| {
+        let table = table.clone();
+        let strategy = strategy.clone();
+        thread::spawn(move || {
+            let _seated = Seated::new(table.scheduler(), d.seat);
+            drink(&simulation, &d, &table, &*strategy)
+        })
+    }).collect();
+
+    let mut diners = Vec::new();
+    let mut elapsed = Duration::default();
+    for h in handles {
+        let (diner, finished) = h.join().unwrap();
+        diners.push(diner);
+        elapsed = elapsed.max(finished);
+    }
+
+    Report::new(diners, elapsed)
+}
+
+/// One agent's sessions, and when they finished.
+fn drink(simulation: &Simulation, d: &Drinker, table: &Table, strategy: &dyn Strategy)
+         -> (Diner, Duration) {
+    let clock = table.clock();
+    let mut diner = Diner::new(&d.name);
+    let mut sessions_left = match simulation.length {
+        Length::Rounds(rounds) => Some(rounds * d.sessions.len() as u32),
+        Length::Duration(_) => None,
+    };
+
+    let mut session = 0;
+    while simulation.another_meal(&mut sessions_left, clock.now()) {
+        let bottles = &d.sessions[session % d.sessions.len()];
+        session += 1;
+
+        table.record(d.seat, Event::Thinking);
+        clock.sleep(d.think_time);
+
+        let thirsty = clock.now();
+        table.record(d.seat, Event::Hungry);
+        strategy.dine(d.seat, bottles, table, &mut || {
+            diner.ate(clock.now() - thirsty);
+
+            table.record(d.seat, Event::Eating);
+            clock.sleep(simulation.eat_time);
+            table.record(d.seat, Event::Done);
+        });
+    }
+
+    (diner, clock.now())
+}
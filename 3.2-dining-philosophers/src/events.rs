@@ -0,0 +1,366 @@
+//! Everything that happens at the table, as it happens.
+//!
+//! Philosophers and forks report every change of state to the table's
+//! `EventSink`, stamped with the table's clock. Sinks can print them, keep
+//! them, or write them out as JSON Lines or as a Chrome trace, which
+//! `chrome://tracing` or <https://ui.perfetto.dev> show as a timeline with a
+//! row per philosopher.
+
+use std::fmt;
+use std::io;
+use std::io::prelude::*;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde_json::Value;
+
+/// Which hand a fork is in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+impl Side {
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Side::Left => "left",
+            Side::Right => "right",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Thinking,
+    /// Done thinking, and now waiting for forks.
+    Hungry,
+    PickedUp { side: Side, fork: usize },
+    Eating,
+    Done,
+    /// Panicked halfway through eating, so the meal's lost.
+    Dropped,
+    PutDown { side: Side, fork: usize },
+    /// Took a bottle from the cellar, for the drinking philosophers.
+    Took { bottle: usize },
+    Returned { bottle: usize },
+}
+
+impl Kind {
+    /// The name used in exported events.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Kind::Thinking => "thinking",
+            Kind::Hungry => "hungry",
+            Kind::PickedUp { .. } => "picked_up",
+            Kind::Eating => "eating",
+            Kind::Done => "done",
+            Kind::Dropped => "dropped",
+            Kind::PutDown { .. } => "put_down",
+            Kind::Took { .. } => "took",
+            Kind::Returned { .. } => "returned",
+        }
+    }
+}
+
+/// Something that happened to the philosopher at seat `philosopher`, `at`
+/// this long after the table's clock started.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Event {
+    pub at: Duration,
+    pub philosopher: usize,
+    pub kind: Kind,
+}
+
+impl Event {
+    /// The event as JSON, with the philosopher's name if we know it.
+    pub fn to_json(&self, names: &[String]) -> Value {
+        let mut json = json!({
+            "at_us": micros(self.at),
+            "philosopher": self.philosopher,
+            "event": self.kind.name(),
+        });
+        if let Some(name) = names.get(self.philosopher) {
+            json["name"] = json!(name);
+        }
+        match self.kind {
+            Kind::PickedUp { side, fork } | Kind::PutDown { side, fork } => {
+                json["side"] = json!(side.name());
+                json["fork"] = json!(fork);
+            }
+            Kind::Took { bottle } | Kind::Returned { bottle } => json["bottle"] = json!(bottle),
+            _ => {}
+        }
+        json
+    }
+}
+
+fn micros(duration: Duration) -> u64 {
+    duration.as_secs() * 1_000_000 + u64::from(duration.subsec_micros())
+}
+
+/// Where events go. Called from every philosopher's thread at once.
+pub trait EventSink: Send + Sync + fmt::Debug {
+    fn record(&self, event: &Event);
+
+    /// Called once dinner's over, to write out anything left over. Returns
+    /// the first error writing any of the events, if there was one.
+    fn finish(&self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Throws everything away.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Discard;
+
+impl EventSink for Discard {
+    fn record(&self, _event: &Event) {}
+}
+
+/// Keeps every event, in the order they were recorded.
+#[derive(Debug, Default)]
+pub struct Memory {
+    events: Mutex<Vec<Event>>,
+}
+
+impl Memory {
+    pub fn new() -> Memory {
+        Memory::default()
+    }
+
+    pub fn events(&self) -> Vec<Event> {
+        self.events.lock().unwrap().clone()
+    }
+}
+
+impl EventSink for Memory {
+    fn record(&self, event: &Event) {
+        self.events.lock().unwrap().push(*event);
+    }
+}
+
+/// Says when everybody starts and finishes eating, on stdout.
+#[derive(Debug, Clone, Default)]
+pub struct Printer {
+    names: Vec<String>,
+}
+
+impl Printer {
+    /// `names` are the philosophers' names by seat.
+    pub fn new(names: Vec<String>) -> Printer {
+        Printer { names }
+    }
+}
+
+impl EventSink for Printer {
+    fn record(&self, event: &Event) {
+        let name = self.names.get(event.philosopher).map(|name| name.as_str()).unwrap_or("?");
+        match event.kind {
+            Kind::Eating => println!("{} is eating.", name),
+            Kind::Done => println!("{} is done eating.", name),
+            Kind::Dropped => println!("{} dropped their fork.", name),
+            _ => {}
+        }
+    }
+}
+
+/// A writer, and the first thing that went wrong writing to it.
+#[derive(Debug)]
+struct Output<W> {
+    writer: W,
+    error: Option<io::Error>,
+}
+
+impl<W: Write> Output<W> {
+    fn new(writer: W) -> Output<W> {
+        Output { writer, error: None }
+    }
+
+    /// Writes `line` unless something has already gone wrong.
+    fn write(&mut self, line: &str) {
+        if self.error.is_none() {
+            if let Err(err) = self.writer.write_all(line.as_bytes()) {
+                self.error = Some(err);
+            }
+        }
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        match self.error.take() {
+            Some(err) => Err(err),
+            None => self.writer.flush(),
+        }
+    }
+}
+
+/// One JSON object per line, per event.
+#[derive(Debug)]
+pub struct JsonLines<W> {
+    names: Vec<String>,
+    output: Mutex<Output<W>>,
+}
+
+impl<W: Write + Send + fmt::Debug> JsonLines<W> {
+    pub fn new(writer: W, names: Vec<String>) -> JsonLines<W> {
+        JsonLines {
+            names,
+            output: Mutex::new(Output::new(writer)),
+        }
+    }
+
+    /// Gives back the writer.
+    pub fn into_inner(self) -> W {
+        self.output.into_inner().unwrap().writer
+    }
+}
+
+impl<W: Write + Send + fmt::Debug> EventSink for JsonLines<W> {
+    fn record(&self, event: &Event) {
+        let line = format!("{}\n", event.to_json(&self.names));
+        self.output.lock().unwrap().write(&line);
+    }
+
+    fn finish(&self) -> io::Result<()> {
+        self.output.lock().unwrap().finish()
+    }
+}
+
+/// Google's trace event format. Every philosopher gets a row, with spans for
+/// thinking, being hungry and eating, and a mark for every fork they pick up
+/// or put down.
+#[derive(Debug)]
+pub struct ChromeTrace<W> {
+    output: Mutex<Trace<W>>,
+}
+
+/// The trace so far, and whether anything's in it yet.
+#[derive(Debug)]
+struct Trace<W> {
+    output: Output<W>,
+    empty: bool,
+}
+
+impl<W: Write> Trace<W> {
+    /// Adds `event` to the list, after a comma unless it's the first.
+    fn push(&mut self, event: &Value) {
+        let separator = if self.empty { "" } else { ",\n" };
+        self.empty = false;
+        self.output.write(&format!("{}{}", separator, event));
+    }
+}
+
+impl<W: Write + Send + fmt::Debug> ChromeTrace<W> {
+    /// Starts the trace, naming a row after each of `names`.
+    pub fn new(writer: W, names: &[String]) -> ChromeTrace<W> {
+        let mut output = Output::new(writer);
+        output.write("[\n");
+        let mut trace = Trace { output, empty: true };
+        for (seat, name) in names.iter().enumerate() {
+            trace.push(&json!({
+                "name": "thread_name",
+                "ph": "M",
+                "pid": 1,
+                "tid": seat,
+                "args": {"name": name},
+            }));
+        }
+
+        ChromeTrace {
+            output: Mutex::new(trace),
+        }
+    }
+
+    /// Gives back the writer. Call `finish` first, or the trace won't be
+    /// finished.
+    pub fn into_inner(self) -> W {
+        self.output.into_inner().unwrap().output.writer
+    }
+}
+
+/// A trace event for the philosopher at `seat`.
+fn trace_event(phase: &str, name: &str, at: Duration, seat: usize) -> Value {
+    json!({"name": name, "ph": phase, "ts": micros(at), "pid": 1, "tid": seat})
+}
+
+impl<W: Write + Send + fmt::Debug> EventSink for ChromeTrace<W> {
+    fn record(&self, event: &Event) {
+        let (at, seat) = (event.at, event.philosopher);
+        let mut events = Vec::new();
+
+        // Each state lasts until the next one starts
+        match event.kind {
+            Kind::Thinking => events.push(trace_event("B", "thinking", at, seat)),
+            Kind::Hungry => {
+                events.push(trace_event("E", "thinking", at, seat));
+                events.push(trace_event("B", "hungry", at, seat));
+            }
+            Kind::Eating => {
+                events.push(trace_event("E", "hungry", at, seat));
+                events.push(trace_event("B", "eating", at, seat));
+            }
+            Kind::Done => events.push(trace_event("E", "eating", at, seat)),
+            Kind::Dropped => {
+                events.push(trace_event("E", "eating", at, seat));
+                let mut mark = trace_event("i", "dropped their fork", at, seat);
+                mark["s"] = json!("t");
+                events.push(mark);
+            }
+            Kind::PickedUp { side, fork } | Kind::PutDown { side, fork } => {
+                let verb = if let Kind::PickedUp { .. } = event.kind { "picked up" } else { "put down" };
+                let mut mark = trace_event("i", &format!("{} {} fork", verb, side.name()), at, seat);
+                mark["s"] = json!("t");
+                mark["args"] = json!({"fork": fork});
+                events.push(mark);
+            }
+            Kind::Took { bottle } | Kind::Returned { bottle } => {
+                let verb = if let Kind::Took { .. } = event.kind { "took" } else { "returned" };
+                let mut mark = trace_event("i", &format!("{} bottle", verb), at, seat);
+                mark["s"] = json!("t");
+                mark["args"] = json!({"bottle": bottle});
+                events.push(mark);
+            }
+        }
+
+        let mut trace = self.output.lock().unwrap();
+        for event in &events {
+            trace.push(event);
+        }
+    }
+
+    fn finish(&self) -> io::Result<()> {
+        let mut trace = self.output.lock().unwrap();
+        trace.output.write("\n]\n");
+        trace.output.finish()
+    }
+}
+
+/// Sends every event to all of its sinks.
+#[derive(Debug, Default)]
+pub struct Tee {
+    sinks: Vec<Arc<dyn EventSink>>,
+    /// Held while an event goes round the sinks, so they all see events
+    /// in the same order.
+    order: Mutex<()>,
+}
+
+impl Tee {
+    pub fn new(sinks: Vec<Arc<dyn EventSink>>) -> Tee {
+        Tee { sinks, order: Mutex::new(()) }
+    }
+}
+
+impl EventSink for Tee {
+    fn record(&self, event: &Event) {
+        let _order = self.order.lock().unwrap();
+        for sink in &self.sinks {
+            sink.record(event);
+        }
+    }
+
+    fn finish(&self) -> io::Result<()> {
+        // Finish them all, even if one of them fails
+        let results: Vec<io::Result<()>> = self.sinks.iter().map(|sink| sink.finish()).collect();
+        results.into_iter().collect()
+    }
+}
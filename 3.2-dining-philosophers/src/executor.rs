@@ -0,0 +1,151 @@
+//! Just enough of an async runtime to seat philosophers as tasks instead of
+//! threads.
+//!
+//! An `Executor` runs its tasks one at a time on the thread that calls
+//! `run`, polling whichever have been woken up. When none of them can go
+//! on, it sleeps the clock until the next `Sleep` is due. If there's no
+//! `Sleep` to wait for either, the tasks are stuck for good.
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::mem;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Wake, Waker};
+use std::time::Duration;
+
+use clock::Clock;
+
+thread_local! {
+    /// Every `Sleep` that's pending on this thread, with when it's due and
+    /// the task to wake then.
+    static TIMERS: RefCell<Vec<(Duration, Waker)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Waits for `duration` by `clock`, without blocking the thread. Only works
+/// inside an `Executor`, which is what moves the clock on.
+pub fn sleep(clock: &dyn Clock, duration: Duration) -> Sleep<'_> {
+    Sleep {
+        until: clock.now() + duration,
+        clock,
+    }
+}
+
+/// A future from `sleep`.
+#[derive(Debug)]
+pub struct Sleep<'a> {
+    clock: &'a dyn Clock,
+    until: Duration,
+}
+
+impl<'a> Future for Sleep<'a> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+        if self.clock.now() >= self.until {
+            return Poll::Ready(());
+        }
+        TIMERS.with(|timers| timers.borrow_mut().push((self.until, cx.waker().clone())));
+        Poll::Pending
+    }
+}
+
+/// The tasks that never finished, by the order they were spawned in,
+/// because every one of them was waiting on something that would never
+/// happen.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Stalled {
+    pub tasks: Vec<usize>,
+}
+
+/// Wakes a task by putting it back in the queue.
+struct Task {
+    index: usize,
+    ready: Arc<Mutex<VecDeque<usize>>>,
+}
+
+impl Wake for Task {
+    fn wake(self: Arc<Self>) {
+        self.ready.lock().unwrap().push_back(self.index);
+    }
+}
+
+/// Runs futures that all produce a `T`, on one thread.
+pub struct Executor<'a, T> {
+    clock: &'a dyn Clock,
+    tasks: Vec<Option<Pin<Box<dyn Future<Output = T> + 'a>>>>,
+    ready: Arc<Mutex<VecDeque<usize>>>,
+}
+
+impl<'a, T> Executor<'a, T> {
+    /// An executor whose `Sleep`s go by `clock`.
+    pub fn new(clock: &'a dyn Clock) -> Executor<'a, T> {
+        Executor {
+            clock,
+            tasks: Vec::new(),
+            ready: Arc::new(Mutex::new(VecDeque::new())),
+        }
+    }
+
+    /// Adds a task, which starts once the executor is `run`.
+    pub fn spawn<F: Future<Output = T> + 'a>(&mut self, future: F) {
+        self.ready.lock().unwrap().push_back(self.tasks.len());
+        self.tasks.push(Some(Box::pin(future)));
+    }
+
+    /// Runs every task to the end, and gives back what they returned in the
+    /// order they were spawned. Tasks that stalled are kept until the
+    /// executor is dropped, so whatever they were waiting on can still be
+    /// looked at.
+    pub fn run(&mut self) -> Result<Vec<T>, Stalled> {
+        // Timers left over from anything else that ran on this thread
+        TIMERS.with(|timers| timers.borrow_mut().clear());
+
+        let mut results: Vec<Option<T>> = self.tasks.iter().map(|_| None).collect();
+        let mut unfinished = self.tasks.len();
+
+        while unfinished > 0 {
+            let next = self.ready.lock().unwrap().pop_front();
+            if let Some(index) = next {
+                // A task can be woken more than once, even after it's done
+                let poll = match self.tasks[index] {
+                    Some(ref mut task) => {
+                        let waker = Waker::from(Arc::new(Task { index, ready: self.ready.clone() }));
+                        task.as_mut().poll(&mut Context::from_waker(&waker))
+                    }
+                    None => continue,
+                };
+                if let Poll::Ready(result) = poll {
+                    self.tasks[index] = None;
+                    results[index] = Some(result);
+                    unfinished -= 1;
+                }
+                continue;
+            }
+
+            // Nobody can do anything until the next timer goes off
+            let timers = TIMERS.with(|timers| mem::take(&mut *timers.borrow_mut()));
+            let due = match timers.iter().map(|&(until, _)| until).min() {
+                Some(due) => due,
+                None => {
+                    let tasks = (0..self.tasks.len()).filter(|&i| self.tasks[i].is_some()).collect();
+                    return Err(Stalled { tasks });
+                }
+            };
+            let now = self.clock.now();
+            if due > now {
+                self.clock.sleep(due - now);
+            }
+
+            let now = self.clock.now();
+            let (due, later): (Vec<_>, Vec<_>) = timers.into_iter().partition(|&(until, _)| until <= now);
+            TIMERS.with(|timers| timers.borrow_mut().extend(later));
+            for (_, waker) in due {
+                waker.wake();
+            }
+        }
+
+        Ok(results.into_iter().map(|result| result.unwrap()).collect())
+    }
+}
@@ -0,0 +1,332 @@
+//! A fork that remembers who's holding it and who's waiting for it, so a
+//! `Watchdog` can tell when the table has deadlocked. It also tells the
+//! table's `EventSink` whenever it's picked up or put down.
+//!
+//! A philosopher who panics while holding a fork poisons it, the same as
+//! any `Mutex`. On a table laid `with_recovery`, whoever picks it up next
+//! cleans it off instead of panicking too, and the fork counts how often
+//! that's happened.
+
+use std::future::Future;
+use std::mem;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, LockResult, Mutex, MutexGuard, PoisonError, TryLockError, TryLockResult};
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
+
+use clock::{Clock, RealClock, Scheduler};
+use events::{Discard, Event, EventSink, Kind, Side};
+
+/// Stands in for "nobody" in `Fork::holder`.
+const NOBODY: usize = usize::MAX;
+
+/// What a `Fork` really is. It's locked the same either way, but not
+/// talked about the same.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resource {
+    /// A fork between two neighbours round a table, picked up and put down.
+    Fork,
+    /// A bottle in a `Table::cellar`, which isn't on either side of
+    /// anybody, so it's taken and returned instead.
+    Bottle,
+}
+
+impl Resource {
+    /// What one's called.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Resource::Fork => "fork",
+            Resource::Bottle => "bottle",
+        }
+    }
+
+    /// What everybody who needs them is called.
+    pub fn users(&self) -> &'static str {
+        match *self {
+            Resource::Fork => "philosophers",
+            Resource::Bottle => "agents",
+        }
+    }
+}
+
+/// What picking up a fork somebody panicked holding does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Poison {
+    /// Hands back an error, the same as a `Mutex`.
+    Stays,
+    /// Cleans it off and hands it over as if nothing had happened.
+    Recovered,
+}
+
+/// A lock that knows which philosopher (by seat number) is holding it, and
+/// which are queued up for it. Poisoning works the same as for a `Mutex`,
+/// unless it's `recovering`.
+///
+/// It also remembers when it was last put down, by its `Clock`, so that
+/// whoever picks it up next can't be any earlier than that.
+///
+/// Threads wait for it with `lock`, and async tasks with `pick_up`.
+#[derive(Debug)]
+pub struct Fork {
+    /// When the fork was last put down.
+    lock: Mutex<Duration>,
+    holder: AtomicUsize,
+    waiters: Mutex<Vec<usize>>,
+    /// Tasks to wake when the fork is put down.
+    wakers: Mutex<Vec<Waker>>,
+    clock: Arc<dyn Clock>,
+    /// Who decides who runs when, if anybody does.
+    scheduler: Option<Arc<dyn Scheduler>>,
+    /// Where the fork is on the table, for events.
+    index: usize,
+    resource: Resource,
+    events: Arc<dyn EventSink>,
+    poison: Poison,
+    /// How many times it has been.
+    recoveries: AtomicUsize,
+}
+
+/// Holding a fork. Puts it back down when dropped.
+#[derive(Debug)]
+pub struct ForkGuard<'a> {
+    fork: &'a Fork,
+    who: usize,
+    /// Only ever `None` while it's being dropped.
+    guard: Option<MutexGuard<'a, Duration>>,
+}
+
+/// Waiting for a fork without blocking the thread, from `Fork::pick_up`.
+#[derive(Debug)]
+pub struct PickUp<'a> {
+    fork: &'a Fork,
+    who: usize,
+    waiting: bool,
+}
+
+impl Default for Fork {
+    fn default() -> Fork {
+        Fork::new()
+    }
+}
+
+impl Fork {
+    pub fn new() -> Fork {
+        Fork::with_clock(Arc::new(RealClock::new()))
+    }
+
+    pub fn with_clock(clock: Arc<dyn Clock>) -> Fork {
+        Fork::laid(0, Resource::Fork, clock, Arc::new(Discard), None, Poison::Stays)
+    }
+
+    /// A fork that never stays poisoned: whoever picks it up after a panic
+    /// gets it as if nothing had happened.
+    pub fn recovering() -> Fork {
+        Fork::laid(0, Resource::Fork, Arc::new(RealClock::new()), Arc::new(Discard), None, Poison::Recovered)
+    }
+
+    /// Fork (or bottle) number `index`, reporting to `events` and taking
+    /// turns by `scheduler`.
+    pub(crate) fn laid(index: usize, resource: Resource, clock: Arc<dyn Clock>, events: Arc<dyn EventSink>,
+                       scheduler: Option<Arc<dyn Scheduler>>, poison: Poison) -> Fork {
+        Fork {
+            lock: Mutex::new(Duration::default()),
+            holder: AtomicUsize::new(NOBODY),
+            waiters: Mutex::new(Vec::new()),
+            wakers: Mutex::new(Vec::new()),
+            clock,
+            scheduler,
+            index,
+            resource,
+            events,
+            poison,
+            recoveries: AtomicUsize::new(0),
+        }
+    }
+
+    /// Picks the fork up for the philosopher at seat `who`, waiting for as
+    /// long as it takes.
+    pub fn lock(&self, who: usize) -> LockResult<ForkGuard<'_>> {
+        self.turn();
+        self.waiters().push(who);
+        let result = match self.scheduler {
+            Some(ref scheduler) => self.wait_turn(&**scheduler),
+            None => self.lock.lock(),
+        };
+        self.waiters().retain(|&waiter| waiter != who);
+
+        match result {
+            Ok(guard) => Ok(self.hold(who, guard)),
+            Err(poisoned) => self.poisoned(who, poisoned.into_inner()),
+        }
+    }
+
+    /// Picks the fork up for `who` if nobody else has it.
+    pub fn try_lock(&self, who: usize) -> TryLockResult<ForkGuard<'_>> {
+        self.turn();
+        match self.lock.try_lock() {
+            Ok(guard) => Ok(self.hold(who, guard)),
+            Err(TryLockError::Poisoned(poisoned)) => {
+                self.poisoned(who, poisoned.into_inner()).map_err(TryLockError::Poisoned)
+            }
+            Err(TryLockError::WouldBlock) => Err(TryLockError::WouldBlock),
+        }
+    }
+
+    /// Picks the fork up for `who` once it's free, without blocking.
+    pub fn pick_up(&self, who: usize) -> PickUp<'_> {
+        PickUp { fork: self, who, waiting: false }
+    }
+
+    /// Who's holding the fork right now, if anybody.
+    pub fn holder(&self) -> Option<usize> {
+        match self.holder.load(Ordering::SeqCst) {
+            NOBODY => None,
+            holder => Some(holder),
+        }
+    }
+
+    /// Who's waiting for the fork, in the order they started waiting.
+    pub fn waiting(&self) -> Vec<usize> {
+        self.waiters().clone()
+    }
+
+    /// Cleans the fork off if it's lying there poisoned, and says whether
+    /// it was. A fork somebody is holding is left alone, since they'll
+    /// have cleaned it off when they picked it up, if it's recovering.
+    pub fn recover(&self) -> bool {
+        match self.lock.try_lock() {
+            Err(TryLockError::Poisoned(_)) => {
+                // Nobody else can get at it while we've got the lock, so
+                // it can't be counted twice
+                self.lock.clear_poison();
+                self.recoveries.fetch_add(1, Ordering::SeqCst);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// How many times the fork has been cleaned off after a panic.
+    pub fn recoveries(&self) -> usize {
+        self.recoveries.load(Ordering::SeqCst)
+    }
+
+    /// Waits for the fork by stalling on `scheduler` rather than blocking,
+    /// so whoever's holding it gets to run and put it down.
+    fn wait_turn(&self, scheduler: &dyn Scheduler) -> LockResult<MutexGuard<'_, Duration>> {
+        loop {
+            match self.lock.try_lock() {
+                Ok(guard) => return Ok(guard),
+                Err(TryLockError::Poisoned(poisoned)) => return Err(poisoned),
+                Err(TryLockError::WouldBlock) => scheduler.stall(),
+            }
+        }
+    }
+
+    /// Lets the scheduler, if there is one, have somebody else go first.
+    fn turn(&self) {
+        if let Some(ref scheduler) = self.scheduler {
+            scheduler.turn();
+        }
+    }
+
+    /// Picks the fork up for `who` even though it was poisoned. It's an
+    /// error unless the fork is recovering.
+    fn poisoned<'a>(&'a self, who: usize, guard: MutexGuard<'a, Duration>) -> LockResult<ForkGuard<'a>> {
+        if self.poison == Poison::Stays {
+            return Err(PoisonError::new(self.hold(who, guard)));
+        }
+        self.lock.clear_poison();
+        self.recoveries.fetch_add(1, Ordering::SeqCst);
+        Ok(self.hold(who, guard))
+    }
+
+    fn hold<'a>(&'a self, who: usize, guard: MutexGuard<'a, Duration>) -> ForkGuard<'a> {
+        self.clock.catch_up(*guard);
+        self.holder.store(who, Ordering::SeqCst);
+        self.record(who, true);
+        ForkGuard { fork: self, who, guard: Some(guard) }
+    }
+
+    /// Tells the table's sink that `who` just picked the fork up, or put it
+    /// down. Fork `seat` is on the left of whoever sits at `seat`, the way
+    /// `Roster::seat` lays the table, so any other fork is on their right.
+    fn record(&self, who: usize, picked_up: bool) {
+        let (index, side) = (self.index, if self.index == who { Side::Left } else { Side::Right });
+        let kind = match (self.resource, picked_up) {
+            (Resource::Fork, true) => Kind::PickedUp { side, fork: index },
+            (Resource::Fork, false) => Kind::PutDown { side, fork: index },
+            (Resource::Bottle, true) => Kind::Took { bottle: index },
+            (Resource::Bottle, false) => Kind::Returned { bottle: index },
+        };
+        self.events.record(&Event {
+            at: self.clock.now(),
+            philosopher: who,
+            kind,
+        });
+    }
+
+    fn waiters(&self) -> MutexGuard<'_, Vec<usize>> {
+        // The list is only ever pushed to and filtered, so a panic can't
+        // leave it in a state worth refusing to look at
+        self.waiters.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<'a> Drop for ForkGuard<'a> {
+    fn drop(&mut self) {
+        if let Some(mut guard) = self.guard.take() {
+            self.fork.turn();
+            *guard = self.fork.clock.now();
+            self.fork.record(self.who, false);
+            // Before the lock is let go, so whoever gets the fork next
+            // can't have their name overwritten
+            self.fork.holder.store(NOBODY, Ordering::SeqCst);
+        }
+
+        // Only once it really is free, or a task could wake up, find it
+        // still taken, and go back to sleep with nobody left to wake it
+        let wakers = mem::take(&mut *self.fork.wakers.lock().unwrap());
+        for waker in wakers {
+            waker.wake();
+        }
+        if let Some(ref scheduler) = self.fork.scheduler {
+            scheduler.changed();
+        }
+    }
+}
+
+impl<'a> Future for PickUp<'a> {
+    type Output = LockResult<ForkGuard<'a>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let (fork, who) = (self.fork, self.who);
+        if !self.waiting {
+            fork.waiters().push(who);
+            self.waiting = true;
+        }
+
+        // Ask to be woken before trying, so a fork put down in between
+        // isn't missed
+        fork.wakers.lock().unwrap().push(cx.waker().clone());
+        let result = match fork.lock.try_lock() {
+            Ok(guard) => Ok(fork.hold(who, guard)),
+            Err(TryLockError::Poisoned(poisoned)) => fork.poisoned(who, poisoned.into_inner()),
+            Err(TryLockError::WouldBlock) => return Poll::Pending,
+        };
+
+        fork.waiters().retain(|&waiter| waiter != who);
+        self.waiting = false;
+        Poll::Ready(result)
+    }
+}
+
+impl<'a> Drop for PickUp<'a> {
+    fn drop(&mut self) {
+        // Given up on before getting the fork
+        if self.waiting {
+            self.fork.waiters().retain(|&waiter| waiter != self.who);
+        }
+    }
+}
@@ -0,0 +1,242 @@
+//! The dining philosophers: everyone at the table needs both the fork on
+//! their left and the fork on their right to eat, and there's only one fork
+//! between each pair of neighbours.
+//!
+//! How philosophers get hold of their forks without deadlocking is up to a
+//! `Strategy`, so different approaches can be compared on the same `Table`.
+
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+#[macro_use]
+extern crate serde_json;
+extern crate termion;
+extern crate toml;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+pub mod board;
+pub mod cli;
+pub mod clock;
+pub mod drinking;
+pub mod events;
+pub mod executor;
+pub mod fork;
+pub mod model;
+pub mod roster;
+pub mod simulation;
+pub mod strategy;
+pub mod supervisor;
+pub mod tasks;
+pub mod tui;
+pub mod watchdog;
+
+pub use clock::{Clock, DiscreteClock, PacedClock, RealClock, Scheduler, VirtualClock};
+pub use events::{Event, EventSink};
+pub use fork::{Fork, ForkGuard, Poison, Resource};
+pub use roster::{Guest, Roster};
+pub use simulation::{Length, Mode, Report, Simulation};
+pub use strategy::{Kind, Strategy};
+pub use supervisor::{Failure, Supervisor};
+
+// Create a struct that represents a philosopher
+// Store a property "name" of type `String` -- generally
+// better for structures to own its own data, rather than using
+// references
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Philosopher {
+    pub name: String,
+    pub left: usize,
+    pub right: usize,
+    /// How many meals they want.
+    pub appetite: u32,
+    /// How long they think before each meal.
+    pub think_time: Duration,
+    /// Meals, counting from 1, that they panic halfway through.
+    pub fumbles: Vec<u32>,
+}
+
+impl Philosopher {
+    // we define an "associated function" called new,
+    // that takes a &str and returns a Philosopher.
+    pub fn new(name: &str, left: usize, right: usize) -> Philosopher {
+        Philosopher {
+            name: name.to_string(),
+            left,
+            right,
+            appetite: 1,
+            think_time: Duration::from_millis(0),
+            fumbles: Vec::new(),
+        }
+    }
+
+    /// Where this philosopher sits, which is the number of their left fork.
+    pub fn seat_number(&self) -> usize {
+        self.left
+    }
+
+    /// Picks up both forks however `strategy` says to, calls `eat`, and
+    /// puts them back down.
+    pub fn eat<F: FnMut()>(&self, table: &Table, strategy: &dyn Strategy, mut eat: F) {
+        strategy.dine(self.seat_number(), &[self.left, self.right], table, &mut eat);
+    }
+}
+
+/// The forks, and the clock on the wall. Each fork is a `Fork`, so only one
+/// philosopher can hold it at a time, and everybody can see who.
+///
+/// Whatever happens at the table is sent to its `EventSink`, which throws
+/// it all away unless it's given another with `with_events`.
+///
+/// A fork somebody panicked holding stays poisoned, unless the table is
+/// laid `with_recovery`.
+///
+/// Who runs when is up to the OS, unless the table is given a `Scheduler`
+/// `with_scheduler`.
+#[derive(Debug)]
+pub struct Table {
+    forks: Vec<Fork>,
+    /// Whether they're really forks, or bottles in a `cellar`.
+    resource: Resource,
+    clock: Arc<dyn Clock>,
+    events: Arc<dyn EventSink>,
+    scheduler: Option<Arc<dyn Scheduler>>,
+    poison: Poison,
+}
+
+impl Table {
+    /// A table laid with `seats` forks, one between each pair of neighbours.
+    ///
+    /// # Panics
+    ///
+    /// With fewer than two seats, since a lonely philosopher would need the
+    /// same fork in both hands.
+    pub fn new(seats: usize) -> Table {
+        Table::with_clock(seats, Arc::new(RealClock::new()))
+    }
+
+    /// A table where time is kept by `clock`.
+    pub fn with_clock(seats: usize, clock: Arc<dyn Clock>) -> Table {
+        assert!(seats >= 2, "a table needs at least two seats, not {}", seats);
+        Table::laid(seats, Resource::Fork, clock)
+    }
+
+    /// A table set with `bottles` bottles instead of forks, for
+    /// `drinking`. Any number of agents can share a bottle, so there's no
+    /// telling which side of anybody it's on, and they're taken and
+    /// returned rather than picked up and put down.
+    pub fn cellar(bottles: usize, clock: Arc<dyn Clock>) -> Table {
+        Table::laid(bottles, Resource::Bottle, clock)
+    }
+
+    fn laid(count: usize, resource: Resource, clock: Arc<dyn Clock>) -> Table {
+        let events: Arc<dyn EventSink> = Arc::new(events::Discard);
+        Table {
+            forks: Table::lay(count, resource, &clock, &events, &None, Poison::Stays),
+            resource,
+            clock,
+            events,
+            scheduler: None,
+            poison: Poison::Stays,
+        }
+    }
+
+    /// The same table, but telling `events` about everything that happens
+    /// at it.
+    pub fn with_events(self, events: Arc<dyn EventSink>) -> Table {
+        Table {
+            forks: Table::lay(self.seats(), self.resource, &self.clock, &events, &self.scheduler, self.poison),
+            events,
+            ..self
+        }
+    }
+
+    /// The same table, but with forks that get cleaned off when somebody
+    /// panics holding them, instead of poisoning everybody who picks them
+    /// up after.
+    pub fn with_recovery(self) -> Table {
+        let poison = Poison::Recovered;
+        Table {
+            forks: Table::lay(self.seats(), self.resource, &self.clock, &self.events, &self.scheduler, poison),
+            poison,
+            ..self
+        }
+    }
+
+    /// The same table, but with `scheduler` deciding who runs when. It's
+    /// usually the table's clock as well.
+    pub fn with_scheduler(self, scheduler: Arc<dyn Scheduler>) -> Table {
+        let scheduler = Some(scheduler);
+        Table {
+            forks: Table::lay(self.seats(), self.resource, &self.clock, &self.events, &scheduler, self.poison),
+            scheduler,
+            ..self
+        }
+    }
+
+    fn lay(count: usize, resource: Resource, clock: &Arc<dyn Clock>, events: &Arc<dyn EventSink>,
+           scheduler: &Option<Arc<dyn Scheduler>>, poison: Poison) -> Vec<Fork> {
+        (0..count)
+            .map(|index| Fork::laid(index, resource, clock.clone(), events.clone(), scheduler.clone(), poison))
+            .collect()
+    }
+
+    /// Whether the forks are really forks, or bottles in a `cellar`.
+    pub fn resource(&self) -> Resource {
+        self.resource
+    }
+
+    /// Whether the table was laid `with_recovery`.
+    pub fn is_recovering(&self) -> bool {
+        self.poison == Poison::Recovered
+    }
+
+    pub fn seats(&self) -> usize {
+        self.forks.len()
+    }
+
+    pub fn fork(&self, index: usize) -> &Fork {
+        &self.forks[index]
+    }
+
+    pub fn clock(&self) -> &dyn Clock {
+        &*self.clock
+    }
+
+    pub fn events(&self) -> &dyn EventSink {
+        &*self.events
+    }
+
+    /// Who decides who runs when, if anybody does.
+    pub fn scheduler(&self) -> Option<&dyn Scheduler> {
+        self.scheduler.as_deref()
+    }
+
+    /// The calling thread is about to touch something the philosophers
+    /// share, so the table's scheduler, if it has one, could let somebody
+    /// else go first.
+    pub fn turn(&self) {
+        if let Some(scheduler) = self.scheduler() {
+            scheduler.turn();
+        }
+    }
+
+    /// Tells the table's scheduler, if it has one, that something a stalled
+    /// thread might be waiting for has changed.
+    pub fn changed(&self) {
+        if let Some(scheduler) = self.scheduler() {
+            scheduler.changed();
+        }
+    }
+
+    /// Tells the table's sink that `kind` just happened to the philosopher
+    /// at `seat`.
+    pub fn record(&self, seat: usize, kind: events::Kind) {
+        self.events.record(&Event {
+            at: self.clock.now(),
+            philosopher: seat,
+            kind,
+        });
+    }
+}
@@ -1,112 +1,219 @@
-use std::sync::{Mutex, Arc};
-use std::thread;
-
-// Create a struct that represents a philosopher
-// Store a property "name" of type `String` -- generally
-// better for structures to own its own data, rather than using
-// references
-struct Philosopher {
-    name: String,
-    left: usize,
-    right: usize,
-}
+extern crate dining_philosophers;
+
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+use std::process;
+use std::sync::Arc;
+use std::time::Duration;
+
+use dining_philosophers::{Clock, EventSink, Mode, PacedClock, Roster, Strategy, Supervisor, Table,
+                          tasks};
+use dining_philosophers::board::Board;
+use dining_philosophers::drinking::{self, Graph};
+use dining_philosophers::cli::{self, Options};
+use dining_philosophers::events::{ChromeTrace, JsonLines, Printer, Tee};
+use dining_philosophers::tui::Tui;
+use dining_philosophers::watchdog::Watchdog;
 
-// We define things on the struct;
-impl Philosopher {
-    // we define an "associated function" called new,
-    // that takes a &str and returns a Philosopher.
-    fn new(name: &str, left: usize, right: usize) -> Philosopher {
-        Philosopher {
-            name: name.to_string(),
-            left: left,
-            right: right,
+fn main() {
+    let options = match Options::from_env() {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("error: {}\n\n{}", err, cli::USAGE);
+            process::exit(cli::EXIT_USAGE);
         }
+    };
+
+    if options.help {
+        println!("{}", cli::USAGE);
+        return;
+    }
+    if let Some(ref path) = options.graph {
+        drink(&options, path);
+        return;
     }
 
-    // Methods take an explicit `self` parameter;
-    // distinguishing it from the static method (or "associated function")
-    fn eat(&self, table: &Table) {
-        // Use an underscored variable name to indicate
-        // to rust that we're not going to use these variables,
-        // and we know that, so don't show an error
-        //
-        // Accesses a mutex from the fork table, and calls lock, which
-        // either locks the thread, or waits if the thread is unlocked
-        //
-        // Just use unwrap() here as well since we do not expect the threads
-        // to panic and fail
-        let _left = table.forks[self.left].lock().unwrap();
-        // The locks release when _left and _right go out of scope
-        let _right = table.forks[self.right].lock().unwrap();
-
-        println!("{} is eating.", self.name);
-        thread::sleep_ms(1000);
-        println!("{} is done eating.", self.name);
+    let roster = match (&options.roster, options.count) {
+        (Some(path), _) => Roster::load(path).unwrap_or_else(|err| {
+            eprintln!("error: {}", err);
+            process::exit(cli::EXIT_ROSTER);
+        }),
+        (_, Some(count)) => Roster::generated(count),
+        _ => Roster::default(),
+    };
+
+    // a `Vec<T>`, or a vector, is a growable array type.
+    let mut philosophers = roster.seat();
+    for &(seat, meal) in &options.fumbles {
+        match philosophers.get_mut(seat) {
+            Some(p) => p.fumbles.push(meal),
+            None => {
+                eprintln!("error: nobody's sitting at seat {}, there are only {}", seat, roster.len());
+                process::exit(cli::EXIT_USAGE);
+            }
+        }
     }
-}
+    let names: Vec<String> = philosophers.iter().map(|p| p.name.clone()).collect();
 
-struct Table {
-    forks: Vec<Mutex<()>>,
-}
+    // Everything that happens at the table goes to every one of these
+    let mut sinks: Vec<Arc<dyn EventSink>> = Vec::new();
+    let board = Arc::new(Board::new(names.clone()));
+    if options.tui {
+        sinks.push(board.clone());
+    } else if !options.quiet {
+        sinks.push(Arc::new(Printer::new(names.clone())));
+    }
+    files(&options, &names, &mut sinks);
+    let events = Arc::new(Tee::new(sinks));
 
-fn main() {
     // Arc stands for "atomic reference count", which we need
     // to share Table across multiple threads
     // as we share it, the reference count will go up and when each thread ends, it will go back
     // down.
-    let table = Arc::new(Table { forks: vec![
-        Mutex::new(()),
-        Mutex::new(()),
-        Mutex::new(()),
-        Mutex::new(()),
-        Mutex::new(()),
-    ]});
-
-    // a `Vec<T>`, or a vector, is a growable array type.
-    let philosophers = vec![
-        Philosopher::new("Mikael Stanne", 0, 1),
-        Philosopher::new("Jesper Strömblad", 1, 2),
-        Philosopher::new("Glenn Ljungström", 2, 3),
-        Philosopher::new("Anders Fridén", 3, 4),
-        // Can create Philosopher without the `new` method
-        // Notice how the last one doesn't match the pattern, otherwise we'd have deadlock
-        Philosopher { name: "Niclas Engelin".to_string(), left: 0, right: 4 },
-    ];
-
-    // `let handles: Vec<_> =`
-    // Explicitly annotating the type Vec<_>,
-    // with type `_` which is a placeholder type
-    // meaning "Rust will figure it out"
     //
-    // `philosophers.into_iter().map(|p| {`
-    // We create an iterator from our philosophers vector,
-    // takes ownership of each philosophers to pass into our threads.
-    // Map takes a closure so that thread::spawn is using the
-    // correct `p` value.
-    let handles: Vec<_> = philosophers.into_iter().map(|p| {
-        // clone() method on Arc<T> is what bumps up the ref count,
-        // and decrements when falls out of scope. We want to track how many refs
-        // to table exist across our threads. If we didn't have a count, we swouldn't
-        // know how to deallocate it.
-        let table = table.clone(); // shadow binding
-
-        // Executes the closure passed into `thread::spawn` in
-        // a new thread
-        //
-        // We annotate with `move` indicating that the closure
-        // is going to take ownership of the values its capturing,
-        // the `p` from the current closure.
-        thread::spawn(move || {
-            p.eat(&table);
-        }) // nnote lack of semicolon; expression, returns return
-        // values of thread::spawn calls which are handles to those threads
-    }).collect(); // makes them into some kind of collection, this is why we needed
-                  // `Vec<_>`
-
-    for h in handles {
-        // `join()` blocks execution until the thread has completed execution.
-        // So all programs will complete their work before moving on to the next
-        // handle in the loop
-        h.join().unwrap();
+    // One fork per philosopher
+    let paced = Arc::new(PacedClock::new());
+    let (clock, scheduler) = if options.tui { (paced.clone() as Arc<dyn Clock>, None) } else { options.clock.build() };
+    let mut table = Table::with_clock(roster.len(), clock).with_events(events.clone());
+    if let Some(scheduler) = scheduler {
+        table = table.with_scheduler(scheduler);
+    }
+    if options.supervise {
+        table = table.with_recovery();
+    }
+    let table = Arc::new(table);
+
+    let tui = if options.tui {
+        let step = options.simulation.eat_time / 4;
+        match Tui::start(board, paced, step) {
+            Ok(tui) => Some(tui),
+            Err(err) => {
+                eprintln!("error: can't draw the table here: {}", err);
+                process::exit(cli::EXIT_USAGE);
+            }
+        }
+    } else {
+        None
+    };
+    // Anything that gives up early has to give the terminal back first
+    let close = move |tui: &Option<Arc<Tui>>| {
+        if let Some(ref tui) = *tui {
+            tui.close();
+        }
+    };
+
+    let report = match options.mode {
+        Mode::Serial => {
+            // One at a time can't deadlock, so there's nothing to watch
+            let strategy = options.strategy.build(table.seats());
+            options.simulation.run_serial(philosophers, &table, &*strategy)
+        }
+        Mode::Threaded => {
+            // Everybody sits the same way round, it's up to the strategy to
+            // keep them from deadlocking
+            let strategy: Arc<dyn Strategy> = Arc::from(options.strategy.build(table.seats()));
+
+            // A deadlocked table never finishes dinner, so give up and say
+            // who was stuck instead
+            let watching = tui.clone();
+            let watchdog = Watchdog::spawn(table.clone(), names, Duration::from_millis(100), move |deadlock| {
+                close(&watching);
+                eprintln!("{}", deadlock);
+                process::exit(cli::EXIT_DEADLOCK);
+            });
+
+            let report = if options.supervise {
+                Supervisor::default().run(&options.simulation, philosophers, table, strategy)
+            } else {
+                options.simulation.run(philosophers, table, strategy)
+            };
+            watchdog.stop();
+            report
+        }
+        Mode::Async => {
+            let strategy = options.strategy.build(table.seats());
+            match tasks::run(&options.simulation, philosophers, &table, &*strategy) {
+                Ok(report) => report,
+                Err(tasks::Error::Deadlock(deadlock)) => {
+                    close(&tui);
+                    eprintln!("{}", deadlock);
+                    process::exit(cli::EXIT_DEADLOCK);
+                }
+            }
+        }
+    };
+
+    if let Some(ref tui) = tui {
+        tui.finish();
+    } else if !options.quiet {
+        println!();
+    }
+    for line in report.lines(options.starvation) {
+        println!("{}", line);
+    }
+
+    finish(&*events);
+}
+
+/// The drinking philosophers, instead of dinner.
+fn drink(options: &Options, path: &Path) {
+    let graph = Graph::load(path).unwrap_or_else(|err| {
+        eprintln!("error: {}", err);
+        process::exit(cli::EXIT_ROSTER);
+    });
+    let drinkers = graph.seat();
+    let names: Vec<String> = drinkers.iter().map(|d| d.name.clone()).collect();
+
+    let mut sinks: Vec<Arc<dyn EventSink>> = Vec::new();
+    files(options, &names, &mut sinks);
+    let events = Arc::new(Tee::new(sinks));
+
+    // One bottle is a fork like any other, as far as the table's concerned
+    let (clock, scheduler) = options.clock.build();
+    let mut table = Table::cellar(graph.bottles.len(), clock).with_events(events.clone());
+    if let Some(scheduler) = scheduler {
+        table = table.with_scheduler(scheduler);
+    }
+    let table = Arc::new(table);
+    let strategy: Arc<dyn Strategy> = Arc::from(drinking::build(options.strategy, &graph));
+
+    let watchdog = Watchdog::spawn(table.clone(), names, Duration::from_millis(100), |deadlock| {
+        eprintln!("{}", deadlock);
+        process::exit(cli::EXIT_DEADLOCK);
+    });
+    let report = drinking::run(&options.simulation, drinkers, table, strategy);
+    watchdog.stop();
+
+    for line in report.lines(options.starvation) {
+        println!("{}", line);
+    }
+    finish(&*events);
+}
+
+/// The event log and the trace, if they were asked for.
+fn files(options: &Options, names: &[String], sinks: &mut Vec<Arc<dyn EventSink>>) {
+    if let Some(ref path) = options.events {
+        sinks.push(Arc::new(JsonLines::new(create(path), names.to_vec())));
+    }
+    if let Some(ref path) = options.trace {
+        sinks.push(Arc::new(ChromeTrace::new(create(path), names)));
+    }
+}
+
+fn finish(events: &dyn EventSink) {
+    if let Err(err) = events.finish() {
+        eprintln!("error: couldn't write events: {}", err);
+        process::exit(cli::EXIT_EVENTS);
+    }
+}
+
+fn create(path: &Path) -> BufWriter<File> {
+    match File::create(path) {
+        Ok(file) => BufWriter::new(file),
+        Err(err) => {
+            eprintln!("error: couldn't create {}: {}", path.display(), err);
+            process::exit(cli::EXIT_EVENTS);
+        }
     }
 }
@@ -0,0 +1,436 @@
+//! Checks a strategy against a great many of the ways the philosophers'
+//! threads could take turns, instead of whichever ones happen to come up
+//! when it's run.
+//!
+//! The strategy isn't written out again for this: `Model::check` runs its
+//! real `dine` on a table whose scheduler decides who goes when. Only one
+//! philosopher runs at a time, and every time one is about to touch a fork,
+//! the waiter's permits or Chandy and Misra's bookkeeping (the
+//! `Scheduler::turn`), or has to wait for somebody, it picks who goes next.
+//! Each run follows a script of those picks, and the next run changes the
+//! last one it can.
+//!
+//! That isn't every schedule there is, though. Making a philosopher who
+//! could have carried on wait instead is called a preemption, and only
+//! schedules with up to `Model::preemptions` of them are tried, or a
+//! philosopher backing off could be sent round forever. Sleeping doesn't
+//! take any time here, and it isn't a pick either: whoever's waited longest
+//! goes next. So a deadlock that needs more preemptions than that, or the
+//! philosophers waking up in some other order, won't be found. The fewest
+//! preemptions are tried first, so whatever does go wrong is found the
+//! shortest way there is.
+//!
+//! If somebody hasn't finished but nobody can move, that's a deadlock, and
+//! the check hands back what everybody did to get there.
+
+use std::any::Any;
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use std::fmt;
+use std::mem;
+use std::panic;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::{self, ThreadId};
+use std::time::Duration;
+
+use clock::{Clock, Scheduler, Seated};
+use events::{Event, EventSink, Kind as EventKind};
+use strategy::{Kind, Strategy};
+use {Philosopher, Table};
+
+/// One thing a philosopher does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Step {
+    PickUp(usize),
+    PutDown(usize),
+    Eat,
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Step::PickUp(fork) => write!(f, "picks up fork {}", fork),
+            Step::PutDown(fork) => write!(f, "puts down fork {}", fork),
+            Step::Eat => write!(f, "eats"),
+        }
+    }
+}
+
+/// `philosopher` took `step`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Move {
+    pub philosopher: usize,
+    pub step: Step,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Problem {
+    /// Somebody still wants to eat, but nobody can do anything.
+    Deadlock,
+    /// This philosopher ate without holding both their forks.
+    EatingWithoutForks(usize),
+}
+
+/// What went wrong, and a schedule that makes it happen.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Counterexample {
+    pub problem: Problem,
+    pub trace: Vec<Move>,
+}
+
+impl fmt::Display for Counterexample {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.problem {
+            Problem::Deadlock => write!(f, "deadlock after {} steps", self.trace.len())?,
+            Problem::EatingWithoutForks(seat) => {
+                write!(f, "seat {} eats without both forks after {} steps", seat, self.trace.len())?
+            }
+        }
+        for m in &self.trace {
+            write!(f, "\n    seat {} {}", m.philosopher, m.step)?;
+        }
+        Ok(())
+    }
+}
+
+/// Some philosophers, the strategy they use, and how long they eat for.
+pub struct Model {
+    pub philosophers: Vec<Philosopher>,
+    /// How many times everybody eats.
+    pub meals: u32,
+    /// The most preemptions in any one schedule. Schedules that need more
+    /// aren't tried.
+    pub preemptions: usize,
+    /// A fresh strategy for every run, so none of them remember the last.
+    build: Box<dyn Fn() -> Box<dyn Strategy>>,
+}
+
+impl fmt::Debug for Model {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Model")
+            .field("philosophers", &self.philosophers)
+            .field("meals", &self.meals)
+            .field("preemptions", &self.preemptions)
+            .finish()
+    }
+}
+
+impl Model {
+    /// How `kind` has `philosophers` eat `meals` times each.
+    pub fn new(kind: Kind, philosophers: &[Philosopher], meals: u32) -> Model {
+        let seats = philosophers.len();
+        Model::with(philosophers, meals, move || kind.build(seats))
+    }
+
+    /// The same, but with whatever strategy `build` makes.
+    pub fn with<F>(philosophers: &[Philosopher], meals: u32, build: F) -> Model
+        where F: Fn() -> Box<dyn Strategy> + 'static {
+        Model {
+            philosophers: philosophers.to_vec(),
+            meals,
+            // Enough for everybody but one to be stopped with a fork in
+            // hand, at three seats
+            preemptions: 2,
+            build: Box::new(build),
+        }
+    }
+
+    /// Tries every schedule with up to `preemptions` preemptions. Gives back
+    /// how many it ran if nothing went wrong in any of them.
+    pub fn check(&self) -> Result<usize, Counterexample> {
+        let mut runs = 0;
+        for preemptions in 0..=self.preemptions {
+            let mut script = Vec::new();
+            loop {
+                let mut choices = self.run(script, preemptions)?;
+                runs += 1;
+
+                // Next time, the last pick there was any other option for
+                // goes the next way instead
+                script = loop {
+                    match choices.pop() {
+                        Some((taken, options)) if taken + 1 < options => {
+                            let mut script: Vec<usize> = choices.iter().map(|&(taken, _)| taken).collect();
+                            script.push(taken + 1);
+                            break script;
+                        }
+                        Some(_) => {}
+                        None => break Vec::new(),
+                    }
+                };
+                if script.is_empty() {
+                    break;
+                }
+            }
+        }
+        Ok(runs)
+    }
+
+    /// Runs dinner once, following `script`. Gives back every pick that
+    /// was made, and how many options each had.
+    fn run(&self, script: Vec<usize>, preemptions: usize) -> Result<Vec<(usize, usize)>, Counterexample> {
+        let scheduler = Arc::new(Scripted::new(script, preemptions));
+        let table = Table::with_clock(self.philosophers.len(), scheduler.clone())
+            .with_events(scheduler.clone())
+            .with_scheduler(scheduler.clone());
+        let strategy = (self.build)();
+        let meals = self.meals;
+
+        scheduler.expect(self.philosophers.len());
+        let panics: Vec<Box<dyn Any + Send>> = thread::scope(|scope| {
+            let handles: Vec<_> = self.philosophers.iter().map(|p| {
+                let (table, strategy, scheduler) = (&table, &*strategy, &*scheduler);
+                scope.spawn(move || {
+                    let seat = p.seat_number();
+                    let _seated = Seated::new(table.scheduler(), seat);
+                    for _ in 0..meals {
+                        strategy.dine(seat, &[p.left, p.right], table, &mut || {
+                            table.turn();
+                            let held = |fork| table.fork(fork).holder() == Some(seat);
+                            scheduler.eat(seat, held(p.left) && held(p.right));
+                        });
+                    }
+                })
+            }).collect();
+            handles.into_iter().filter_map(|handle| handle.join().err()).collect()
+        });
+
+        // Anything but being told to give up is a real panic
+        if let Some(panic) = panics.into_iter().find(|panic| !panic.is::<GaveUp>()) {
+            panic::resume_unwind(panic);
+        }
+
+        let run = scheduler.run.lock().unwrap();
+        match run.problem {
+            Some(problem) => Err(Counterexample { problem, trace: run.trace.clone() }),
+            None => Ok(run.choices.clone()),
+        }
+    }
+}
+
+/// What the threads of a run that has already gone wrong unwind with.
+struct GaveUp;
+
+/// Unwinds the calling thread out of a run that's gone wrong, unless it's
+/// already on its way out.
+fn give_up() {
+    if !thread::panicking() {
+        panic::resume_unwind(Box::new(GaveUp));
+    }
+}
+
+/// The clock and scheduler for one run, which picks who goes when the way
+/// its script says to.
+#[derive(Debug)]
+struct Scripted {
+    run: Mutex<Run>,
+    /// Signalled whenever it's somebody else's turn, or the run's over.
+    turn: Condvar,
+}
+
+#[derive(Debug, Default)]
+struct Run {
+    /// Which option to take at each pick, before taking the first every
+    /// time.
+    script: Vec<usize>,
+    /// Every pick made so far, and how many options it had.
+    choices: Vec<(usize, usize)>,
+    preemptions: usize,
+    /// How many threads haven't arrived yet.
+    expected: usize,
+    seats: HashMap<ThreadId, usize>,
+    running: Option<usize>,
+    /// Who could go next, longest waiting first.
+    ready: VecDeque<usize>,
+    /// Who's waiting for something to change.
+    stalled: BTreeSet<usize>,
+    trace: Vec<Move>,
+    /// Whatever went wrong. Once there's something, everybody gives up.
+    problem: Option<Problem>,
+}
+
+impl Run {
+    fn me(&self) -> Option<usize> {
+        self.seats.get(&thread::current().id()).cloned()
+    }
+
+    /// One of `options`, the way the script says, or the first.
+    fn pick(&mut self, options: &[usize]) -> usize {
+        if options.len() == 1 {
+            return options[0];
+        }
+        let taken = self.script.get(self.choices.len()).cloned().unwrap_or(0);
+        self.choices.push((taken, options.len()));
+        options[taken]
+    }
+}
+
+impl Scripted {
+    fn new(script: Vec<usize>, preemptions: usize) -> Scripted {
+        Scripted {
+            run: Mutex::new(Run { script, preemptions, ..Run::default() }),
+            turn: Condvar::new(),
+        }
+    }
+
+    /// Hands the turn to somebody who's ready. If nobody is, but somebody
+    /// is still waiting, they'll be waiting forever.
+    fn give_way(&self, run: &mut Run) {
+        let ready: Vec<usize> = run.ready.iter().cloned().collect();
+        if ready.is_empty() {
+            run.running = None;
+            if !run.stalled.is_empty() {
+                run.problem = Some(Problem::Deadlock);
+                self.turn.notify_all();
+            }
+        } else {
+            let next = run.pick(&ready);
+            self.hand_over(run, next);
+        }
+    }
+
+    fn hand_over(&self, run: &mut Run, next: usize) {
+        run.ready.retain(|&seat| seat != next);
+        run.running = Some(next);
+        self.turn.notify_all();
+    }
+
+    fn wait_turn(&self, mut run: MutexGuard<'_, Run>, me: usize) {
+        while run.running != Some(me) && run.problem.is_none() {
+            run = self.turn.wait(run).unwrap();
+        }
+        if run.problem.is_some() {
+            drop(run);
+            give_up();
+        }
+    }
+
+    /// `seat` eats, with both forks or without.
+    fn eat(&self, seat: usize, with_both: bool) {
+        let mut run = self.run.lock().unwrap();
+        run.trace.push(Move { philosopher: seat, step: Step::Eat });
+        if !with_both {
+            run.problem = Some(Problem::EatingWithoutForks(seat));
+            self.turn.notify_all();
+            drop(run);
+            give_up();
+        }
+    }
+}
+
+impl Clock for Scripted {
+    fn now(&self) -> Duration {
+        Duration::default()
+    }
+
+    fn sleep(&self, _duration: Duration) {
+        let mut run = self.run.lock().unwrap();
+        if let Some(me) = run.me() {
+            if run.problem.is_some() {
+                drop(run);
+                return give_up();
+            }
+            // Whoever's waited longest goes next, without it counting as a
+            // pick, or somebody backing off could be picked again forever.
+            // Schedules where somebody else wakes up first aren't tried
+            run.ready.push_back(me);
+            let next = run.ready[0];
+            self.hand_over(&mut run, next);
+            self.wait_turn(run, me);
+        }
+    }
+
+    fn catch_up(&self, _moment: Duration) {}
+}
+
+impl Scheduler for Scripted {
+    fn expect(&self, threads: usize) {
+        self.run.lock().unwrap().expected += threads;
+    }
+
+    fn arrive(&self, id: usize) {
+        let mut run = self.run.lock().unwrap();
+        run.seats.insert(thread::current().id(), id);
+        run.ready.push_back(id);
+        run.expected -= 1;
+        if run.expected == 0 {
+            // However they got here, they line up in order
+            run.ready.make_contiguous().sort();
+            self.give_way(&mut run);
+        }
+        self.wait_turn(run, id);
+    }
+
+    fn leave(&self) {
+        let mut run = self.run.lock().unwrap();
+        if let Some(me) = run.seats.remove(&thread::current().id()) {
+            if run.problem.is_none() && run.running == Some(me) {
+                self.give_way(&mut run);
+            }
+        }
+    }
+
+    fn stall(&self) {
+        let mut run = self.run.lock().unwrap();
+        if let Some(me) = run.me() {
+            if run.problem.is_some() {
+                drop(run);
+                return give_up();
+            }
+            run.stalled.insert(me);
+            self.give_way(&mut run);
+            self.wait_turn(run, me);
+        }
+    }
+
+    fn changed(&self) {
+        let mut run = self.run.lock().unwrap();
+        let stalled = mem::take(&mut run.stalled);
+        run.ready.extend(stalled);
+    }
+
+    fn stuck(&self) -> bool {
+        self.run.lock().unwrap().problem == Some(Problem::Deadlock)
+    }
+
+    fn turn(&self) {
+        let mut run = self.run.lock().unwrap();
+        let me = match run.me() {
+            Some(me) => me,
+            None => return,
+        };
+        if run.problem.is_some() {
+            drop(run);
+            return give_up();
+        }
+        if run.preemptions == 0 {
+            return;
+        }
+
+        // Carrying on is the first option, and anybody else costs a
+        // preemption
+        let mut options = vec![me];
+        options.extend(run.ready.iter().cloned());
+        let next = run.pick(&options);
+        if next != me {
+            run.preemptions -= 1;
+            run.ready.push_back(me);
+            self.hand_over(&mut run, next);
+            self.wait_turn(run, me);
+        }
+    }
+}
+
+impl EventSink for Scripted {
+    fn record(&self, event: &Event) {
+        let step = match event.kind {
+            EventKind::PickedUp { fork, .. } => Step::PickUp(fork),
+            EventKind::PutDown { fork, .. } => Step::PutDown(fork),
+            _ => return,
+        };
+        let mut run = self.run.lock().unwrap();
+        // Forks put down on the way out of a run that's over don't count
+        if run.problem.is_none() {
+            run.trace.push(Move { philosopher: event.philosopher, step });
+        }
+    }
+}
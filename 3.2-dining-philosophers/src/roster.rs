@@ -0,0 +1,183 @@
+//! Who's coming to dinner.
+//!
+//! A roster is a list of guests, either read from a TOML or JSON file or
+//! made up on the spot with `Roster::generated`. A TOML roster looks like
+//!
+//! ```toml
+//! [[philosopher]]
+//! name = "Mikael Stanne"
+//! appetite = 2
+//! think_time_ms = 500
+//!
+//! [[philosopher]]
+//! name = "Jesper Strömblad"
+//! ```
+//!
+//! and the JSON one is the same shape, `{"philosopher": [{"name": ...}]}`.
+//! Only the name is required.
+
+use std::error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde_json;
+use toml;
+
+use Philosopher;
+
+/// The philosophers the original example seated.
+pub const DEFAULT_NAMES: [&str; 5] = [
+    "Mikael Stanne",
+    "Jesper Strömblad",
+    "Glenn Ljungström",
+    "Anders Fridén",
+    "Niclas Engelin",
+];
+
+/// Someone on the guest list, before they've been given a seat.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Guest {
+    pub name: String,
+    /// How many meals they want.
+    #[serde(default = "default_appetite")]
+    pub appetite: u32,
+    /// How long they think before each meal, in milliseconds.
+    #[serde(default)]
+    pub think_time_ms: u64,
+}
+
+fn default_appetite() -> u32 {
+    1
+}
+
+impl Guest {
+    pub fn new(name: &str) -> Guest {
+        Guest {
+            name: name.to_string(),
+            appetite: default_appetite(),
+            think_time_ms: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Roster {
+    #[serde(rename = "philosopher")]
+    pub guests: Vec<Guest>,
+}
+
+impl Default for Roster {
+    fn default() -> Roster {
+        Roster::from_names(&DEFAULT_NAMES)
+    }
+}
+
+impl Roster {
+    pub fn from_names(names: &[&str]) -> Roster {
+        Roster {
+            guests: names.iter().map(|name| Guest::new(name)).collect(),
+        }
+    }
+
+    /// `count` philosophers with made up names, for when the guest list
+    /// doesn't matter.
+    pub fn generated(count: usize) -> Roster {
+        Roster {
+            guests: (1..count + 1).map(|n| Guest::new(&format!("Philosopher {}", n))).collect(),
+        }
+    }
+
+    /// Reads a roster from `path`, which must end in `.toml` or `.json`.
+    pub fn load(path: &Path) -> Result<Roster, Error> {
+        let text = fs::read_to_string(path).map_err(|err| Error::Io(path.to_path_buf(), err))?;
+
+        let roster: Roster = match path.extension().and_then(|extension| extension.to_str()) {
+            Some("toml") => toml::from_str(&text).map_err(Error::Toml)?,
+            Some("json") => serde_json::from_str(&text).map_err(Error::Json)?,
+            _ => return Err(Error::UnknownFormat(path.to_path_buf())),
+        };
+
+        roster.validate()?;
+        Ok(roster)
+    }
+
+    /// Everybody needs two forks, and a table of one only has the one.
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.guests.len() < 2 {
+            return Err(Error::TooFew(self.guests.len()));
+        }
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.guests.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.guests.is_empty()
+    }
+
+    /// Seats everybody around a table with one fork per guest. Everybody's
+    /// left fork has their own number and their right fork is their
+    /// neighbour's, so the last philosopher's right fork is fork 0.
+    ///
+    /// Nobody is seated the "wrong way round" to break the cycle, that's the
+    /// strategy's job.
+    pub fn seat(&self) -> Vec<Philosopher> {
+        let seats = self.guests.len();
+
+        self.guests.iter().enumerate()
+            .map(|(seat, guest)| Philosopher {
+                name: guest.name.clone(),
+                left: seat,
+                right: (seat + 1) % seats,
+                appetite: guest.appetite,
+                think_time: Duration::from_millis(guest.think_time_ms),
+                fumbles: Vec::new(),
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Io(PathBuf, io::Error),
+    Toml(toml::de::Error),
+    Json(serde_json::Error),
+    /// Neither `.toml` nor `.json`.
+    UnknownFormat(PathBuf),
+    TooFew(usize),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Io(ref path, ref err) => write!(f, "couldn't read {}: {}", path.display(), err),
+            Error::Toml(ref err) => write!(f, "bad TOML roster: {}", err),
+            Error::Json(ref err) => write!(f, "bad JSON roster: {}", err),
+            Error::UnknownFormat(ref path) => {
+                write!(f, "don't know how to read {}, expected a .toml or .json file",
+                       path.display())
+            }
+            Error::TooFew(count) => {
+                write!(f, "a table needs at least two philosophers, the roster has {}", count)
+            }
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::Io(_, ref err) => Some(err),
+            Error::Toml(ref err) => Some(err),
+            Error::Json(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
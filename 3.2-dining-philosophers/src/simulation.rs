@@ -0,0 +1,383 @@
+//! Dinner that goes on for a while: every philosopher thinks, gets hungry,
+//! waits for their forks and eats, over and over, and we keep track of how
+//! long they had to wait.
+
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use clock::Seated;
+use events::Kind;
+use supervisor::Failure;
+use {Philosopher, Strategy, Table};
+
+/// How philosophers take turns at the table.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Mode {
+    /// One philosopher at a time, on one thread, taking turns round the
+    /// table. Nobody ever waits for a fork, but nobody eats at the same time
+    /// either.
+    Serial,
+    /// Every philosopher gets their own thread, and the OS decides who
+    /// goes next.
+    #[default]
+    Threaded,
+    /// Every philosopher is an async task, and they all share one thread.
+    Async,
+}
+
+impl Mode {
+    pub const ALL: [Mode; 3] = [Mode::Serial, Mode::Threaded, Mode::Async];
+
+    /// The name used by `--mode`.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Mode::Serial => "serial",
+            Mode::Threaded => "threaded",
+            Mode::Async => "async",
+        }
+    }
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Mode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Mode, String> {
+        Mode::ALL.iter().cloned()
+            .find(|mode| mode.name() == s)
+            .ok_or_else(|| format!("unknown mode `{}`, expected serial, threaded or async", s))
+    }
+}
+
+/// How long dinner goes on for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Length {
+    /// Everybody eats their `appetite` this many times over.
+    Rounds(u32),
+    /// Everybody keeps thinking and eating until time's up. A meal that has
+    /// already started is finished, though.
+    Duration(Duration),
+}
+
+impl Default for Length {
+    fn default() -> Length {
+        Length::Rounds(1)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Simulation {
+    pub length: Length,
+    /// How long each meal takes.
+    pub eat_time: Duration,
+}
+
+impl Default for Simulation {
+    fn default() -> Simulation {
+        Simulation {
+            length: Length::default(),
+            eat_time: Duration::from_millis(1000),
+        }
+    }
+}
+
+impl Simulation {
+    /// Gives every philosopher a thread and lets them get on with it until
+    /// dinner's over. Time is kept by the table's clock, so with a
+    /// `VirtualClock` none of the thinking and eating takes any real time.
+    pub fn run(&self, philosophers: Vec<Philosopher>, table: Arc<Table>,
+               strategy: Arc<dyn Strategy>) -> Report {
+        let simulation = *self;
+
+        // `let handles: Vec<_> =`
+        // Explicitly annotating the type Vec<_>,
+        // with type `_` which is a placeholder type
+        // meaning "Rust will figure it out"
+        //
+        // `philosophers.into_iter().map(|p| {`
+        // We create an iterator from our philosophers vector,
+        // takes ownership of each philosophers to pass into our threads.
+        // Map takes a closure so that thread::spawn is using the
+        // correct `p` value.
+        //
+        // A clock that decides who runs when needs to know how many to wait
+        // for before anybody starts
+        if let Some(scheduler) = table.scheduler() {
+            scheduler.expect(philosophers.len());
+        }
+        let handles: Vec<_> = philosophers.into_iter().map(|p| {
+            // clone() method on Arc<T> is what bumps up the ref count,
+            // and decrements when falls out of scope. We want to track how many refs
+            // to table exist across our threads. If we didn't have a count, we swouldn't
+            // know how to deallocate it.
+            let table = table.clone(); // shadow binding
+            let strategy = strategy.clone();
+
+            // Executes the closure passed into `thread::spawn` in
+            // a new thread
+            //
+            // We annotate with `move` indicating that the closure
+            // is going to take ownership of the values its capturing,
+            // the `p` from the current closure.
+            thread::spawn(move || {
+                let _seated = Seated::new(table.scheduler(), p.seat_number());
+                simulation.dine(&p, &table, &*strategy)
+            }) // nnote lack of semicolon; expression, returns return
+            // values of thread::spawn calls which are handles to those threads
+        }).collect(); // makes them into some kind of collection, this is why we needed
+                      // `Vec<_>`
+
+        // `join()` blocks execution until the thread has completed execution.
+        // So all programs will complete their work before moving on to the next
+        // handle in the loop
+        let mut diners = Vec::new();
+        let mut elapsed = Duration::default();
+        for h in handles {
+            let (diner, finished) = h.join().unwrap();
+            diners.push(diner);
+            // Dinner's over when the last philosopher is done
+            elapsed = elapsed.max(finished);
+        }
+
+        Report::new(diners, elapsed)
+    }
+
+    /// One philosopher's dinner, and when they finished by the table's
+    /// clock. Everything they do goes to the table's `EventSink`.
+    fn dine(&self, p: &Philosopher, table: &Table, strategy: &dyn Strategy) -> (Diner, Duration) {
+        let clock = table.clock();
+        let mut diner = Diner::new(&p.name);
+        let mut meals_left = self.meals_for(p);
+
+        while self.another_meal(&mut meals_left, clock.now()) {
+            let meal = diner.meals + 1;
+            self.meal(p, table, strategy, &mut diner, meal);
+        }
+
+        (diner, clock.now())
+    }
+
+    /// Everybody takes it in turns to have a meal, going round the table
+    /// until nobody has any left, all on the calling thread. This is how the
+    /// very first version of dinner went, before anybody had a thread of
+    /// their own.
+    pub fn run_serial(&self, philosophers: Vec<Philosopher>, table: &Table,
+                      strategy: &dyn Strategy) -> Report {
+        let clock = table.clock();
+        let mut diners: Vec<Diner> = philosophers.iter().map(|p| Diner::new(&p.name)).collect();
+        let mut meals_left: Vec<Option<u32>> = philosophers.iter().map(|p| self.meals_for(p)).collect();
+
+        loop {
+            let mut anybody_ate = false;
+            // iterate over the vector, getting a reference to each philosopher
+            for (i, p) in philosophers.iter().enumerate() {
+                if self.another_meal(&mut meals_left[i], clock.now()) {
+                    let meal = diners[i].meals + 1;
+                    self.meal(p, table, strategy, &mut diners[i], meal);
+                    anybody_ate = true;
+                }
+            }
+            if !anybody_ate {
+                break;
+            }
+        }
+
+        Report::new(diners, clock.now())
+    }
+
+    /// Thinks, picks up both forks, eats and puts them back down, once.
+    /// Panics halfway through eating if `number` is one of the
+    /// philosopher's `fumbles`.
+    pub(crate) fn meal(&self, p: &Philosopher, table: &Table, strategy: &dyn Strategy, diner: &mut Diner,
+                       number: u32) {
+        let clock = table.clock();
+        let seat = p.seat_number();
+
+        table.record(seat, Kind::Thinking);
+        clock.sleep(p.think_time);
+
+        let hungry = clock.now();
+        table.record(seat, Kind::Hungry);
+        p.eat(table, strategy, || {
+            diner.ate(clock.now() - hungry);
+
+            table.record(seat, Kind::Eating);
+            if p.fumbles.contains(&number) {
+                clock.sleep(self.eat_time / 2);
+                table.record(seat, Kind::Dropped);
+                panic!("{} dropped their fork", p.name);
+            }
+            clock.sleep(self.eat_time);
+            table.record(seat, Kind::Done);
+        });
+    }
+
+    /// How many meals `p` is having, or `None` if they eat until time's up.
+    pub(crate) fn meals_for(&self, p: &Philosopher) -> Option<u32> {
+        match self.length {
+            Length::Rounds(rounds) => Some(rounds * p.appetite),
+            Length::Duration(_) => None,
+        }
+    }
+
+    /// Whether there's another meal to have, given what `meals_for` said
+    /// and what the time is `now`. Counts it off if there is.
+    pub(crate) fn another_meal(&self, meals_left: &mut Option<u32>, now: Duration) -> bool {
+        match (*meals_left, self.length) {
+            (Some(0), _) => false,
+            (Some(left), _) => {
+                *meals_left = Some(left - 1);
+                true
+            }
+            (None, Length::Duration(length)) => now < length,
+            (None, _) => true,
+        }
+    }
+}
+
+/// How dinner went for one philosopher.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diner {
+    pub name: String,
+    pub meals: u32,
+    /// From getting hungry to picking up both forks, over every meal.
+    pub total_wait: Duration,
+    /// The longest they went hungry for.
+    pub max_wait: Duration,
+}
+
+impl Diner {
+    /// Somebody who hasn't eaten yet.
+    pub fn new(name: &str) -> Diner {
+        Diner {
+            name: name.to_string(),
+            meals: 0,
+            total_wait: Duration::from_millis(0),
+            max_wait: Duration::from_millis(0),
+        }
+    }
+
+    /// Counts a meal they waited `waited` for.
+    pub(crate) fn ate(&mut self, waited: Duration) {
+        self.meals += 1;
+        self.total_wait += waited;
+        self.max_wait = self.max_wait.max(waited);
+    }
+
+    pub fn average_wait(&self) -> Option<Duration> {
+        if self.meals == 0 {
+            return None;
+        }
+        Some(self.total_wait / self.meals)
+    }
+}
+
+/// How dinner went for everybody, in seating order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    pub diners: Vec<Diner>,
+    pub elapsed: Duration,
+    /// Every time somebody panicked, if a `Supervisor` was watching.
+    pub failures: Vec<Failure>,
+    /// How many times each fork was cleaned off after a panic, if the
+    /// table was laid `with_recovery`.
+    pub recovered: Vec<usize>,
+}
+
+impl Report {
+    /// A report of a dinner nobody panicked at.
+    pub fn new(diners: Vec<Diner>, elapsed: Duration) -> Report {
+        Report {
+            diners,
+            elapsed,
+            failures: Vec::new(),
+            recovered: Vec::new(),
+        }
+    }
+
+    pub fn meals(&self) -> u32 {
+        self.diners.iter().map(|diner| diner.meals).sum()
+    }
+
+    /// Jain's fairness index over meals eaten: 1 when everybody ate the
+    /// same amount, down to `1 / n` when one philosopher ate everything.
+    /// Only really interesting for a `Length::Duration`, since with rounds
+    /// everybody eats what they asked for.
+    pub fn fairness(&self) -> f64 {
+        let meals: Vec<f64> = self.diners.iter().map(|diner| f64::from(diner.meals)).collect();
+        let sum: f64 = meals.iter().sum();
+        let sum_of_squares: f64 = meals.iter().map(|meals| meals * meals).sum();
+
+        if sum_of_squares == 0.0 {
+            // Nobody ate, which is at least fair
+            return 1.0;
+        }
+        sum * sum / (meals.len() as f64 * sum_of_squares)
+    }
+
+    /// Meals that were never finished, or never started, because somebody
+    /// panicked.
+    pub fn lost(&self) -> u32 {
+        self.failures.iter().map(|failure| failure.lost).sum()
+    }
+
+    /// Whoever went hungry the longest.
+    pub fn hungriest(&self) -> Option<&Diner> {
+        self.diners.iter().max_by_key(|diner| diner.max_wait)
+    }
+
+    /// Everyone who went hungry for longer than `threshold`.
+    pub fn starving(&self, threshold: Duration) -> Vec<&Diner> {
+        self.diners.iter().filter(|diner| diner.max_wait > threshold).collect()
+    }
+
+    /// The report, ready to print.
+    pub fn lines(&self, threshold: Duration) -> Vec<String> {
+        let mut lines = Vec::new();
+
+        for diner in &self.diners {
+            let average = diner.average_wait()
+                .map(|average| format!("{:.3}s", average.as_secs_f64()))
+                .unwrap_or_else(|| "-".to_string());
+            lines.push(format!("{:<20} {:>5} meals, waited {:.3}s at most, {} on average{}",
+                               diner.name, diner.meals, diner.max_wait.as_secs_f64(), average,
+                               if diner.max_wait > threshold { "  STARVING" } else { "" }));
+        }
+
+        lines.push(String::new());
+        lines.push(format!("{} meals in {:.1}s", self.meals(), self.elapsed.as_secs_f64()));
+        lines.push(format!("Fairness: {:.3} (1 is perfectly fair)", self.fairness()));
+        if let Some(hungriest) = self.hungriest() {
+            lines.push(format!("Longest wait: {:.3}s ({})",
+                               hungriest.max_wait.as_secs_f64(), hungriest.name));
+        }
+
+        let starving = self.starving(threshold);
+        if !starving.is_empty() {
+            lines.push(format!("{} of {} went hungry for longer than {:.3}s",
+                               starving.len(), self.diners.len(), threshold.as_secs_f64()));
+        }
+
+        if !self.failures.is_empty() {
+            lines.push(String::new());
+            for failure in &self.failures {
+                lines.push(failure.to_string());
+            }
+            lines.push(format!("{} meals lost to {} failures", self.lost(), self.failures.len()));
+        }
+        for (fork, &times) in self.recovered.iter().enumerate().filter(|&(_, &times)| times > 0) {
+            let times = if times == 1 { "once".to_string() } else { format!("{} times", times) };
+            lines.push(format!("Fork {} was poisoned and cleaned off {}", fork, times));
+        }
+
+        lines
+    }
+}
@@ -0,0 +1,858 @@
+//! Ways of getting both forks without the whole table deadlocking.
+//!
+//! If every philosopher grabs their left fork and then waits for their
+//! right, and they all do it at once, nobody ever eats. Each strategy here
+//! breaks that cycle differently:
+//!
+//! * `Naive` doesn't, to show what happens.
+//! * `ResourceOrdering` always picks up the lower numbered fork first, so
+//!   somebody is always reaching across the "wrong" way.
+//! * `Waiter` only lets all but one philosopher reach for forks at a time.
+//! * `ChandyMisra` hands forks around as clean or dirty, so whoever ate
+//!   last gives way.
+//! * `Backoff` puts the left fork back down if the right one is taken, and
+//!   tries again later.
+//!
+//! Every strategy works for threads, with `dine`, and for async tasks, with
+//! `pick_up`. The futures are written out by hand, like the tasks in
+//! `tasks`, but they make the same choices the threads do.
+//!
+//! `dine` takes whatever set of forks somebody needs, not just a left and a
+//! right, so the same strategies work for the bottles in a `drinking`
+//! cellar. There, the waiter is a `Sommelier` who hands out whole sessions
+//! at once, and `ChandyMisra` is laid `sharing` the bottles.
+
+use std::fmt;
+use std::future::Future;
+use std::mem;
+use std::pin::Pin;
+use std::str::FromStr;
+use std::sync::{Condvar, Mutex, TryLockError};
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
+
+use clock;
+use executor::{self, Sleep};
+use fork::{ForkGuard, PickUp};
+use {Philosopher, Table};
+
+/// How a philosopher gets hold of their forks.
+///
+/// `Send + Sync` so one strategy can be shared by every philosopher's
+/// thread.
+pub trait Strategy: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Picks up every one of `forks` from `table` for whoever sits at
+    /// `seat`, calls `eat` while holding them, then puts them back down. At
+    /// dinner that's a philosopher's left fork and then their right.
+    fn dine(&self, seat: usize, forks: &[usize], table: &Table, eat: &mut dyn FnMut());
+
+    /// The same for an async task: picks up both of `philosopher`'s forks
+    /// without blocking the thread, and gives back what they have to hold
+    /// on to while they eat. Only works inside an `Executor`.
+    fn pick_up<'a>(&'a self, philosopher: &'a Philosopher, table: &'a Table) -> PickingUp<'a>;
+}
+
+/// A future from `Strategy::pick_up`.
+pub type PickingUp<'a> = Pin<Box<dyn Future<Output = Holding<'a>> + 'a>>;
+
+/// Everything a task holds while it eats. Dropping it puts the forks back
+/// down, and then hands back whatever else the strategy gave out, the same
+/// order `dine` does it in. Threads hold their forks in one too.
+#[derive(Default)]
+pub struct Holding<'a> {
+    forks: Vec<ForkGuard<'a>>,
+    permit: Option<Permit<'a>>,
+    meal: Option<Meal<'a>>,
+    handed: Option<Handed<'a>>,
+}
+
+impl<'a> Holding<'a> {
+    /// Picks up `forks` for `seat` one after the other, waiting for each.
+    fn lock(table: &'a Table, seat: usize, forks: &[usize]) -> Holding<'a> {
+        let mut holding = Holding::default();
+        holding.forks = forks.iter().map(|&fork| table.fork(fork).lock(seat).unwrap()).collect();
+        holding
+    }
+}
+
+impl<'a> Drop for Holding<'a> {
+    fn drop(&mut self) {
+        // Last picked up, first put down
+        while self.forks.pop().is_some() {}
+    }
+}
+
+impl<'a> fmt::Debug for Holding<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Holding")
+            .field("forks", &self.forks)
+            .field("permit", &self.permit.is_some())
+            .field("meal", &self.meal.is_some())
+            .field("handed", &self.handed.is_some())
+            .finish()
+    }
+}
+
+/// Picks up two forks one after the other, reaching across for a while in
+/// between, for a task.
+struct Both<'a> {
+    table: &'a Table,
+    me: usize,
+    forks: [usize; 2],
+    reach: Duration,
+    held: Vec<ForkGuard<'a>>,
+    step: Step<'a>,
+}
+
+enum Step<'a> {
+    PickingUp(PickUp<'a>),
+    Reaching(Sleep<'a>),
+}
+
+impl<'a> Both<'a> {
+    fn new(table: &'a Table, me: usize, forks: [usize; 2], reach: Duration) -> Both<'a> {
+        Both {
+            table,
+            me,
+            forks,
+            reach,
+            held: Vec::new(),
+            step: Step::PickingUp(table.fork(forks[0]).pick_up(me)),
+        }
+    }
+}
+
+impl<'a> Future for Both<'a> {
+    type Output = Vec<ForkGuard<'a>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Vec<ForkGuard<'a>>> {
+        let this = &mut *self;
+        loop {
+            this.step = match this.step {
+                Step::PickingUp(ref mut fork) => match Pin::new(fork).poll(cx) {
+                    Poll::Pending => return Poll::Pending,
+                    Poll::Ready(fork) => {
+                        this.held.push(fork.unwrap());
+                        if this.held.len() == 2 {
+                            return Poll::Ready(mem::take(&mut this.held));
+                        }
+                        Step::Reaching(executor::sleep(this.table.clock(), this.reach))
+                    }
+                },
+                Step::Reaching(ref mut reach) => match Pin::new(reach).poll(cx) {
+                    Poll::Pending => return Poll::Pending,
+                    Poll::Ready(()) => Step::PickingUp(this.table.fork(this.forks[1]).pick_up(this.me)),
+                },
+            };
+        }
+    }
+}
+
+/// Just the forks, picked up by `Both`.
+fn forks<'a>(table: &'a Table, me: usize, forks: [usize; 2], reach: Duration) -> PickingUp<'a> {
+    gated(Ready, table, me, forks, reach)
+}
+
+fn gated<'a, G: Gate<'a> + Unpin + 'a>(gate: G, table: &'a Table, me: usize, forks: [usize; 2],
+                                       reach: Duration) -> PickingUp<'a> {
+    Box::pin(Gated { gate, both: Both::new(table, me, forks, reach), holding: Holding::default() })
+}
+
+/// Something a task has to wait for before it reaches for any forks, like a
+/// permit from the waiter, which goes into `Holding` until it's done
+/// eating. Polled again every time the forks are, so once it's in there it
+/// has to stay ready.
+trait Gate<'a> {
+    fn poll_gate(&mut self, cx: &mut Context, holding: &mut Holding<'a>) -> Poll<()>;
+}
+
+/// No waiting at all.
+struct Ready;
+
+impl<'a> Gate<'a> for Ready {
+    fn poll_gate(&mut self, _cx: &mut Context, _holding: &mut Holding<'a>) -> Poll<()> {
+        Poll::Ready(())
+    }
+}
+
+/// Waits at the gate, then picks up both forks.
+struct Gated<'a, G> {
+    gate: G,
+    both: Both<'a>,
+    holding: Holding<'a>,
+}
+
+impl<'a, G: Gate<'a> + Unpin> Future for Gated<'a, G> {
+    type Output = Holding<'a>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Holding<'a>> {
+        let this = &mut *self;
+        if this.gate.poll_gate(cx, &mut this.holding).is_pending() {
+            return Poll::Pending;
+        }
+        match Pin::new(&mut this.both).poll(cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(forks) => {
+                let mut holding = mem::take(&mut this.holding);
+                holding.forks = forks;
+                Poll::Ready(holding)
+            }
+        }
+    }
+}
+
+/// The strategies to pick from on the command line.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Kind {
+    Naive,
+    #[default]
+    ResourceOrdering,
+    Waiter,
+    ChandyMisra,
+    Backoff,
+}
+
+impl Kind {
+    pub const ALL: [Kind; 5] = [Kind::Naive, Kind::ResourceOrdering, Kind::Waiter,
+                                Kind::ChandyMisra, Kind::Backoff];
+
+    /// The strategies that can't deadlock.
+    pub const SAFE: [Kind; 4] = [Kind::ResourceOrdering, Kind::Waiter, Kind::ChandyMisra,
+                                 Kind::Backoff];
+
+    /// The name used by `--strategy`.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            Kind::Naive => "naive",
+            Kind::ResourceOrdering => "ordering",
+            Kind::Waiter => "waiter",
+            Kind::ChandyMisra => "chandy-misra",
+            Kind::Backoff => "backoff",
+        }
+    }
+
+    /// A fresh strategy for a table with `seats` seats.
+    pub fn build(&self, seats: usize) -> Box<dyn Strategy> {
+        match *self {
+            Kind::Naive => Box::new(Naive::default()),
+            Kind::ResourceOrdering => Box::new(ResourceOrdering),
+            Kind::Waiter => Box::new(Waiter::new(seats)),
+            Kind::ChandyMisra => Box::new(ChandyMisra::new(seats)),
+            Kind::Backoff => Box::new(Backoff::default()),
+        }
+    }
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Kind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Kind, String> {
+        Kind::ALL.iter().cloned()
+            .find(|kind| kind.name() == s)
+            .ok_or_else(|| {
+                let names: Vec<&str> = Kind::ALL.iter().map(|kind| kind.name()).collect();
+                format!("unknown strategy `{}`, expected one of {}", s, names.join(", "))
+            })
+    }
+}
+
+/// Left fork, then right fork, like everybody would if nobody told them
+/// otherwise. Deadlocks as soon as everybody picks up their left fork at
+/// once. In a cellar, it takes the bottles in whatever order the session
+/// lists them, the way the code being modelled takes its locks.
+#[derive(Debug, Clone, Copy)]
+pub struct Naive {
+    /// How long it takes to reach for the right fork. The longer it is, the
+    /// sooner the table deadlocks.
+    pub reach: Duration,
+}
+
+impl Default for Naive {
+    fn default() -> Naive {
+        Naive {
+            reach: Duration::from_millis(10),
+        }
+    }
+}
+
+impl Strategy for Naive {
+    fn name(&self) -> &'static str {
+        Kind::Naive.name()
+    }
+
+    fn dine(&self, seat: usize, forks: &[usize], table: &Table, eat: &mut dyn FnMut()) {
+        let mut holding = Holding::default();
+        for (i, &fork) in forks.iter().enumerate() {
+            if i > 0 {
+                table.clock().sleep(self.reach);
+            }
+            holding.forks.push(table.fork(fork).lock(seat).unwrap());
+        }
+        eat();
+    }
+
+    fn pick_up<'a>(&'a self, philosopher: &'a Philosopher, table: &'a Table) -> PickingUp<'a> {
+        forks(table, philosopher.seat_number(), [philosopher.left, philosopher.right], self.reach)
+    }
+}
+
+/// Lower numbered fork first. This is the original trick of seating the
+/// last philosopher the other way round, without having to remember to.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ResourceOrdering;
+
+impl Strategy for ResourceOrdering {
+    fn name(&self) -> &'static str {
+        Kind::ResourceOrdering.name()
+    }
+
+    fn dine(&self, seat: usize, forks: &[usize], table: &Table, eat: &mut dyn FnMut()) {
+        let mut ordered = forks.to_vec();
+        ordered.sort();
+
+        // The locks release when _holding goes out of scope
+        let _holding = Holding::lock(table, seat, &ordered);
+        eat();
+    }
+
+    fn pick_up<'a>(&'a self, philosopher: &'a Philosopher, table: &'a Table) -> PickingUp<'a> {
+        let first = philosopher.left.min(philosopher.right);
+        let second = philosopher.left.max(philosopher.right);
+        forks(table, philosopher.seat_number(), [first, second], Duration::from_millis(0))
+    }
+}
+
+/// A waiter who only lets `seats - 1` philosophers reach for forks at once,
+/// so at least one of them can always get both. That only works round a
+/// table, so a cellar gets a `Sommelier` instead.
+#[derive(Debug)]
+pub struct Waiter {
+    permits: Mutex<Permits>,
+    freed: Condvar,
+}
+
+#[derive(Debug)]
+struct Permits {
+    /// How many more philosophers may reach for forks right now.
+    left: usize,
+    /// When a permit was last handed back, so whoever waited for it can
+    /// catch up.
+    freed_at: Duration,
+    /// Tasks waiting for a permit.
+    wakers: Vec<Waker>,
+}
+
+impl Waiter {
+    pub fn new(seats: usize) -> Waiter {
+        Waiter::with_permits(seats.saturating_sub(1).max(1))
+    }
+
+    /// A waiter with `permits` permits to hand out, however many seats
+    /// there are. Unless that's fewer than the seats, they're no help at
+    /// all.
+    pub fn with_permits(permits: usize) -> Waiter {
+        Waiter {
+            permits: Mutex::new(Permits {
+                left: permits,
+                freed_at: Duration::default(),
+                wakers: Vec::new(),
+            }),
+            freed: Condvar::new(),
+        }
+    }
+}
+
+impl Strategy for Waiter {
+    fn name(&self) -> &'static str {
+        Kind::Waiter.name()
+    }
+
+    fn dine(&self, seat: usize, forks: &[usize], table: &Table, eat: &mut dyn FnMut()) {
+        {
+            table.turn();
+            let mut permits = self.permits.lock().unwrap();
+            if permits.left == 0 {
+                while permits.left == 0 {
+                    permits = clock::wait(table.scheduler(), &self.permits, &self.freed, permits).unwrap();
+                }
+                table.clock().catch_up(permits.freed_at);
+            }
+            permits.left -= 1;
+        }
+
+        // Handed back after the forks are put down, even if the philosopher
+        // panics, or the waiter would run out
+        let _permit = Permit { waiter: self, table };
+
+        let _holding = Holding::lock(table, seat, forks);
+        eat();
+    }
+
+    fn pick_up<'a>(&'a self, philosopher: &'a Philosopher, table: &'a Table) -> PickingUp<'a> {
+        let admit = Admit { waiter: self, table, waited: false };
+        gated(admit, table, philosopher.seat_number(), [philosopher.left, philosopher.right],
+              Duration::from_millis(0))
+    }
+}
+
+/// Waiting for one of the waiter's permits, as a task.
+struct Admit<'a> {
+    waiter: &'a Waiter,
+    table: &'a Table,
+    waited: bool,
+}
+
+impl<'a> Gate<'a> for Admit<'a> {
+    fn poll_gate(&mut self, cx: &mut Context, holding: &mut Holding<'a>) -> Poll<()> {
+        if holding.permit.is_some() {
+            return Poll::Ready(());
+        }
+
+        let mut permits = self.waiter.permits.lock().unwrap();
+        if permits.left == 0 {
+            permits.wakers.push(cx.waker().clone());
+            self.waited = true;
+            return Poll::Pending;
+        }
+        if self.waited {
+            self.table.clock().catch_up(permits.freed_at);
+        }
+        permits.left -= 1;
+        holding.permit = Some(Permit { waiter: self.waiter, table: self.table });
+        Poll::Ready(())
+    }
+}
+
+/// One of the waiter's permits, given back when dropped.
+struct Permit<'a> {
+    waiter: &'a Waiter,
+    table: &'a Table,
+}
+
+impl<'a> Drop for Permit<'a> {
+    fn drop(&mut self) {
+        self.table.turn();
+        let mut permits = self.waiter.permits.lock().unwrap();
+        permits.left += 1;
+        permits.freed_at = permits.freed_at.max(self.table.clock().now());
+        self.waiter.freed.notify_one();
+        for waker in mem::take(&mut permits.wakers) {
+            waker.wake();
+        }
+        self.table.changed();
+    }
+}
+
+/// Chandy and Misra's solution. Every fork belongs to somebody and is
+/// either clean or dirty. Eating makes your forks dirty, and a dirty fork
+/// has to be handed over (cleaned) to a neighbour who wants it unless
+/// you're eating with it. Clean forks are kept until they've been eaten
+/// with, so whoever ate least recently wins.
+///
+/// Forks start out dirty, with the lower numbered of the two philosophers
+/// who share them. That way nobody starts out waiting on somebody who's
+/// waiting on them.
+///
+/// These forks are Chandy and Misra's, one between every two neighbours.
+/// At dinner they're the table's forks, but a cellar laid `sharing` has
+/// one between any two agents who ever need the same bottle, and everybody
+/// has to get all of theirs before they drink anything.
+#[derive(Debug)]
+pub struct ChandyMisra {
+    state: Mutex<Forks>,
+    /// Signalled whenever forks get dirty, which is when they can change
+    /// hands.
+    dirtied: Condvar,
+}
+
+#[derive(Debug)]
+struct Forks {
+    /// The seat number of each fork's owner.
+    owners: Vec<usize>,
+    dirty: Vec<bool>,
+    /// Which forks each seat needs to eat.
+    needs: Vec<Vec<usize>>,
+    eating: Vec<bool>,
+    /// Tasks waiting for forks to get dirty.
+    wakers: Vec<Waker>,
+}
+
+impl Forks {
+    /// Asks for whichever forks seat `me` doesn't have yet, and says
+    /// whether they've got all of them now.
+    fn claim(&mut self, me: usize) -> bool {
+        // Asking for a dirty fork that isn't being eaten with means
+        // getting it, cleaned
+        for &fork in &self.needs[me] {
+            let owner = self.owners[fork];
+            if owner != me && self.dirty[fork] && !self.eating[owner] {
+                self.owners[fork] = me;
+                self.dirty[fork] = false;
+            }
+        }
+
+        self.needs[me].iter().all(|&fork| self.owners[fork] == me)
+    }
+
+    /// Done eating, so all of `me`'s forks can be handed over.
+    fn dirty(&mut self, me: usize) {
+        self.eating[me] = false;
+        for &fork in &self.needs[me] {
+            self.dirty[fork] = true;
+        }
+    }
+}
+
+impl ChandyMisra {
+    /// Philosophers are expected to be seated the way `Roster::seat`
+    /// does it, with fork `n` between seats `n - 1` and `n`.
+    pub fn new(seats: usize) -> ChandyMisra {
+        let owners = (0..seats).map(|fork| fork.saturating_sub(1)).collect();
+        let needs = (0..seats).map(|seat| vec![seat, (seat + 1) % seats]).collect();
+        ChandyMisra::laid(owners, needs)
+    }
+
+    /// For a cellar, where `bottles[seat]` is every bottle the agent at
+    /// `seat` ever drinks from. Any two agents who share one get a fork
+    /// between them.
+    pub fn sharing(bottles: &[Vec<usize>]) -> ChandyMisra {
+        let mut owners = Vec::new();
+        let mut needs = vec![Vec::new(); bottles.len()];
+        for (me, mine) in bottles.iter().enumerate() {
+            for (them, theirs) in bottles.iter().enumerate().skip(me + 1) {
+                if mine.iter().any(|bottle| theirs.contains(bottle)) {
+                    needs[me].push(owners.len());
+                    needs[them].push(owners.len());
+                    owners.push(me);
+                }
+            }
+        }
+        ChandyMisra::laid(owners, needs)
+    }
+
+    fn laid(owners: Vec<usize>, needs: Vec<Vec<usize>>) -> ChandyMisra {
+        ChandyMisra {
+            state: Mutex::new(Forks {
+                dirty: vec![true; owners.len()],
+                owners,
+                eating: vec![false; needs.len()],
+                needs,
+                wakers: Vec::new(),
+            }),
+            dirtied: Condvar::new(),
+        }
+    }
+}
+
+impl Strategy for ChandyMisra {
+    fn name(&self) -> &'static str {
+        Kind::ChandyMisra.name()
+    }
+
+    fn dine(&self, seat: usize, forks: &[usize], table: &Table, eat: &mut dyn FnMut()) {
+        {
+            table.turn();
+            let mut state = self.state.lock().unwrap();
+            while !state.claim(seat) {
+                state = clock::wait(table.scheduler(), &self.state, &self.dirtied, state).unwrap();
+            }
+            state.eating[seat] = true;
+        }
+        // Even a meal that ends in a panic leaves the forks dirty, or the
+        // neighbours would wait for them forever
+        let _meal = Meal { chandy_misra: self, table, me: seat };
+
+        // Nobody else owns these, so the locks are only for show, but it
+        // keeps every strategy honest about using the table's forks
+        let _holding = Holding::lock(table, seat, forks);
+        eat();
+    }
+
+    fn pick_up<'a>(&'a self, philosopher: &'a Philosopher, table: &'a Table) -> PickingUp<'a> {
+        let (me, forks) = (philosopher.seat_number(), [philosopher.left, philosopher.right]);
+        gated(Claim { chandy_misra: self, table, me }, table, me, forks, Duration::from_millis(0))
+    }
+}
+
+/// Waiting to own every fork, as a task.
+struct Claim<'a> {
+    chandy_misra: &'a ChandyMisra,
+    table: &'a Table,
+    me: usize,
+}
+
+impl<'a> Gate<'a> for Claim<'a> {
+    fn poll_gate(&mut self, cx: &mut Context, holding: &mut Holding<'a>) -> Poll<()> {
+        if holding.meal.is_some() {
+            return Poll::Ready(());
+        }
+
+        let mut state = self.chandy_misra.state.lock().unwrap();
+        if !state.claim(self.me) {
+            state.wakers.push(cx.waker().clone());
+            return Poll::Pending;
+        }
+        state.eating[self.me] = true;
+        holding.meal = Some(Meal { chandy_misra: self.chandy_misra, table: self.table, me: self.me });
+        Poll::Ready(())
+    }
+}
+
+/// Somebody eating with all their forks. Dirties them when dropped.
+struct Meal<'a> {
+    chandy_misra: &'a ChandyMisra,
+    table: &'a Table,
+    me: usize,
+}
+
+impl<'a> Drop for Meal<'a> {
+    fn drop(&mut self) {
+        self.table.turn();
+        let mut state = self.chandy_misra.state.lock().unwrap();
+        state.dirty(self.me);
+        self.chandy_misra.dirtied.notify_all();
+        for waker in mem::take(&mut state.wakers) {
+            waker.wake();
+        }
+        self.table.changed();
+    }
+}
+
+/// Picks up the left fork and tries for the right. If it's taken, puts the
+/// left one back down and waits a little longer each time before trying
+/// again. In a cellar, it's the first bottle and then all the others.
+#[derive(Debug, Clone, Copy)]
+pub struct Backoff {
+    /// How long to wait after the first failed attempt.
+    pub initial: Duration,
+    /// The longest wait between attempts.
+    pub max: Duration,
+}
+
+impl Default for Backoff {
+    fn default() -> Backoff {
+        Backoff {
+            initial: Duration::from_millis(1),
+            max: Duration::from_millis(32),
+        }
+    }
+}
+
+impl Strategy for Backoff {
+    fn name(&self) -> &'static str {
+        Kind::Backoff.name()
+    }
+
+    fn dine(&self, seat: usize, forks: &[usize], table: &Table, eat: &mut dyn FnMut()) {
+        let (&first, rest) = forks.split_first().expect("nobody can eat with no forks");
+        let mut wait = self.initial;
+
+        loop {
+            {
+                let mut holding = Holding::lock(table, seat, &[first]);
+                for &fork in rest {
+                    match table.fork(fork).try_lock(seat) {
+                        Ok(guard) => holding.forks.push(guard),
+                        Err(TryLockError::WouldBlock) => break,
+                        Err(TryLockError::Poisoned(err)) => panic!("{}", err),
+                    }
+                }
+                if holding.forks.len() == forks.len() {
+                    eat();
+                    return;
+                }
+            }
+
+            table.clock().sleep(self.pause(seat, wait));
+            wait = (wait * 2).min(self.max);
+        }
+    }
+
+    fn pick_up<'a>(&'a self, philosopher: &'a Philosopher, table: &'a Table) -> PickingUp<'a> {
+        let me = philosopher.seat_number();
+        Box::pin(Retry {
+            backoff: self,
+            table,
+            me,
+            forks: [philosopher.left, philosopher.right],
+            wait: self.initial,
+            step: Step::PickingUp(table.fork(philosopher.left).pick_up(me)),
+        })
+    }
+}
+
+impl Backoff {
+    /// How long seat `me` waits before trying again, after `wait`.
+    fn pause(&self, me: usize, wait: Duration) -> Duration {
+        // Neighbours who back off for exactly as long as each other would
+        // just collide again, so stagger them by seat
+        wait + Duration::from_micros(me as u64 * 97 % 500)
+    }
+}
+
+/// Backing off, as a task.
+struct Retry<'a> {
+    backoff: &'a Backoff,
+    table: &'a Table,
+    me: usize,
+    forks: [usize; 2],
+    wait: Duration,
+    step: Step<'a>,
+}
+
+impl<'a> Future for Retry<'a> {
+    type Output = Holding<'a>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Holding<'a>> {
+        let this = &mut *self;
+        loop {
+            this.step = match this.step {
+                Step::PickingUp(ref mut left) => match Pin::new(left).poll(cx) {
+                    Poll::Pending => return Poll::Pending,
+                    Poll::Ready(left) => {
+                        let left = left.unwrap();
+                        match this.table.fork(this.forks[1]).try_lock(this.me) {
+                            Ok(right) => {
+                                let mut holding = Holding::default();
+                                holding.forks = vec![left, right];
+                                return Poll::Ready(holding);
+                            }
+                            Err(TryLockError::WouldBlock) => drop(left),
+                            Err(TryLockError::Poisoned(err)) => panic!("{}", err),
+                        }
+
+                        let pause = this.backoff.pause(this.me, this.wait);
+                        this.wait = (this.wait * 2).min(this.backoff.max);
+                        Step::Reaching(executor::sleep(this.table.clock(), pause))
+                    }
+                },
+                Step::Reaching(ref mut pause) => match Pin::new(pause).poll(cx) {
+                    Poll::Pending => return Poll::Pending,
+                    Poll::Ready(()) => Step::PickingUp(this.table.fork(this.forks[0]).pick_up(this.me)),
+                },
+            };
+        }
+    }
+}
+
+/// The waiter for a cellar: hands over every bottle a session needs at
+/// once, or none of them, so nobody is ever sat holding one bottle waiting
+/// for another. Works for forks too, by handing over both.
+#[derive(Debug)]
+pub struct Sommelier {
+    cellar: Mutex<Cellar>,
+    returned: Condvar,
+}
+
+#[derive(Debug)]
+struct Cellar {
+    /// Which bottles have been handed out.
+    out: Vec<bool>,
+    /// Tasks waiting for bottles to be handed back.
+    wakers: Vec<Waker>,
+}
+
+impl Cellar {
+    /// Hands over all of `bottles` if none of them are out, and says
+    /// whether it did.
+    fn hand_over(&mut self, bottles: &[usize]) -> bool {
+        if bottles.iter().any(|&bottle| self.out[bottle]) {
+            return false;
+        }
+        for &bottle in bottles {
+            self.out[bottle] = true;
+        }
+        true
+    }
+}
+
+impl Sommelier {
+    pub fn new(bottles: usize) -> Sommelier {
+        Sommelier {
+            cellar: Mutex::new(Cellar { out: vec![false; bottles], wakers: Vec::new() }),
+            returned: Condvar::new(),
+        }
+    }
+}
+
+impl Strategy for Sommelier {
+    fn name(&self) -> &'static str {
+        Kind::Waiter.name()
+    }
+
+    fn dine(&self, seat: usize, forks: &[usize], table: &Table, eat: &mut dyn FnMut()) {
+        {
+            table.turn();
+            let mut cellar = self.cellar.lock().unwrap();
+            while !cellar.hand_over(forks) {
+                cellar = clock::wait(table.scheduler(), &self.cellar, &self.returned, cellar).unwrap();
+            }
+        }
+        // Given back after they've been put down, so nobody gets them and
+        // then has to wait for them
+        let _handed = Handed { sommelier: self, table, bottles: forks.to_vec() };
+
+        // Nobody else can have these, but the bottles keep the time
+        let _holding = Holding::lock(table, seat, forks);
+        eat();
+    }
+
+    fn pick_up<'a>(&'a self, philosopher: &'a Philosopher, table: &'a Table) -> PickingUp<'a> {
+        let forks = [philosopher.left, philosopher.right];
+        let order = Order { sommelier: self, table, forks };
+        gated(order, table, philosopher.seat_number(), forks, Duration::from_millis(0))
+    }
+}
+
+/// Waiting for the sommelier, as a task.
+struct Order<'a> {
+    sommelier: &'a Sommelier,
+    table: &'a Table,
+    forks: [usize; 2],
+}
+
+impl<'a> Gate<'a> for Order<'a> {
+    fn poll_gate(&mut self, cx: &mut Context, holding: &mut Holding<'a>) -> Poll<()> {
+        if holding.handed.is_some() {
+            return Poll::Ready(());
+        }
+
+        let mut cellar = self.sommelier.cellar.lock().unwrap();
+        if !cellar.hand_over(&self.forks) {
+            cellar.wakers.push(cx.waker().clone());
+            return Poll::Pending;
+        }
+        holding.handed = Some(Handed { sommelier: self.sommelier, table: self.table, bottles: self.forks.to_vec() });
+        Poll::Ready(())
+    }
+}
+
+/// Bottles the sommelier has handed out, given back when dropped.
+struct Handed<'a> {
+    sommelier: &'a Sommelier,
+    table: &'a Table,
+    bottles: Vec<usize>,
+}
+
+impl<'a> Drop for Handed<'a> {
+    fn drop(&mut self) {
+        self.table.turn();
+        let mut cellar = self.sommelier.cellar.lock().unwrap();
+        for &bottle in &self.bottles {
+            cellar.out[bottle] = false;
+        }
+        self.sommelier.returned.notify_all();
+        for waker in mem::take(&mut cellar.wakers) {
+            waker.wake();
+        }
+        self.table.changed();
+    }
+}
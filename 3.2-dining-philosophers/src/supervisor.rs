@@ -0,0 +1,164 @@
+//! Dinner that carries on when somebody panics.
+//!
+//! Without supervision, a philosopher who panics mid-meal poisons both the
+//! forks they were holding. Their neighbours `unwrap` the poisoned forks
+//! and panic too, and so on round the table, until `Simulation::run`
+//! unwraps the first dead thread and takes the whole program down with it.
+//!
+//! A `Supervisor` catches the panic on the philosopher's own thread, makes
+//! a note of it, and starts them again from their next meal. The table has
+//! to be laid `with_recovery`, so that whoever gets to a poisoned fork
+//! first, the neighbour or the supervisor, cleans it off rather than
+//! panicking. Anybody who keeps on panicking is eventually given up on.
+
+use std::any::Any;
+use std::fmt;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use clock::Seated;
+use simulation::{Diner, Report, Simulation};
+use {Philosopher, Strategy, Table};
+
+/// One time somebody panicked.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Failure {
+    pub seat: usize,
+    pub name: String,
+    /// The meal they were having, counting from 1, whether or not they'd
+    /// got as far as eating it.
+    pub meal: u32,
+    /// What they panicked with.
+    pub message: String,
+    /// The meal they were having, plus, if they were given up on, every
+    /// meal they still had to come.
+    pub lost: u32,
+    /// Whether this was once too many, so they weren't started again.
+    pub gave_up: bool,
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} (seat {}) panicked during meal {}: {}", self.name, self.seat, self.meal,
+               self.message)?;
+        if self.gave_up {
+            write!(f, ", and was given up on")?;
+        }
+        Ok(())
+    }
+}
+
+/// Restarts philosophers who panic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Supervisor {
+    /// How many times a philosopher is started again before they're given
+    /// up on.
+    pub restarts: u32,
+}
+
+impl Default for Supervisor {
+    fn default() -> Supervisor {
+        Supervisor { restarts: 3 }
+    }
+}
+
+impl Supervisor {
+    /// Like `Simulation::run`, but a philosopher who panics loses the meal
+    /// they were having instead of bringing down the table. The report
+    /// says who failed, and how often each fork had to be cleaned off.
+    ///
+    /// # Panics
+    ///
+    /// If `table` wasn't laid `with_recovery`.
+    pub fn run(&self, simulation: &Simulation, philosophers: Vec<Philosopher>, table: Arc<Table>,
+               strategy: Arc<dyn Strategy>) -> Report {
+        assert!(table.is_recovering(), "a supervised table has to be laid `with_recovery`");
+        let (supervisor, simulation) = (*self, *simulation);
+
+        if let Some(scheduler) = table.scheduler() {
+            scheduler.expect(philosophers.len());
+        }
+        let handles: Vec<_> = philosophers.into_iter().map(|p| {
+            let table = table.clone();
+            let strategy = strategy.clone();
+            thread::spawn(move || {
+                let _seated = Seated::new(table.scheduler(), p.seat_number());
+                supervisor.dine(&simulation, &p, &table, &*strategy)
+            })
+        }).collect();
+
+        let mut diners = Vec::new();
+        let mut failures = Vec::new();
+        let mut elapsed = Duration::default();
+        for h in handles {
+            // Every panic has already been caught, so this one's a bug
+            let (diner, mut failed, finished) = h.join().unwrap();
+            diners.push(diner);
+            failures.append(&mut failed);
+            elapsed = elapsed.max(finished);
+        }
+
+        Report {
+            diners,
+            elapsed,
+            failures,
+            recovered: (0..table.seats()).map(|fork| table.fork(fork).recoveries()).collect(),
+        }
+    }
+
+    /// One philosopher's dinner, with every meal run under `catch_unwind`.
+    fn dine(&self, simulation: &Simulation, p: &Philosopher, table: &Table, strategy: &dyn Strategy)
+            -> (Diner, Vec<Failure>, Duration) {
+        let clock = table.clock();
+        let mut diner = Diner::new(&p.name);
+        let mut meals_left = simulation.meals_for(p);
+        let mut failures = Vec::new();
+        let mut meal = 0;
+
+        while simulation.another_meal(&mut meals_left, clock.now()) {
+            meal += 1;
+            // A meal that doesn't get finished doesn't count
+            let before = diner.clone();
+            let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                simulation.meal(p, table, strategy, &mut diner, meal);
+            }));
+
+            let payload = match result {
+                Ok(()) => continue,
+                Err(payload) => payload,
+            };
+            diner = before;
+            // Unless a neighbour has beaten us to it
+            table.fork(p.left).recover();
+            table.fork(p.right).recover();
+
+            let gave_up = failures.len() as u32 >= self.restarts;
+            failures.push(Failure {
+                seat: p.seat_number(),
+                name: p.name.clone(),
+                meal,
+                message: message(&*payload),
+                lost: if gave_up { 1 + meals_left.unwrap_or(0) } else { 1 },
+                gave_up,
+            });
+            if gave_up {
+                break;
+            }
+        }
+
+        (diner, failures, clock.now())
+    }
+}
+
+/// What a panic said, if it said anything.
+fn message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "something went wrong".to_string()
+    }
+}
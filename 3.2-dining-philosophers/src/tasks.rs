@@ -0,0 +1,192 @@
+//! Dinner with every philosopher as an async task instead of a thread, all
+//! taking turns on one thread.
+//!
+//! A philosopher here is a future, written out by hand as the state machine
+//! an `async fn` would turn into: each time it's polled it carries on from
+//! whatever it was waiting for last, until it has to wait again. Forks are
+//! picked up with the strategy's `pick_up`, and thinking and eating are
+//! `Sleep`s, so nobody ever blocks the thread.
+
+use std::error;
+use std::fmt;
+use std::future::Future;
+use std::mem;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use events::Kind as Event;
+use executor::{self, Executor, Sleep};
+use simulation::{Diner, Report, Simulation};
+use strategy::{Holding, PickingUp, Strategy};
+use watchdog::{self, Deadlock};
+use {Philosopher, Table};
+
+/// Why dinner didn't finish.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// Every philosopher that hadn't finished was waiting for a fork that
+    /// would never be put down.
+    Deadlock(Deadlock),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Deadlock(ref deadlock) => deadlock.fmt(f),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+/// Like `Simulation::run`, but with tasks. Dinner happens on the calling
+/// thread, by the table's clock, and everybody gets their forks with
+/// `strategy`'s `pick_up`.
+pub fn run(simulation: &Simulation, philosophers: Vec<Philosopher>, table: &Table, strategy: &dyn Strategy)
+           -> Result<Report, Error> {
+    let mut executor = Executor::new(table.clock());
+    for p in &philosophers {
+        executor.spawn(Dinner::new(simulation, p, table, strategy));
+    }
+
+    match executor.run() {
+        Ok(finished) => {
+            let mut diners = Vec::new();
+            let mut elapsed = Duration::default();
+            for (diner, done) in finished {
+                diners.push(diner);
+                elapsed = elapsed.max(done);
+            }
+            Ok(Report::new(diners, elapsed))
+        }
+        // Nothing else is going on, and the stuck philosophers are still
+        // waiting, so this is the whole picture
+        Err(_) => Err(Error::Deadlock(Deadlock {
+            cycle: watchdog::find_cycle(table).unwrap_or_default(),
+            names: philosophers.iter().map(|p| p.name.clone()).collect(),
+            resource: table.resource(),
+        })),
+    }
+}
+
+/// What a philosopher is waiting for.
+enum State<'a> {
+    /// About to start another meal, if there's one to have.
+    Done,
+    Thinking(Sleep<'a>),
+    Hungry(PickingUp<'a>),
+    Eating(Holding<'a>, Sleep<'a>),
+    /// Halfway through a meal they're about to drop their fork in.
+    Fumbling(Holding<'a>, Sleep<'a>),
+}
+
+/// One philosopher's dinner, as a future.
+struct Dinner<'a> {
+    simulation: Simulation,
+    p: &'a Philosopher,
+    table: &'a Table,
+    strategy: &'a dyn Strategy,
+    diner: Diner,
+    meals_left: Option<u32>,
+    /// When they last got hungry.
+    hungry: Duration,
+    state: State<'a>,
+}
+
+impl<'a> Dinner<'a> {
+    fn new(simulation: &Simulation, p: &'a Philosopher, table: &'a Table, strategy: &'a dyn Strategy)
+           -> Dinner<'a> {
+        Dinner {
+            simulation: *simulation,
+            p,
+            table,
+            strategy,
+            diner: Diner::new(&p.name),
+            meals_left: simulation.meals_for(p),
+            hungry: Duration::default(),
+            state: State::Done,
+        }
+    }
+
+    fn record(&self, event: Event) {
+        self.table.record(self.p.seat_number(), event);
+    }
+
+    fn sleep(&self, duration: Duration) -> Sleep<'a> {
+        executor::sleep(self.table.clock(), duration)
+    }
+}
+
+impl<'a> Future for Dinner<'a> {
+    type Output = (Diner, Duration);
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<(Diner, Duration)> {
+        let this = &mut *self;
+        let clock = this.table.clock();
+
+        // Keep going until there's something to wait for. Every arm either
+        // moves on to the next state, or puts the state back and waits.
+        loop {
+            this.state = match mem::replace(&mut this.state, State::Done) {
+                State::Done => {
+                    if !this.simulation.another_meal(&mut this.meals_left, clock.now()) {
+                        let diner = mem::replace(&mut this.diner, Diner::new(&this.p.name));
+                        return Poll::Ready((diner, clock.now()));
+                    }
+                    this.record(Event::Thinking);
+                    State::Thinking(this.sleep(this.p.think_time))
+                }
+                State::Thinking(mut thinking) => match Pin::new(&mut thinking).poll(cx) {
+                    Poll::Pending => {
+                        this.state = State::Thinking(thinking);
+                        return Poll::Pending;
+                    }
+                    Poll::Ready(()) => {
+                        this.hungry = clock.now();
+                        this.record(Event::Hungry);
+                        State::Hungry(this.strategy.pick_up(this.p, this.table))
+                    }
+                },
+                State::Hungry(mut forks) => match forks.as_mut().poll(cx) {
+                    Poll::Pending => {
+                        this.state = State::Hungry(forks);
+                        return Poll::Pending;
+                    }
+                    Poll::Ready(holding) => {
+                        this.diner.ate(clock.now() - this.hungry);
+                        this.record(Event::Eating);
+                        if this.p.fumbles.contains(&this.diner.meals) {
+                            State::Fumbling(holding, this.sleep(this.simulation.eat_time / 2))
+                        } else {
+                            State::Eating(holding, this.sleep(this.simulation.eat_time))
+                        }
+                    }
+                },
+                State::Eating(holding, mut eating) => match Pin::new(&mut eating).poll(cx) {
+                    Poll::Pending => {
+                        this.state = State::Eating(holding, eating);
+                        return Poll::Pending;
+                    }
+                    Poll::Ready(()) => {
+                        this.record(Event::Done);
+                        // Puts everything back down
+                        drop(holding);
+                        State::Done
+                    }
+                },
+                State::Fumbling(holding, mut eating) => match Pin::new(&mut eating).poll(cx) {
+                    Poll::Pending => {
+                        this.state = State::Fumbling(holding, eating);
+                        return Poll::Pending;
+                    }
+                    // Still holding everything, the same as a thread would be
+                    Poll::Ready(()) => {
+                        this.record(Event::Dropped);
+                        panic!("{} dropped their fork", this.p.name)
+                    }
+                },
+            };
+        }
+    }
+}
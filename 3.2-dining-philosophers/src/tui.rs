@@ -0,0 +1,179 @@
+//! Watching dinner in the terminal.
+//!
+//! The table is redrawn a few times a second from a `Board`, with every
+//! philosopher coloured by what they're doing and every fork by whoever's
+//! holding it. Time goes by a `PacedClock`, so it can be paused, stepped
+//! through and sped up from the keyboard:
+//!
+//! * space pauses and resumes
+//! * `s` lets time run on a little, then pauses again
+//! * `+` and `-` speed the clock up and slow it down
+//! * `q` gives up on dinner altogether
+
+use std::io::{self, Stdout, Write};
+use std::process;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use termion::color::{self, Fg};
+use termion::event::Key;
+use termion::input::TermRead;
+use termion::raw::{IntoRawMode, RawTerminal};
+use termion::screen::AlternateScreen;
+use termion::{clear, cursor, style, terminal_size};
+
+use board::{Board, Label, Mood, Paint};
+use clock::{Clock, PacedClock};
+
+/// How often the table is redrawn.
+const FRAME: Duration = Duration::from_millis(50);
+
+const FASTEST: f64 = 64.0;
+const SLOWEST: f64 = 1.0 / 16.0;
+
+type Screen = AlternateScreen<RawTerminal<Stdout>>;
+
+/// The terminal, taken over until `close`.
+pub struct Tui {
+    screen: Mutex<Option<Screen>>,
+    clock: Arc<PacedClock>,
+    keys: Mutex<Receiver<Key>>,
+    over: AtomicBool,
+}
+
+impl Tui {
+    /// Takes over the terminal and starts drawing `board`. Each press of
+    /// `s` lets `clock` run on by `step`.
+    pub fn start(board: Arc<Board>, clock: Arc<PacedClock>, step: Duration) -> io::Result<Arc<Tui>> {
+        let mut screen = AlternateScreen::from(io::stdout().into_raw_mode()?);
+        write!(screen, "{}", cursor::Hide)?;
+
+        let (sender, keys) = mpsc::channel();
+        let tui = Arc::new(Tui {
+            screen: Mutex::new(Some(screen)),
+            clock: clock.clone(),
+            keys: Mutex::new(keys),
+            over: AtomicBool::new(false),
+        });
+
+        let input = tui.clone();
+        let controls = clock.clone();
+        thread::spawn(move || {
+            for key in io::stdin().keys() {
+                let key = match key {
+                    Ok(key) => key,
+                    Err(_) => break,
+                };
+                match key {
+                    Key::Char('q') | Key::Ctrl('c') => {
+                        input.close();
+                        process::exit(0);
+                    }
+                    Key::Char(' ') if controls.is_paused() => controls.resume(),
+                    Key::Char(' ') => controls.pause(),
+                    Key::Char('s') => controls.step(step),
+                    Key::Char('+') | Key::Char('=') => {
+                        controls.set_speed((controls.speed() * 2.0).min(FASTEST));
+                    }
+                    Key::Char('-') => controls.set_speed((controls.speed() / 2.0).max(SLOWEST)),
+                    _ => {}
+                }
+                // Whoever's waiting for a key press at the end of dinner
+                let _ = sender.send(key);
+            }
+        });
+
+        let drawing = tui.clone();
+        thread::spawn(move || {
+            loop {
+                let status = status(&clock, drawing.over.load(Ordering::SeqCst));
+                if !drawing.draw(&board, &status) {
+                    return;
+                }
+                thread::sleep(FRAME);
+            }
+        });
+
+        Ok(tui)
+    }
+
+    /// Says dinner's over, and waits for a key before giving back the
+    /// terminal.
+    pub fn finish(&self) {
+        self.over.store(true, Ordering::SeqCst);
+        // So the time on the screen is when dinner ended
+        self.clock.pause();
+        let keys = self.keys.lock().unwrap();
+        // Throw away anything pressed during dinner
+        while keys.try_recv().is_ok() {}
+        let _ = keys.recv();
+        self.close();
+    }
+
+    /// Gives the terminal back the way it was. Safe to call more than once.
+    pub fn close(&self) {
+        if let Some(mut screen) = self.screen.lock().unwrap().take() {
+            let _ = write!(screen, "{}{}", style::Reset, cursor::Show);
+            let _ = screen.flush();
+        }
+    }
+
+    /// Draws a frame, unless the terminal has been given back already.
+    fn draw(&self, board: &Board, status: &str) -> bool {
+        let mut screen = self.screen.lock().unwrap();
+        let screen = match *screen {
+            Some(ref mut screen) => screen,
+            None => return false,
+        };
+
+        let (width, height) = terminal_size().unwrap_or((80, 24));
+        // The last two lines are for the legend and the status line
+        let labels = board.scene().layout(width, height.saturating_sub(2));
+
+        let mut frame = String::new();
+        frame.push_str(clear::All.as_ref());
+        for label in labels {
+            frame.push_str(&paint(&label));
+        }
+
+        frame.push_str(&cursor::Goto(1, height.saturating_sub(1).max(1)).to_string());
+        for &mood in &Mood::ALL {
+            frame.push_str(&format!("{}{}{} ", colour(Paint::Mood(mood)), mood.name(), style::Reset));
+        }
+        frame.push_str(&format!("{}{}", cursor::Goto(1, height.max(1)), status));
+
+        let _ = screen.write_all(frame.as_bytes());
+        let _ = screen.flush();
+        true
+    }
+}
+
+/// The line along the bottom.
+fn status(clock: &PacedClock, over: bool) -> String {
+    let state = if over {
+        "dinner's over, press any key"
+    } else if clock.is_paused() {
+        "paused"
+    } else {
+        "running"
+    };
+    format!("{:.1}s at {}x, {}   space: pause  s: step  +/-: speed  q: quit",
+            clock.now().as_secs_f64(), clock.speed(), state)
+}
+
+fn paint(label: &Label) -> String {
+    format!("{}{}{}{}", cursor::Goto(label.x, label.y), colour(label.paint), label.text, style::Reset)
+}
+
+fn colour(paint: Paint) -> String {
+    match paint {
+        Paint::Mood(Mood::Thinking) => Fg(color::Blue).to_string(),
+        Paint::Mood(Mood::Hungry) => Fg(color::Yellow).to_string(),
+        Paint::Mood(Mood::Eating) => Fg(color::Green).to_string(),
+        Paint::Mood(Mood::Full) => Fg(color::LightBlack).to_string(),
+        Paint::Free => Fg(color::White).to_string(),
+    }
+}
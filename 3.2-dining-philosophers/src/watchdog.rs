@@ -0,0 +1,152 @@
+//! Notices when the table has deadlocked.
+//!
+//! Every so often the watchdog looks at who's holding and who's waiting for
+//! each fork, and draws the wait-for graph: an edge from each waiting
+//! philosopher to whoever holds the fork they want. A cycle in that graph
+//! means everybody in it is waiting on somebody else in it, forever.
+//!
+//! A table with a `Scheduler` can also just say it's `stuck`, even when
+//! what everybody's waiting for isn't a fork.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use {Resource, Table};
+
+/// One edge of a wait-for cycle: `philosopher` wants `fork`, which
+/// `holder` has.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Wait {
+    pub philosopher: usize,
+    pub fork: usize,
+    pub holder: usize,
+}
+
+/// A cycle of philosophers waiting on each other, with their names.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Deadlock {
+    /// Starts with the lowest numbered seat, and each philosopher is
+    /// waiting on the next one. Empty if nobody's waiting on a fork, but
+    /// the table's scheduler says they're stuck all the same.
+    pub cycle: Vec<Wait>,
+    pub names: Vec<String>,
+    /// Whether it's forks they're waiting for, or bottles.
+    pub resource: Resource,
+}
+
+impl fmt::Display for Deadlock {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (users, name) = (self.resource.users(), self.resource.name());
+        if self.cycle.is_empty() {
+            return write!(f, "deadlock: everybody is stuck waiting, though not for each other's {}s", name);
+        }
+        write!(f, "deadlock: {} {} are waiting on each other", self.cycle.len(), users)?;
+        for wait in &self.cycle {
+            write!(f, "\n    {} (seat {}) is waiting for {} {}, which {} (seat {}) is holding",
+                   self.name(wait.philosopher), wait.philosopher, name, wait.fork,
+                   self.name(wait.holder), wait.holder)?;
+        }
+        Ok(())
+    }
+}
+
+impl Deadlock {
+    fn name(&self, seat: usize) -> &str {
+        self.names.get(seat).map(|name| name.as_str()).unwrap_or("somebody")
+    }
+}
+
+/// Looks for a cycle in the table's wait-for graph right now.
+///
+/// The forks are looked at one after the other, not all at once, so a busy
+/// table can occasionally look deadlocked when it isn't. `Watchdog` only
+/// believes a cycle it sees twice in a row.
+pub fn find_cycle(table: &Table) -> Option<Vec<Wait>> {
+    let mut waits = HashMap::new();
+    for index in 0..table.seats() {
+        let fork = table.fork(index);
+        if let Some(holder) = fork.holder() {
+            for philosopher in fork.waiting() {
+                if philosopher != holder {
+                    waits.insert(philosopher, Wait { philosopher, fork: index, holder });
+                }
+            }
+        }
+    }
+
+    // Everybody waits for at most one fork, so following the edges from any
+    // philosopher either runs out or goes round in a circle
+    let mut starts: Vec<usize> = waits.keys().cloned().collect();
+    starts.sort();
+    for start in starts {
+        let mut path = vec![start];
+        let mut current = start;
+
+        while let Some(wait) = waits.get(&current) {
+            current = wait.holder;
+            if let Some(position) = path.iter().position(|&seat| seat == current) {
+                let mut cycle: Vec<Wait> = path[position..].iter().map(|seat| waits[seat].clone()).collect();
+                let lowest = (0..cycle.len()).min_by_key(|&i| cycle[i].philosopher).unwrap();
+                cycle.rotate_left(lowest);
+                return Some(cycle);
+            }
+            path.push(current);
+        }
+    }
+
+    None
+}
+
+/// A thread that keeps an eye on a table and calls `on_deadlock` if it ever
+/// deadlocks.
+pub struct Watchdog {
+    stop: Arc<AtomicBool>,
+    handle: thread::JoinHandle<()>,
+}
+
+impl Watchdog {
+    /// Checks `table` every `interval`. `names` are the philosophers' names
+    /// by seat, for the report.
+    pub fn spawn<F>(table: Arc<Table>, names: Vec<String>, interval: Duration, on_deadlock: F)
+                    -> Watchdog
+        where F: FnOnce(Deadlock) + Send + 'static
+    {
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = stop.clone();
+
+        let handle = thread::spawn(move || {
+            let mut suspect = None;
+
+            while !stopped.load(Ordering::SeqCst) {
+                // Checked first, so that whatever cycle there is afterwards
+                // is the one they're stuck in
+                let stuck = table.scheduler().is_some_and(|scheduler| scheduler.stuck());
+                let cycle = find_cycle(&table);
+                match cycle {
+                    Some(cycle) if stuck || Some(&cycle) == suspect.as_ref() => {
+                        on_deadlock(Deadlock { cycle, names, resource: table.resource() });
+                        return;
+                    }
+                    None if stuck => {
+                        on_deadlock(Deadlock { cycle: Vec::new(), names, resource: table.resource() });
+                        return;
+                    }
+                    cycle => suspect = cycle,
+                }
+                thread::sleep(interval);
+            }
+        });
+
+        Watchdog { stop, handle }
+    }
+
+    /// Stops watching.
+    pub fn stop(self) {
+        self.stop.store(true, Ordering::SeqCst);
+        let _ = self.handle.join();
+    }
+}
@@ -0,0 +1,90 @@
+extern crate dining_philosophers;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use dining_philosophers::{Event, EventSink, Kind, Length, Roster, Simulation, Strategy, Table,
+                          VirtualClock};
+use dining_philosophers::board::{Board, Mood, Paint, Scene};
+use dining_philosophers::events::{self, Side};
+
+fn names(count: usize) -> Vec<String> {
+    Roster::generated(count).guests.into_iter().map(|guest| guest.name).collect()
+}
+
+fn event(philosopher: usize, kind: events::Kind) -> Event {
+    Event { at: Duration::default(), philosopher, kind }
+}
+
+#[test]
+fn scene_follows_events() {
+    let mut scene = Scene::new(names(3));
+    assert_eq!(scene.moods, vec![Mood::Thinking; 3]);
+    assert_eq!(scene.holders, vec![None; 3]);
+
+    scene.apply(&event(1, events::Kind::Hungry));
+    scene.apply(&event(1, events::Kind::PickedUp { side: Side::Left, fork: 1 }));
+    scene.apply(&event(1, events::Kind::PickedUp { side: Side::Right, fork: 2 }));
+    scene.apply(&event(1, events::Kind::Eating));
+    assert_eq!(scene.moods, vec![Mood::Thinking, Mood::Eating, Mood::Thinking]);
+    assert_eq!(scene.holders, vec![None, Some(1), Some(1)]);
+    assert_eq!(scene.meals, vec![0, 1, 0]);
+
+    scene.apply(&event(1, events::Kind::Done));
+    scene.apply(&event(1, events::Kind::PutDown { side: Side::Right, fork: 2 }));
+    assert_eq!(scene.moods[1], Mood::Full);
+    assert_eq!(scene.holders, vec![None, Some(1), None]);
+}
+
+#[test]
+fn layout_goes_round_the_table() {
+    let mut scene = Scene::new(names(4));
+    scene.apply(&event(2, events::Kind::Hungry));
+    scene.apply(&event(2, events::Kind::PickedUp { side: Side::Left, fork: 2 }));
+
+    let labels = scene.layout(80, 24);
+    assert_eq!(labels.len(), 8);
+    assert!(labels.iter().all(|label| label.x >= 1 && label.y >= 1));
+    assert!(labels.iter().all(|label| label.x as usize + label.text.chars().count() <= 81));
+    assert!(labels.iter().all(|label| label.y <= 24));
+
+    // Seat 0 at the top, then clockwise
+    let (top, right, bottom, left) = (&labels[0], &labels[1], &labels[2], &labels[3]);
+    assert_eq!(top.text, "0 Philosopher 1 (0)");
+    assert!(top.y < right.y && right.y < bottom.y);
+    assert!(left.x < top.x && top.x < right.x);
+    assert_eq!(bottom.paint, Paint::Mood(Mood::Hungry));
+
+    // Forks are inside the circle, coloured by whoever's holding them
+    let forks = &labels[4..];
+    assert_eq!(forks[2].text, "ψ2");
+    assert_eq!(forks[2].paint, Paint::Mood(Mood::Hungry));
+    assert_eq!(forks[0].text, "ψ");
+    assert_eq!(forks[0].paint, Paint::Free);
+    assert!(forks.iter().all(|fork| fork.y > top.y && fork.y < bottom.y));
+}
+
+#[test]
+fn layout_fits_a_tiny_terminal() {
+    let scene = Scene::new(names(5));
+    for label in scene.layout(20, 5) {
+        assert!(label.x >= 1 && label.y >= 1);
+    }
+}
+
+#[test]
+fn board_ends_with_everybody_full_and_every_fork_down() {
+    let roster = Roster::default();
+    let board = Arc::new(Board::new(names(roster.len())));
+    let table = Table::with_clock(roster.len(), Arc::new(VirtualClock::new()))
+        .with_events(board.clone() as Arc<dyn EventSink>);
+    let strategy: Arc<dyn Strategy> = Arc::from(Kind::Waiter.build(roster.len()));
+    let simulation = Simulation { length: Length::Rounds(3), eat_time: Duration::from_secs(1) };
+
+    simulation.run(roster.seat(), Arc::new(table), strategy);
+
+    let scene = board.scene();
+    assert_eq!(scene.moods, vec![Mood::Full; 5]);
+    assert_eq!(scene.holders, vec![None; 5]);
+    assert_eq!(scene.meals, vec![3; 5]);
+}
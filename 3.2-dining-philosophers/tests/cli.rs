@@ -0,0 +1,118 @@
+extern crate dining_philosophers;
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use dining_philosophers::{Kind, Length, Mode, Simulation, clock};
+use dining_philosophers::cli::Options;
+
+fn args(args: &[&str]) -> Vec<String> {
+    args.iter().map(|arg| arg.to_string()).collect()
+}
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn strategy_flag() {
+    assert_eq!(Options::parse(args(&[])).unwrap().strategy, Kind::ResourceOrdering);
+    assert_eq!(Options::parse(args(&["--strategy", "waiter"])).unwrap().strategy, Kind::Waiter);
+    assert_eq!(Options::parse(args(&["--strategy=chandy-misra"])).unwrap().strategy,
+               Kind::ChandyMisra);
+    assert!(Options::parse(args(&["--strategy"])).is_err());
+    assert!(Options::parse(args(&["--strategy", "polite"])).is_err());
+}
+
+#[test]
+fn roster_flags() {
+    assert_eq!(Options::parse(args(&["--count", "200"])).unwrap().count, Some(200));
+    assert_eq!(Options::parse(args(&["--roster=guests.toml"])).unwrap().roster,
+               Some(PathBuf::from("guests.toml")));
+    assert!(Options::parse(args(&["--count", "1"])).is_err());
+    assert!(Options::parse(args(&["--count", "many"])).is_err());
+    assert!(Options::parse(args(&["--count", "5", "--roster", "guests.toml"])).is_err());
+}
+
+#[test]
+fn graph_flags() {
+    let options = Options::parse(args(&["--graph", "bank.toml", "--strategy", "waiter"])).unwrap();
+    assert_eq!(options.graph, Some(PathBuf::from("bank.toml")));
+    assert!(Options::parse(args(&["--graph", "g.toml", "--events", "e.jsonl", "--trace", "t.json"])).is_ok());
+
+    assert!(Options::parse(args(&["--graph", "g.toml", "--count", "3"])).is_err());
+    assert!(Options::parse(args(&["--graph", "g.toml", "--tui"])).is_err());
+    assert!(Options::parse(args(&["--graph", "g.toml", "--mode", "async"])).is_err());
+    assert!(Options::parse(args(&["--graph", "g.toml", "--strategy", "chandy-misra"])).is_ok());
+}
+
+#[test]
+fn simulation_flags() {
+    let defaults = Options::parse(args(&[])).unwrap();
+    assert_eq!(defaults.simulation, Simulation::default());
+    assert_eq!(defaults.starvation, ms(5000));
+
+    let options = Options::parse(args(&["--rounds", "3", "--eat-time=10", "--starvation", "20",
+                                        "--quiet"])).unwrap();
+    assert_eq!(options.simulation, Simulation { length: Length::Rounds(3), eat_time: ms(10) });
+    assert!(options.quiet);
+    assert_eq!(options.starvation, ms(20));
+
+    assert_eq!(Options::parse(args(&["--duration", "250"])).unwrap().simulation.length,
+               Length::Duration(ms(250)));
+    assert!(Options::parse(args(&["--rounds", "3", "--duration", "250"])).is_err());
+
+    assert_eq!(Options::parse(args(&["--mode", "serial", "--strategy", "waiter"])).unwrap().mode,
+               Mode::Serial);
+}
+
+#[test]
+fn supervise_flags() {
+    let options = Options::parse(args(&["--supervise", "--fumble", "2:1", "--fumble=0:3"])).unwrap();
+    assert!(options.supervise);
+    assert_eq!(options.fumbles, vec![(2, 1), (0, 3)]);
+
+    assert!(Options::parse(args(&["--supervise", "--mode", "serial"])).is_err());
+    assert!(Options::parse(args(&["--fumble", "1:1", "--mode", "async"])).is_ok());
+    assert!(Options::parse(args(&["--fumble", "1"])).is_err());
+    assert!(Options::parse(args(&["--fumble", "1:0"])).is_err());
+    assert!(Options::parse(args(&["--fumble", "a:1"])).is_err());
+}
+
+#[test]
+fn event_flags() {
+    let defaults = Options::parse(args(&[])).unwrap();
+    assert_eq!((defaults.events, defaults.trace, defaults.quiet), (None, None, false));
+
+    let options = Options::parse(args(&["--events", "dinner.jsonl", "--trace=dinner.json"]))
+        .unwrap();
+    assert_eq!(options.events.unwrap().to_str(), Some("dinner.jsonl"));
+    assert_eq!(options.trace.unwrap().to_str(), Some("dinner.json"));
+    assert!(Options::parse(args(&["--trace"])).is_err());
+}
+
+#[test]
+fn clock_flag() {
+    assert_eq!(Options::parse(args(&[])).unwrap().clock, clock::Kind::Real);
+    assert_eq!(Options::parse(args(&["--clock", "virtual"])).unwrap().clock, clock::Kind::Virtual);
+    assert_eq!(Options::parse(args(&["--clock", "discrete"])).unwrap().clock, clock::Kind::Discrete);
+    assert!(Options::parse(args(&["--clock", "sundial"])).is_err());
+}
+
+#[test]
+fn mode_flag() {
+    assert_eq!(Options::parse(args(&[])).unwrap().mode, Mode::Threaded);
+    assert_eq!(Options::parse(args(&["--mode", "async"])).unwrap().mode, Mode::Async);
+    assert_eq!(Options::parse(args(&["--mode=async", "--strategy", "naive"])).unwrap().mode,
+               Mode::Async);
+    assert_eq!(Options::parse(args(&["--mode", "async", "--strategy", "waiter"])).unwrap().strategy,
+               Kind::Waiter);
+    assert!(Options::parse(args(&["--mode", "fibers"])).is_err());
+}
+
+#[test]
+fn tui_flag() {
+    assert!(!Options::parse(args(&[])).unwrap().tui);
+    assert!(Options::parse(args(&["--tui"])).unwrap().tui);
+    assert!(Options::parse(args(&["--tui", "--clock", "virtual"])).is_err());
+}
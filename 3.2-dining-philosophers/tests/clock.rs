@@ -0,0 +1,201 @@
+extern crate dining_philosophers;
+
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use dining_philosophers::{Clock, DiscreteClock, Event, Kind, Length, PacedClock, RealClock, Report,
+                          Roster, Simulation, Strategy, Table, VirtualClock};
+use dining_philosophers::events::Memory;
+
+fn secs(secs: u64) -> Duration {
+    Duration::from_secs(secs)
+}
+
+fn simulate(roster: &Roster, kind: Kind, length: Length) -> Report {
+    let table = Arc::new(Table::with_clock(roster.len(), Arc::new(VirtualClock::new())));
+    let strategy: Arc<dyn Strategy> = Arc::from(kind.build(table.seats()));
+    let simulation = Simulation { length, eat_time: secs(1) };
+
+    simulation.run(roster.seat(), table, strategy)
+}
+
+#[test]
+fn real_clock_really_sleeps() {
+    let clock = RealClock::new();
+    clock.sleep(Duration::from_millis(20));
+    assert!(clock.now() >= Duration::from_millis(20));
+}
+
+#[test]
+fn virtual_clock_only_pretends() {
+    let clock = Arc::new(VirtualClock::new());
+    let started = Instant::now();
+
+    clock.sleep(secs(3600));
+    assert_eq!(clock.now(), secs(3600));
+    assert!(started.elapsed() < secs(1));
+
+    // Every thread keeps its own time, and only ever catches up
+    let other = clock.clone();
+    thread::spawn(move || {
+        assert_eq!(other.now(), secs(0));
+        other.catch_up(secs(10));
+        other.catch_up(secs(5));
+        assert_eq!(other.now(), secs(10));
+    }).join().unwrap();
+    assert_eq!(clock.now(), secs(3600));
+}
+
+#[test]
+fn forks_hand_the_time_over() {
+    let clock = Arc::new(VirtualClock::new());
+    let table = Arc::new(Table::with_clock(2, clock.clone()));
+
+    {
+        let _fork = table.fork(0).lock(0).unwrap();
+        clock.sleep(secs(5));
+    }
+
+    let (table, clock) = (table.clone(), clock.clone());
+    thread::spawn(move || {
+        let _fork = table.fork(0).lock(1).unwrap();
+        assert_eq!(clock.now(), secs(5));
+    }).join().unwrap();
+}
+
+#[test]
+fn thousands_of_rounds_in_no_time() {
+    let started = Instant::now();
+
+    for &kind in Kind::SAFE.iter() {
+        let report = simulate(&Roster::generated(5), kind, Length::Rounds(2000));
+
+        assert!(report.diners.iter().all(|diner| diner.meals == 2000), "{}", kind);
+        // Nobody can eat more than two at a time around five forks, and
+        // everybody's meals take 2000 seconds back to back
+        assert!(report.elapsed >= secs(5000 / 2), "{} took {:?}", kind, report.elapsed);
+        assert!(report.elapsed >= secs(2000));
+    }
+
+    assert!(started.elapsed() < secs(30));
+}
+
+#[test]
+fn meals_that_have_to_take_turns_add_up_exactly() {
+    // Two philosophers share both forks, so every meal waits for the last
+    for &kind in Kind::SAFE.iter() {
+        let report = simulate(&Roster::generated(2), kind, Length::Rounds(1000));
+        assert_eq!(report.meals(), 2000, "{}", kind);
+
+        if kind == Kind::Backoff {
+            // Backing off for a little too long wastes time between meals
+            assert!(report.elapsed >= secs(2000));
+        } else {
+            assert_eq!(report.elapsed, secs(2000), "{}", kind);
+        }
+    }
+}
+
+#[test]
+fn duration_in_virtual_time() {
+    let report = simulate(&Roster::generated(5), Kind::Waiter, Length::Duration(secs(600)));
+
+    // Nobody starts a meal after time's up, but they finish the one they're
+    // on, and might still have had to wait for forks
+    assert!(report.elapsed >= secs(600));
+    assert!(report.diners.iter().all(|diner| diner.meals > 100));
+}
+
+fn simulate_discretely(roster: &Roster, kind: Kind, length: Length) -> (Report, Vec<Event>) {
+    let memory = Arc::new(Memory::new());
+    let clock = Arc::new(DiscreteClock::new());
+    let table = Table::with_clock(roster.len(), clock.clone()).with_events(memory.clone());
+    let table = Arc::new(table.with_scheduler(clock));
+    let strategy: Arc<dyn Strategy> = Arc::from(kind.build(table.seats()));
+    let simulation = Simulation { length, eat_time: secs(1) };
+
+    (simulation.run(roster.seat(), table, strategy), memory.events())
+}
+
+#[test]
+fn discrete_clock_runs_the_same_dinner_every_time() {
+    for &kind in Kind::SAFE.iter() {
+        let (report, events) = simulate_discretely(&Roster::generated(5), kind, Length::Rounds(50));
+        assert!(report.diners.iter().all(|diner| diner.meals == 50), "{}", kind);
+
+        for _ in 0..3 {
+            let (again, same) = simulate_discretely(&Roster::generated(5), kind, Length::Rounds(50));
+            assert_eq!(again, report, "{}", kind);
+            assert!(same == events, "{} went differently the second time", kind);
+        }
+    }
+}
+
+#[test]
+fn discrete_clock_keeps_one_time_for_everybody() {
+    // Two philosophers share both forks, so every meal waits for the last,
+    // and nobody waits any longer than that
+    for &kind in Kind::SAFE.iter() {
+        let (report, events) = simulate_discretely(&Roster::generated(2), kind, Length::Rounds(100));
+        assert_eq!(report.meals(), 200, "{}", kind);
+        if kind != Kind::Backoff {
+            assert_eq!(report.elapsed, secs(200), "{}", kind);
+        }
+
+        // Events come in the order they happened, by the one clock
+        assert!(events.windows(2).all(|pair| pair[0].at <= pair[1].at), "{}", kind);
+    }
+}
+
+#[test]
+fn discrete_clock_outside_dinner() {
+    // Somebody who hasn't sat down just moves the time along
+    let clock = DiscreteClock::new();
+    clock.sleep(secs(5));
+    clock.sleep(secs(5));
+    assert_eq!(clock.now(), secs(10));
+}
+
+
+#[test]
+fn paced_clock_speeds_up() {
+    let clock = PacedClock::new();
+    clock.set_speed(10.0);
+    let started = Instant::now();
+
+    clock.sleep(Duration::from_millis(500));
+    assert!(clock.now() >= Duration::from_millis(500));
+    assert!(started.elapsed() < Duration::from_millis(400));
+}
+
+#[test]
+fn paced_clock_stands_still_while_paused() {
+    let clock = Arc::new(PacedClock::new());
+    clock.pause();
+    assert!(clock.is_paused());
+    let paused_at = clock.now();
+    thread::sleep(Duration::from_millis(20));
+    assert_eq!(clock.now(), paused_at);
+
+    // A sleeper waits until the clock starts again
+    let sleeper = clock.clone();
+    let handle = thread::spawn(move || sleeper.sleep(Duration::from_millis(10)));
+    thread::sleep(Duration::from_millis(50));
+    assert_eq!(clock.now(), paused_at);
+    clock.resume();
+    handle.join().unwrap();
+    assert!(clock.now() >= paused_at + Duration::from_millis(10));
+}
+
+#[test]
+fn paced_clock_steps() {
+    let clock = PacedClock::new();
+    clock.pause();
+    let paused_at = clock.now();
+
+    clock.step(Duration::from_millis(30));
+    thread::sleep(Duration::from_millis(80));
+    assert_eq!(clock.now(), paused_at + Duration::from_millis(30));
+    assert!(clock.is_paused());
+}
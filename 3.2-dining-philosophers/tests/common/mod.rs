@@ -0,0 +1,14 @@
+//! Helpers shared by more than one test file.
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+
+/// Writes `contents` to a file called `name` somewhere nobody else will
+/// trip over it.
+pub fn temp_file(name: &str, contents: &str) -> PathBuf {
+    let path = env::temp_dir().join(format!("dining_philosophers_{}_{}", process::id(), name));
+    fs::write(&path, contents).unwrap();
+    path
+}
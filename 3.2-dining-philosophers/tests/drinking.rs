@@ -0,0 +1,170 @@
+extern crate dining_philosophers;
+extern crate serde_json;
+
+mod common;
+
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use dining_philosophers::{Kind, Length, Report, Simulation, Strategy, Table, VirtualClock, events};
+use dining_philosophers::drinking::{self, Agent, Error, Graph};
+use dining_philosophers::events::Memory;
+
+use common::temp_file;
+
+fn bank() -> Graph {
+    Graph {
+        bottles: vec!["accounts".to_string(), "ledger".to_string(), "audit".to_string()],
+        agents: vec![
+            Agent::new("transfer", &[&["accounts", "ledger"], &["ledger", "audit"]]),
+            Agent::new("report", &[&["audit", "accounts"]]),
+            Agent::new("reconcile", &[&["ledger", "audit", "accounts"]]),
+        ],
+    }
+}
+
+fn drink(graph: &Graph, kind: Kind, rounds: u32) -> Report {
+    let table = Arc::new(Table::cellar(graph.bottles.len(), Arc::new(VirtualClock::new())));
+    let strategy: Arc<dyn Strategy> = Arc::from(drinking::build(kind, graph));
+    let simulation = Simulation { length: Length::Rounds(rounds), eat_time: Duration::from_secs(1) };
+
+    drinking::run(&simulation, graph.seat(), table, strategy)
+}
+
+#[test]
+fn loads_toml() {
+    let path = temp_file("graph.toml", r#"
+        bottles = ["accounts", "ledger", "audit"]
+
+        [[agent]]
+        name = "transfer"
+        sessions = [["accounts", "ledger"], ["ledger", "audit"]]
+        think_time_ms = 5
+
+        [[agent]]
+        name = "report"
+        sessions = [["audit", "accounts"]]
+    "#);
+
+    let graph = Graph::load(&path).unwrap();
+    assert_eq!(graph.agents[0].think_time_ms, 5);
+
+    let drinkers = graph.seat();
+    assert_eq!(drinkers[0].sessions, vec![vec![0, 1], vec![1, 2]]);
+    assert_eq!(drinkers[0].think_time, Duration::from_millis(5));
+    assert_eq!((drinkers[1].seat, drinkers[1].sessions.clone()), (1, vec![vec![2, 0]]));
+}
+
+#[test]
+fn loads_json() {
+    let path = temp_file("graph.json", r#"{
+        "bottles": ["a", "b"],
+        "agent": [{"name": "one", "sessions": [["a"], ["a", "b"]]}, {"name": "two", "sessions": [["b"]]}]
+    }"#);
+
+    let graph = Graph::load(&path).unwrap();
+    assert_eq!(graph.seat()[0].sessions, vec![vec![0], vec![0, 1]]);
+}
+
+#[test]
+fn the_example_is_the_bank() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("examples/bank.toml");
+    let mut graph = Graph::load(&path).unwrap();
+    for agent in &mut graph.agents {
+        agent.think_time_ms = 0;
+    }
+    assert_eq!(graph, bank());
+}
+
+#[test]
+fn rejects_bad_graphs() {
+    let check = |graph: Graph| graph.validate().unwrap_err().to_string();
+    let with = |agent: Agent| Graph { agents: vec![agent], ..bank() };
+
+    assert_eq!(check(Graph { agents: vec![], ..bank() }), "the graph doesn't have any agents");
+    assert_eq!(check(with(Agent::new("idle", &[]))), "idle doesn't have any sessions");
+    assert_eq!(check(with(Agent::new("sober", &[&[]]))), "sober has a session without any bottles");
+    assert_eq!(check(with(Agent::new("thirsty", &[&["wine"]]))),
+               "thirsty wants wine, which isn't one of the bottles");
+    assert_eq!(check(with(Agent::new("greedy", &[&["audit", "audit"]]))),
+               "greedy wants audit twice in the same session");
+    let mut doubled = bank();
+    doubled.bottles.push("ledger".to_string());
+    assert_eq!(check(doubled), "there's more than one ledger bottle");
+
+    let typo = temp_file("typo.toml", "bottles = []\n[[agent]]\nname = \"x\"\nsesions = []\n");
+    match Graph::load(&typo) {
+        Err(Error::Toml(_)) => {}
+        other => panic!("expected a TOML error, got {:?}", other),
+    }
+}
+
+#[test]
+fn a_ring_is_the_dining_philosophers() {
+    let report = drink(&Graph::ring(5), Kind::ResourceOrdering, 3);
+
+    assert_eq!(report.diners[4].name, "Philosopher 5");
+    assert!(report.diners.iter().all(|diner| diner.meals == 3));
+    // Five people, two forks each, so two eat at a time at best
+    assert!(report.elapsed >= Duration::from_secs(3 * 5 / 2));
+}
+
+#[test]
+fn safe_strategies_get_through_every_session() {
+    for &kind in &Kind::SAFE {
+        let report = drink(&bank(), kind, 4);
+
+        let meals: Vec<u32> = report.diners.iter().map(|diner| diner.meals).collect();
+        assert_eq!(meals, vec![8, 4, 4], "{}", kind);
+        // Every session shares a bottle with every other, so nothing ever
+        // overlaps
+        assert!(report.elapsed >= Duration::from_secs(16), "{}", kind);
+    }
+}
+
+#[test]
+fn sessions_that_share_nothing_go_at_once() {
+    let graph = Graph {
+        bottles: vec!["a".to_string(), "b".to_string()],
+        agents: vec![Agent::new("one", &[&["a"]]), Agent::new("two", &[&["b"]])],
+    };
+
+    for &kind in &Kind::ALL {
+        assert_eq!(drink(&graph, kind, 3).elapsed, Duration::from_secs(3), "{}", kind);
+    }
+}
+
+#[test]
+fn bottles_are_taken_and_returned() {
+    let graph = bank();
+    let memory = Arc::new(Memory::new());
+    let table = Table::cellar(graph.bottles.len(), Arc::new(VirtualClock::new()))
+        .with_events(memory.clone());
+    let strategy: Arc<dyn Strategy> = Arc::from(drinking::build(Kind::ResourceOrdering, &graph));
+    let simulation = Simulation { length: Length::Rounds(1), eat_time: Duration::from_secs(1) };
+    drinking::run(&simulation, graph.seat(), Arc::new(table), strategy);
+
+    let mut taken: Vec<(usize, usize)> = Vec::new();
+    for event in memory.events() {
+        match event.kind {
+            events::Kind::Took { bottle } => taken.push((event.philosopher, bottle)),
+            events::Kind::Returned { bottle } => assert!(taken.contains(&(event.philosopher, bottle))),
+            events::Kind::PickedUp { .. } | events::Kind::PutDown { .. } => {
+                panic!("a bottle isn't a fork: {:?}", event)
+            }
+            _ => {}
+        }
+    }
+    // Reconcile takes all three, the others two each, twice for transfer
+    taken.sort();
+    assert_eq!(taken, vec![(0, 0), (0, 1), (0, 1), (0, 2), (1, 0), (1, 2), (2, 0), (2, 1), (2, 2)]);
+
+    let took = events::Event {
+        at: Duration::from_millis(1),
+        philosopher: 2,
+        kind: events::Kind::Took { bottle: 1 },
+    };
+    assert_eq!(took.to_json(&[]),
+               serde_json::json!({"at_us": 1000, "philosopher": 2, "event": "took", "bottle": 1}));
+}
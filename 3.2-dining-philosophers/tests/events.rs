@@ -0,0 +1,211 @@
+extern crate dining_philosophers;
+extern crate serde_json;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use dining_philosophers::{Event, EventSink, Kind, Length, Roster, Simulation, Strategy, Supervisor,
+                          Table, VirtualClock};
+use dining_philosophers::events::{self, ChromeTrace, JsonLines, Memory, Side};
+use serde_json::Value;
+
+/// Two rounds of dinner on a virtual clock, telling `sink` everything.
+fn dine(roster: &Roster, kind: Kind, sink: Arc<dyn EventSink>) {
+    let table = Table::with_clock(roster.len(), Arc::new(VirtualClock::new())).with_events(sink);
+    let table = Arc::new(table);
+    let strategy: Arc<dyn Strategy> = Arc::from(kind.build(table.seats()));
+    let simulation = Simulation { length: Length::Rounds(2), eat_time: Duration::from_secs(1) };
+
+    simulation.run(roster.seat(), table, strategy);
+}
+
+fn recorded(roster: &Roster, kind: Kind) -> Vec<Event> {
+    let memory = Arc::new(Memory::new());
+    dine(roster, kind, memory.clone());
+    memory.events()
+}
+
+/// What `seat` did, in order.
+fn by(events: &[Event], seat: usize) -> Vec<events::Kind> {
+    events.iter().filter(|event| event.philosopher == seat).map(|event| event.kind).collect()
+}
+
+fn names(roster: &Roster) -> Vec<String> {
+    roster.guests.iter().map(|guest| guest.name.clone()).collect()
+}
+
+#[test]
+fn every_meal_goes_think_hungry_forks_eat_done() {
+    let roster = Roster::default();
+    for &kind in &Kind::SAFE {
+        let events = recorded(&roster, kind);
+
+        for seat in 0..roster.len() {
+            let mut held = HashMap::new();
+            let mut meals = Vec::new();
+            for event in by(&events, seat) {
+                match event {
+                    events::Kind::PickedUp { side, .. } => *held.entry(side).or_insert(0) += 1,
+                    events::Kind::PutDown { side, .. } => *held.entry(side).or_insert(0) -= 1,
+                    events::Kind::Eating => {
+                        assert_eq!((held[&Side::Left], held[&Side::Right]), (1, 1),
+                                   "{} let seat {} eat without both forks", kind, seat);
+                        meals.push(event);
+                    }
+                    _ => meals.push(event),
+                }
+            }
+            assert_eq!(held.values().sum::<i32>(), 0, "{} left seat {} holding a fork", kind, seat);
+
+            use dining_philosophers::events::Kind::*;
+            assert_eq!(meals, vec![Thinking, Hungry, Eating, Done, Thinking, Hungry, Eating, Done],
+                       "{}", kind);
+        }
+    }
+}
+
+#[test]
+fn every_philosophers_events_are_in_time_order() {
+    let roster = Roster::generated(7);
+    for &kind in &Kind::SAFE {
+        let events = recorded(&roster, kind);
+        for seat in 0..roster.len() {
+            let times: Vec<Duration> = events.iter()
+                .filter(|event| event.philosopher == seat)
+                .map(|event| event.at)
+                .collect();
+            assert!(times.windows(2).all(|pair| pair[0] <= pair[1]), "{}", kind);
+        }
+    }
+}
+
+#[test]
+fn forks_know_which_hand_they_are_in() {
+    // With ordering, the last philosopher reaches for fork 0 first, which is
+    // on their right
+    let roster = Roster::default();
+    let events = recorded(&roster, Kind::ResourceOrdering);
+
+    let last = by(&events, 4);
+    let forks: Vec<_> = last.iter().filter_map(|kind| match *kind {
+        events::Kind::PickedUp { side, fork } => Some((side, fork)),
+        _ => None,
+    }).collect();
+    assert_eq!(&forks[..2], &[(Side::Right, 0), (Side::Left, 4)]);
+
+    let first = by(&events, 0);
+    assert!(first.contains(&events::Kind::PickedUp { side: Side::Left, fork: 0 }));
+    assert!(first.contains(&events::Kind::PickedUp { side: Side::Right, fork: 1 }));
+}
+
+#[test]
+fn eating_takes_as_long_as_a_meal() {
+    let roster = Roster::default();
+    let events = recorded(&roster, Kind::Waiter);
+
+    for seat in 0..roster.len() {
+        let started: Vec<Duration> = events.iter()
+            .filter(|event| event.philosopher == seat && event.kind == events::Kind::Eating)
+            .map(|event| event.at)
+            .collect();
+        let finished: Vec<Duration> = events.iter()
+            .filter(|event| event.philosopher == seat && event.kind == events::Kind::Done)
+            .map(|event| event.at)
+            .collect();
+        for (start, finish) in started.iter().zip(&finished) {
+            assert_eq!(*finish - *start, Duration::from_secs(1));
+        }
+    }
+}
+
+#[test]
+fn json_lines_has_an_object_per_event() {
+    let roster = Roster::default();
+    let memory = Arc::new(Memory::new());
+    let lines = Arc::new(JsonLines::new(Vec::new(), names(&roster)));
+    dine(&roster, Kind::ResourceOrdering,
+         Arc::new(events::Tee::new(vec![memory.clone(), lines.clone()])));
+    lines.finish().unwrap();
+
+    let output = String::from_utf8(Arc::try_unwrap(lines).unwrap().into_inner()).unwrap();
+    let objects: Vec<Value> = output.lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(objects.len(), memory.events().len());
+
+    for (object, event) in objects.iter().zip(memory.events()) {
+        assert_eq!(object["philosopher"], event.philosopher);
+        assert_eq!(object["name"], roster.guests[event.philosopher].name.as_str());
+        assert_eq!(object["event"], event.kind.name());
+        assert_eq!(object["at_us"].as_u64().unwrap(), event.at.as_secs() * 1_000_000);
+    }
+
+    let picked = objects.iter().find(|object| object["event"] == "picked_up").unwrap();
+    assert!(picked["side"] == "left" || picked["side"] == "right");
+    assert!(picked["fork"].is_u64());
+}
+
+#[test]
+fn chrome_trace_spans_match_up() {
+    let roster = Roster::default();
+    let trace = Arc::new(ChromeTrace::new(Vec::new(), &names(&roster)));
+    dine(&roster, Kind::ChandyMisra, trace.clone());
+    trace.finish().unwrap();
+
+    let output = Arc::try_unwrap(trace).unwrap().into_inner();
+    let trace: Vec<Value> = serde_json::from_slice(&output).unwrap();
+
+    // Nothing but real events, not even an empty one at the end
+    assert!(trace.iter().all(|event| event["ph"].is_string()));
+    let rows: Vec<&Value> = trace.iter().filter(|event| event["ph"] == "M").collect();
+    assert_eq!(rows.len(), roster.len());
+    assert_eq!(rows[2]["args"]["name"], roster.guests[2].name.as_str());
+
+    // Thinking, hungry and eating, twice over, for everybody
+    assert_eq!(spans(&trace), 3 * 2 * roster.len());
+}
+
+#[test]
+fn chrome_trace_ends_meals_that_were_dropped() {
+    let roster = Roster::default();
+    let mut philosophers = roster.seat();
+    philosophers[2].fumbles.push(1);
+    let trace = Arc::new(ChromeTrace::new(Vec::new(), &names(&roster)));
+    let table = Table::with_clock(roster.len(), Arc::new(VirtualClock::new()))
+        .with_events(trace.clone())
+        .with_recovery();
+    let strategy: Arc<dyn Strategy> = Arc::from(Kind::Waiter.build(table.seats()));
+    let simulation = Simulation { length: Length::Rounds(2), eat_time: Duration::from_secs(1) };
+    Supervisor::default().run(&simulation, philosophers, Arc::new(table), strategy);
+    trace.finish().unwrap();
+
+    let output = Arc::try_unwrap(trace).unwrap().into_inner();
+    let trace: Vec<Value> = serde_json::from_slice(&output).unwrap();
+    spans(&trace);
+    assert!(trace.iter().any(|event| event["name"] == "dropped their fork" && event["tid"] == 2));
+}
+
+/// Checks every span that begins ends, on the same row, in order, and says
+/// how many there were.
+fn spans(trace: &[Value]) -> usize {
+    let mut open: HashMap<u64, Vec<String>> = HashMap::new();
+    let mut spans = 0;
+    for event in trace {
+        let row = match event["tid"].as_u64() {
+            Some(row) => row,
+            None => continue,
+        };
+        let name = event["name"].as_str().unwrap().to_string();
+        match event["ph"].as_str().unwrap() {
+            "B" => open.entry(row).or_default().push(name),
+            "E" => {
+                assert_eq!(open.get_mut(&row).and_then(|names| names.pop()), Some(name));
+                spans += 1;
+            }
+            _ => {}
+        }
+    }
+    assert!(open.values().all(|names| names.is_empty()));
+    spans
+}
@@ -0,0 +1,120 @@
+extern crate dining_philosophers;
+
+use dining_philosophers::{Kind, Philosopher, Roster, Strategy, Table};
+use dining_philosophers::model::{Model, Move, Problem, Step};
+use dining_philosophers::strategy::{ChandyMisra, ResourceOrdering, Sommelier, Waiter};
+
+/// Checks every safe strategy with `seats` philosophers eating `meals`
+/// times, with up to `preemptions` preemptions.
+fn never_deadlocks(seats: usize, meals: u32, preemptions: usize) {
+    let philosophers = Roster::generated(seats).seat();
+    for &kind in &Kind::SAFE {
+        let mut model = Model::new(kind, &philosophers, meals);
+        model.preemptions = preemptions;
+        match model.check() {
+            Ok(runs) => assert!(runs > 1),
+            Err(counterexample) => {
+                panic!("{} with {} seats and {} meals: {}", kind, seats, meals, counterexample)
+            }
+        }
+    }
+}
+
+#[test]
+fn safe_strategies_never_deadlock_at_three_seats() {
+    // Every schedule gets a lot longer with every meal, so the second one
+    // only gets one preemption
+    never_deadlocks(3, 1, 2);
+    never_deadlocks(3, 2, 1);
+}
+
+#[test]
+fn safe_strategies_never_deadlock_at_four_seats() {
+    never_deadlocks(4, 1, 1);
+}
+
+#[test]
+fn cellar_strategies_never_deadlock_at_dinner_either() {
+    // A sommelier hands over both forks at once, and Chandy and Misra laid
+    // for a cellar share a fork with both neighbours, the same as round
+    // the table
+    let philosophers = Roster::generated(3).seat();
+    let forks: Vec<Vec<usize>> = philosophers.iter().map(|p| vec![p.left, p.right]).collect();
+
+    assert!(Model::with(&philosophers, 1, || Box::new(Sommelier::new(3))).check().is_ok());
+    assert!(Model::with(&philosophers, 1, move || Box::new(ChandyMisra::sharing(&forks))).check().is_ok());
+}
+
+#[test]
+fn naive_deadlocks_when_everybody_takes_their_left_fork() {
+    for &seats in &[3, 4] {
+        let philosophers = Roster::generated(seats).seat();
+        let counterexample = Model::new(Kind::Naive, &philosophers, 1).check().unwrap_err();
+
+        assert_eq!(counterexample.problem, Problem::Deadlock);
+        let everybody_left: Vec<Move> = (0..seats)
+            .map(|seat| Move { philosopher: seat, step: Step::PickUp(seat) })
+            .collect();
+        assert_eq!(counterexample.trace, everybody_left);
+    }
+}
+
+/// Picks up forks in order, except that seat 1 forgets the second one.
+#[derive(Debug)]
+struct Forgetful;
+
+impl Strategy for Forgetful {
+    fn name(&self) -> &'static str {
+        "forgetful"
+    }
+
+    fn dine(&self, seat: usize, forks: &[usize], table: &Table, eat: &mut dyn FnMut()) {
+        if seat != 1 {
+            return ResourceOrdering.dine(seat, forks, table, eat);
+        }
+        let _first = table.fork(*forks.iter().min().unwrap()).lock(1).unwrap();
+        eat();
+    }
+
+    fn pick_up<'a>(&'a self, philosopher: &'a Philosopher, table: &'a Table)
+                   -> dining_philosophers::strategy::PickingUp<'a> {
+        ResourceOrdering.pick_up(philosopher, table)
+    }
+}
+
+#[test]
+fn catches_eating_without_both_forks() {
+    let philosophers = Roster::generated(3).seat();
+    let counterexample = Model::with(&philosophers, 1, || Box::new(Forgetful)).check().unwrap_err();
+
+    assert_eq!(counterexample.problem, Problem::EatingWithoutForks(1));
+    assert_eq!(counterexample.trace.last(), Some(&Move { philosopher: 1, step: Step::Eat }));
+    assert_eq!(counterexample.trace.iter().filter(|m| m.philosopher == 1).count(), 2);
+}
+
+#[test]
+fn waiter_needs_to_hold_somebody_back() {
+    // With a permit for everybody, the waiter is no help at all
+    let philosophers = Roster::generated(3).seat();
+    let counterexample = Model::with(&philosophers, 1, || Box::new(Waiter::with_permits(3)))
+        .check().unwrap_err();
+
+    assert_eq!(counterexample.problem, Problem::Deadlock);
+    // Everybody has their left fork, in whatever order they got it
+    assert_eq!(counterexample.trace.len(), 3);
+    for step in &counterexample.trace {
+        assert_eq!(step.step, Step::PickUp(step.philosopher));
+    }
+    assert!(Model::with(&philosophers, 1, || Box::new(Waiter::with_permits(2))).check().is_ok());
+}
+
+#[test]
+fn counterexamples_read_as_a_schedule() {
+    let philosophers = Roster::generated(3).seat();
+    let counterexample = Model::new(Kind::Naive, &philosophers, 1).check().unwrap_err();
+
+    assert_eq!(counterexample.to_string(), "deadlock after 3 steps\n    \
+                                            seat 0 picks up fork 0\n    \
+                                            seat 1 picks up fork 1\n    \
+                                            seat 2 picks up fork 2");
+}
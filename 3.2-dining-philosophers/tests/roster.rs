@@ -0,0 +1,80 @@
+extern crate dining_philosophers;
+
+mod common;
+
+use std::env;
+use std::time::Duration;
+
+use dining_philosophers::{Guest, Roster};
+use dining_philosophers::roster::Error;
+
+use common::temp_file;
+
+#[test]
+fn loads_toml() {
+    let path = temp_file("roster.toml", r#"
+        [[philosopher]]
+        name = "Mikael"
+        appetite = 3
+        think_time_ms = 250
+
+        [[philosopher]]
+        name = "Jesper"
+    "#);
+
+    let roster = Roster::load(&path).unwrap();
+    assert_eq!(roster.guests, vec![
+        Guest { name: "Mikael".to_string(), appetite: 3, think_time_ms: 250 },
+        Guest::new("Jesper"),
+    ]);
+
+    let philosophers = roster.seat();
+    assert_eq!((philosophers[0].left, philosophers[0].right), (0, 1));
+    assert_eq!((philosophers[1].left, philosophers[1].right), (1, 0));
+    assert_eq!(philosophers[0].appetite, 3);
+    assert_eq!(philosophers[0].think_time, Duration::from_millis(250));
+}
+
+#[test]
+fn loads_json() {
+    let path = temp_file("roster.json", r#"{"philosopher": [
+        {"name": "Mikael"}, {"name": "Jesper", "appetite": 2}, {"name": "Glenn"}
+    ]}"#);
+
+    let roster = Roster::load(&path).unwrap();
+    assert_eq!(roster.len(), 3);
+    assert_eq!(roster.guests[1].appetite, 2);
+}
+
+#[test]
+fn rejects_bad_rosters() {
+    let lonely = temp_file("lonely.toml", "[[philosopher]]\nname = \"Mikael\"\n");
+    let typo = temp_file("typo.json", r#"{"philosopher": [{"name": "Mikael", "apetite": 2}]}"#);
+    let yaml = temp_file("roster.yaml", "");
+
+    match Roster::load(&lonely) {
+        Err(Error::TooFew(1)) => {}
+        other => panic!("expected TooFew, got {:?}", other),
+    }
+    match Roster::load(&typo) {
+        Err(Error::Json(_)) => {}
+        other => panic!("expected a JSON error, got {:?}", other),
+    }
+    match Roster::load(&yaml) {
+        Err(Error::UnknownFormat(_)) => {}
+        other => panic!("expected UnknownFormat, got {:?}", other),
+    }
+    match Roster::load(&env::temp_dir().join("no_such_roster.toml")) {
+        Err(Error::Io(..)) => {}
+        other => panic!("expected an IO error, got {:?}", other),
+    }
+}
+
+#[test]
+fn generated_rosters_seat_everybody() {
+    let philosophers = Roster::generated(300).seat();
+
+    assert_eq!(philosophers.len(), 300);
+    assert_eq!(philosophers[0].name, "Philosopher 1");
+    assert_eq!((philosophers[299].left, philosophers[299].right), (299, 0));
+}
@@ -0,0 +1,119 @@
+extern crate dining_philosophers;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use dining_philosophers::{Kind, Length, Report, Roster, Simulation, Strategy, Table,
+                          VirtualClock};
+use dining_philosophers::simulation::Diner;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+fn simulate(roster: &Roster, kind: Kind, length: Length) -> Report {
+    let table = Arc::new(Table::new(roster.len()));
+    let strategy: Arc<dyn Strategy> = Arc::from(kind.build(table.seats()));
+    let simulation = Simulation { length, eat_time: ms(1) };
+
+    simulation.run(roster.seat(), table, strategy)
+}
+
+fn diner(name: &str, meals: u32, max_wait: u64) -> Diner {
+    Diner {
+        name: name.to_string(),
+        meals,
+        total_wait: ms(max_wait) * meals,
+        max_wait: ms(max_wait),
+    }
+}
+
+#[test]
+fn rounds_feed_everybody_their_appetite() {
+    let mut roster = Roster::generated(5);
+    roster.guests[2].appetite = 3;
+
+    let report = simulate(&roster, Kind::Waiter, Length::Rounds(4));
+
+    let meals: Vec<u32> = report.diners.iter().map(|diner| diner.meals).collect();
+    assert_eq!(meals, vec![4, 4, 12, 4, 4]);
+    assert_eq!(report.diners[2].name, "Philosopher 3");
+}
+
+#[test]
+fn duration_stops_when_times_up() {
+    let report = simulate(&Roster::generated(5), Kind::ChandyMisra, Length::Duration(ms(100)));
+
+    assert!(report.diners.iter().all(|diner| diner.meals > 0));
+    assert!(report.elapsed >= ms(100));
+    assert!(report.elapsed < ms(5000));
+}
+
+#[test]
+fn serial_dinner_is_one_meal_after_another() {
+    let mut roster = Roster::generated(5);
+    roster.guests[1].appetite = 2;
+    roster.guests[3].think_time_ms = 500;
+    let simulation = Simulation { length: Length::Rounds(3), eat_time: ms(1000) };
+
+    // Even the naive strategy can't deadlock on its own
+    for &kind in &Kind::ALL {
+        let table = Table::with_clock(roster.len(), Arc::new(VirtualClock::new()));
+        let strategy = kind.build(table.seats());
+        let report = simulation.run_serial(roster.seat(), &table, &*strategy);
+
+        let meals: Vec<u32> = report.diners.iter().map(|diner| diner.meals).collect();
+        assert_eq!(meals, vec![3, 6, 3, 3, 3], "{}", kind);
+        if kind == Kind::Naive {
+            // Nobody else wants the forks, but it still takes a moment to
+            // reach for the second one
+            assert!(report.diners.iter().all(|diner| diner.max_wait == ms(10)));
+        } else {
+            assert!(report.diners.iter().all(|diner| diner.max_wait == ms(0)), "{}", kind);
+            // 18 meals and three lots of thinking, and nothing at the same time
+            assert_eq!(report.elapsed, ms(18 * 1000 + 3 * 500), "{}", kind);
+        }
+    }
+}
+
+#[test]
+fn serial_dinner_goes_round_until_times_up() {
+    let roster = Roster::generated(4);
+    let table = Table::with_clock(roster.len(), Arc::new(VirtualClock::new()));
+    let strategy = Kind::ResourceOrdering.build(table.seats());
+    let simulation = Simulation { length: Length::Duration(ms(10_000)), eat_time: ms(1000) };
+
+    let report = simulation.run_serial(roster.seat(), &table, &*strategy);
+    let meals: Vec<u32> = report.diners.iter().map(|diner| diner.meals).collect();
+    assert_eq!(meals, vec![3, 3, 2, 2]);
+    assert_eq!(report.elapsed, ms(10_000));
+}
+
+#[test]
+fn fairness_is_jains_index() {
+    let report = |meals: &[u32]| {
+        Report::new(meals.iter().map(|&meals| diner("Mikael", meals, 0)).collect(), ms(0))
+    };
+
+    assert_eq!(report(&[3, 3, 3, 3]).fairness(), 1.0);
+    assert_eq!(report(&[4, 0, 0, 0]).fairness(), 0.25);
+    assert_eq!(report(&[0, 0]).fairness(), 1.0);
+    assert!((report(&[1, 2, 3]).fairness() - 36.0 / 42.0).abs() < 1e-9);
+}
+
+#[test]
+fn flags_starvation() {
+    let report = Report::new(
+        vec![diner("Mikael", 3, 10), diner("Jesper", 1, 900), diner("Glenn", 2, 50)],
+        ms(1000),
+    );
+
+    let starving: Vec<&str> = report.starving(ms(100)).iter().map(|diner| diner.name.as_str()).collect();
+    assert_eq!(starving, vec!["Jesper"]);
+    assert_eq!(report.hungriest().unwrap().name, "Jesper");
+
+    let lines = report.lines(ms(100));
+    assert!(lines[1].starts_with("Jesper") && lines[1].ends_with("STARVING"));
+    assert!(!lines[0].ends_with("STARVING"));
+    assert_eq!(lines.last().unwrap(), "1 of 3 went hungry for longer than 0.100s");
+}
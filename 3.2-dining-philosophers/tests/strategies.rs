@@ -0,0 +1,95 @@
+extern crate dining_philosophers;
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use dining_philosophers::{Kind, Philosopher, Roster, Strategy, Table};
+
+const NAMES: [&str; 5] = ["Mikael", "Jesper", "Glenn", "Anders", "Niclas"];
+
+/// Has everybody at the table eat `meals` times with `kind`, checking that
+/// neighbours never eat at once. Returns how many meals were eaten.
+fn feast(kind: Kind, roster: &Roster, meals: usize) -> usize {
+    let table = Arc::new(Table::new(roster.len()));
+    let strategy: Arc<dyn Strategy> = Arc::from(kind.build(table.seats()));
+    let eating: Arc<Vec<AtomicBool>> = Arc::new((0..roster.len()).map(|_| AtomicBool::new(false)).collect());
+    let eaten = Arc::new(AtomicUsize::new(0));
+    let seats = roster.len();
+
+    let handles: Vec<_> = roster.seat().into_iter().map(|p| {
+        let (table, strategy) = (table.clone(), strategy.clone());
+        let (eating, eaten) = (eating.clone(), eaten.clone());
+
+        thread::spawn(move || {
+            let seat = p.seat_number();
+            for _ in 0..meals {
+                p.eat(&table, &*strategy, || {
+                    assert!(!eating[(seat + seats - 1) % seats].load(Ordering::SeqCst));
+                    assert!(!eating[(seat + 1) % seats].load(Ordering::SeqCst));
+                    eating[seat].store(true, Ordering::SeqCst);
+                    thread::yield_now();
+                    eating[seat].store(false, Ordering::SeqCst);
+                    eaten.fetch_add(1, Ordering::SeqCst);
+                });
+            }
+        })
+    }).collect();
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    eaten.load(Ordering::SeqCst)
+}
+
+/// Runs `feast` on another thread, so a deadlock fails the test instead of
+/// hanging it.
+fn feast_within(kind: Kind, roster: Roster, meals: usize) -> usize {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || sender.send(feast(kind, &roster, meals)).unwrap());
+
+    receiver.recv_timeout(Duration::from_secs(20))
+        .unwrap_or_else(|_| panic!("{} deadlocked", kind))
+}
+
+#[test]
+fn every_strategy_feeds_everyone() {
+    for &kind in Kind::SAFE.iter() {
+        assert_eq!(feast_within(kind, Roster::from_names(&NAMES), 200), NAMES.len() * 200,
+                   "{}", kind);
+    }
+}
+
+#[test]
+fn every_strategy_works_for_two() {
+    for &kind in Kind::SAFE.iter() {
+        assert_eq!(feast_within(kind, Roster::from_names(&["Mikael", "Jesper"]), 200), 400,
+                   "{}", kind);
+    }
+}
+
+#[test]
+fn every_strategy_scales_to_hundreds_of_seats() {
+    for &kind in Kind::SAFE.iter() {
+        assert_eq!(feast_within(kind, Roster::generated(300), 10), 3000, "{}", kind);
+    }
+}
+
+#[test]
+fn seating_wraps_around() {
+    let philosophers = Roster::from_names(&NAMES).seat();
+
+    assert_eq!(philosophers[0], Philosopher::new("Mikael", 0, 1));
+    assert_eq!(philosophers[4], Philosopher::new("Niclas", 4, 0));
+}
+
+#[test]
+fn strategies_by_name() {
+    for &kind in Kind::ALL.iter() {
+        assert_eq!(kind.name().parse(), Ok(kind));
+        assert_eq!(kind.build(5).name(), kind.name());
+    }
+    assert!("polite".parse::<Kind>().is_err());
+}
@@ -0,0 +1,92 @@
+extern crate dining_philosophers;
+
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use dining_philosophers::{Fork, Kind, Length, Report, Roster, Simulation, Strategy, Supervisor,
+                          Table, VirtualClock};
+
+fn supervise(kind: Kind, fumbles: &[(usize, u32)], rounds: u32) -> Report {
+    let roster = Roster::generated(5);
+    let mut philosophers = roster.seat();
+    for &(seat, meal) in fumbles {
+        philosophers[seat].fumbles.push(meal);
+    }
+
+    let table = Table::with_clock(roster.len(), Arc::new(VirtualClock::new())).with_recovery();
+    let strategy: Arc<dyn Strategy> = Arc::from(kind.build(table.seats()));
+    let simulation = Simulation { length: Length::Rounds(rounds), eat_time: Duration::from_secs(1) };
+
+    Supervisor::default().run(&simulation, philosophers, Arc::new(table), strategy)
+}
+
+#[test]
+fn recovering_forks_get_cleaned_off() {
+    let fork = Arc::new(Fork::recovering());
+
+    let poisoner = fork.clone();
+    let _ = thread::spawn(move || {
+        let _guard = poisoner.lock(0).unwrap();
+        panic!("dropped the fork");
+    }).join();
+
+    assert!(fork.lock(1).is_ok());
+    assert_eq!(fork.recoveries(), 1);
+    assert!(!fork.recover());
+    assert_eq!(fork.recoveries(), 1);
+}
+
+#[test]
+fn everybody_else_finishes_dinner() {
+    for &kind in &Kind::SAFE {
+        let report = supervise(kind, &[(2, 2)], 3);
+
+        let meals: Vec<u32> = report.diners.iter().map(|diner| diner.meals).collect();
+        assert_eq!(meals, vec![3, 3, 2, 3, 3], "{}", kind);
+        assert_eq!(report.failures.len(), 1, "{}", kind);
+        assert_eq!(report.lost(), 1);
+
+        let failure = &report.failures[0];
+        assert_eq!((failure.seat, failure.meal, failure.gave_up), (2, 2, false));
+        assert_eq!(failure.message, "Philosopher 3 dropped their fork");
+
+        // Both the forks they were holding, once each
+        assert_eq!(report.recovered, vec![0, 0, 1, 1, 0], "{}", kind);
+    }
+}
+
+#[test]
+fn gives_up_on_anybody_who_keeps_panicking() {
+    let fumbles: Vec<(usize, u32)> = (1..6).map(|meal| (4, meal)).collect();
+    let report = supervise(Kind::Waiter, &fumbles, 5);
+
+    assert_eq!(report.diners[4].meals, 0);
+    assert_eq!(report.failures.len(), 4);
+    assert!(report.failures.iter().take(3).all(|failure| !failure.gave_up && failure.lost == 1));
+    assert!(report.failures[3].gave_up);
+    // The one they were having, and the one they never got to
+    assert_eq!(report.failures[3].lost, 2);
+    assert_eq!(report.lost(), 5);
+    assert!(report.diners.iter().take(4).all(|diner| diner.meals == 5));
+}
+
+#[test]
+fn the_report_says_who_failed() {
+    let report = supervise(Kind::ResourceOrdering, &[(0, 1)], 2);
+    let lines = report.lines(Duration::from_secs(60));
+
+    assert!(lines.contains(&"Philosopher 1 (seat 0) panicked during meal 1: \
+                             Philosopher 1 dropped their fork".to_string()));
+    assert!(lines.contains(&"1 meals lost to 1 failures".to_string()));
+    assert!(lines.contains(&"Fork 0 was poisoned and cleaned off once".to_string()));
+    assert!(lines.contains(&"Fork 1 was poisoned and cleaned off once".to_string()));
+}
+
+#[test]
+#[should_panic(expected = "with_recovery")]
+fn needs_a_recovering_table() {
+    let roster = Roster::generated(3);
+    let strategy: Arc<dyn Strategy> = Arc::from(Kind::Waiter.build(3));
+    Supervisor::default().run(&Simulation::default(), roster.seat(), Arc::new(Table::new(3)), strategy);
+}
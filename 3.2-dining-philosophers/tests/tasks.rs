@@ -0,0 +1,231 @@
+extern crate dining_philosophers;
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use dining_philosophers::{Clock, Kind, Length, RealClock, Report, Roster, Simulation, Table,
+                          VirtualClock, tasks};
+use dining_philosophers::events::{self, Memory};
+use dining_philosophers::executor::{self, Executor, Sleep};
+use dining_philosophers::strategy::Sommelier;
+use dining_philosophers::watchdog::Wait;
+
+fn secs(secs: u64) -> Duration {
+    Duration::from_secs(secs)
+}
+
+fn virtual_table(seats: usize) -> Table {
+    Table::with_clock(seats, Arc::new(VirtualClock::new()))
+}
+
+fn dine(simulation: &Simulation, roster: &Roster, table: &Table, kind: Kind) -> Result<Report, tasks::Error> {
+    tasks::run(simulation, roster.seat(), table, &*kind.build(table.seats()))
+}
+
+/// Gives back its number once its sleep is over.
+struct After<'a>(Sleep<'a>, u64);
+
+impl<'a> Future for After<'a> {
+    type Output = u64;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<u64> {
+        let number = self.1;
+        Pin::new(&mut self.0).poll(cx).map(|()| number)
+    }
+}
+
+#[test]
+fn executor_runs_every_task_to_the_end() {
+    let clock = VirtualClock::new();
+    let mut executor = Executor::new(&clock);
+    for &wait in &[3, 1, 2] {
+        executor.spawn(After(executor::sleep(&clock, secs(wait)), wait));
+    }
+
+    // In the order they were spawned, not the order they finished in
+    assert_eq!(executor.run(), Ok(vec![3, 1, 2]));
+    assert_eq!(clock.now(), secs(3));
+}
+
+#[test]
+fn executor_sleeps_on_a_real_clock_too() {
+    let clock = RealClock::new();
+    let started = Instant::now();
+    let mut executor = Executor::new(&clock);
+    executor.spawn(After(executor::sleep(&clock, Duration::from_millis(30)), 0));
+    executor.spawn(After(executor::sleep(&clock, Duration::from_millis(20)), 1));
+
+    assert_eq!(executor.run(), Ok(vec![0, 1]));
+    assert!(started.elapsed() >= Duration::from_millis(30));
+    // Both at once, not one after the other
+    assert!(started.elapsed() < Duration::from_millis(500));
+}
+
+#[test]
+fn tasks_eat_everything_they_asked_for() {
+    let roster = Roster::generated(7);
+    let table = virtual_table(roster.len());
+    let simulation = Simulation { length: Length::Rounds(20), eat_time: secs(1) };
+
+    let report = dine(&simulation, &roster, &table, Kind::ResourceOrdering).unwrap();
+    assert_eq!(report.diners.len(), 7);
+    assert!(report.diners.iter().all(|diner| diner.meals == 20));
+    // At most three of seven can eat at once
+    assert!(report.elapsed >= secs(20 * 7 / 3));
+}
+
+#[test]
+fn a_sommelier_serves_tasks_too() {
+    let roster = Roster::generated(5);
+    let table = virtual_table(roster.len());
+    let simulation = Simulation { length: Length::Rounds(10), eat_time: secs(1) };
+
+    let report = tasks::run(&simulation, roster.seat(), &table, &Sommelier::new(5)).unwrap();
+    assert!(report.diners.iter().all(|diner| diner.meals == 10));
+    assert!(report.elapsed >= secs(10 * 5 / 2));
+}
+
+#[test]
+fn two_tasks_take_turns() {
+    // Both need both forks, so every meal happens after the last one
+    let roster = Roster::generated(2);
+    let table = virtual_table(2);
+    let simulation = Simulation { length: Length::Rounds(10), eat_time: secs(1) };
+
+    let report = dine(&simulation, &roster, &table, Kind::ResourceOrdering).unwrap();
+    assert_eq!(report.meals(), 20);
+    assert_eq!(report.elapsed, secs(20));
+}
+
+#[test]
+fn tasks_dine_for_a_duration() {
+    let roster = Roster::default();
+    let table = virtual_table(roster.len());
+    let simulation = Simulation { length: Length::Duration(secs(100)), eat_time: secs(1) };
+
+    let report = dine(&simulation, &roster, &table, Kind::ResourceOrdering).unwrap();
+    assert!(report.meals() > 100);
+    assert!(report.elapsed >= secs(100));
+}
+
+#[test]
+fn tasks_dine_on_a_real_clock() {
+    let roster = Roster::default();
+    let table = Table::new(roster.len());
+    let simulation = Simulation { length: Length::Rounds(2), eat_time: Duration::from_millis(5) };
+
+    let report = dine(&simulation, &roster, &table, Kind::ResourceOrdering).unwrap();
+    assert_eq!(report.meals(), 10);
+}
+
+#[test]
+fn naive_tasks_deadlock_and_say_who() {
+    let roster = Roster::default();
+    let table = virtual_table(roster.len());
+    let simulation = Simulation { length: Length::Rounds(1), eat_time: secs(1) };
+
+    // Everybody gets their left fork while the others are still reaching,
+    // every time
+    let cycle: Vec<Wait> = (0..5).map(|seat| Wait {
+        philosopher: seat,
+        fork: (seat + 1) % 5,
+        holder: (seat + 1) % 5,
+    }).collect();
+    match dine(&simulation, &roster, &table, Kind::Naive) {
+        Err(tasks::Error::Deadlock(deadlock)) => {
+            assert_eq!(deadlock.cycle, cycle);
+            assert!(deadlock.to_string().contains(
+                "Mikael Stanne (seat 0) is waiting for fork 1, which Jesper Strömblad (seat 1) is holding"), "{}", deadlock);
+        }
+        other => panic!("expected a deadlock, got {:?}", other),
+    }
+}
+
+#[test]
+fn every_safe_strategy_runs_as_tasks() {
+    let roster = Roster::generated(7);
+    let simulation = Simulation { length: Length::Rounds(20), eat_time: secs(1) };
+
+    for &kind in Kind::SAFE.iter() {
+        let table = virtual_table(roster.len());
+        let report = dine(&simulation, &roster, &table, kind).unwrap();
+        assert!(report.diners.iter().all(|diner| diner.meals == 20), "{}", kind);
+        assert!(report.elapsed >= secs(20 * 7 / 3), "{}", kind);
+    }
+}
+
+#[test]
+fn tasks_take_turns_whatever_the_strategy() {
+    let roster = Roster::generated(2);
+    let simulation = Simulation { length: Length::Rounds(10), eat_time: secs(1) };
+
+    for &kind in Kind::SAFE.iter() {
+        let table = virtual_table(2);
+        let report = dine(&simulation, &roster, &table, kind).unwrap();
+        assert_eq!(report.meals(), 20, "{}", kind);
+        if kind == Kind::Backoff {
+            assert!(report.elapsed >= secs(20));
+        } else {
+            assert_eq!(report.elapsed, secs(20), "{}", kind);
+        }
+    }
+}
+
+#[test]
+fn the_waiter_keeps_one_task_from_reaching() {
+    let roster = Roster::default();
+    let memory = Arc::new(Memory::new());
+    let table = virtual_table(roster.len()).with_events(memory.clone());
+    let simulation = Simulation { length: Length::Rounds(5), eat_time: secs(1) };
+    dine(&simulation, &roster, &table, Kind::Waiter).unwrap();
+
+    // Everybody picks up their left fork first, but never all at once
+    let mut reaching = 0;
+    for event in memory.events() {
+        match event.kind {
+            events::Kind::PickedUp { side: events::Side::Left, .. } => reaching += 1,
+            events::Kind::Done => reaching -= 1,
+            _ => {}
+        }
+        assert!(reaching < 5);
+    }
+}
+
+#[test]
+#[should_panic(expected = "dropped their fork")]
+fn tasks_fumble_too() {
+    let mut philosophers = Roster::default().seat();
+    philosophers[2].fumbles.push(2);
+    let table = virtual_table(philosophers.len());
+    let simulation = Simulation { length: Length::Rounds(3), eat_time: secs(1) };
+
+    let _ = tasks::run(&simulation, philosophers, &table, &*Kind::Waiter.build(table.seats()));
+}
+
+#[test]
+fn tasks_only_eat_with_both_forks() {
+    let roster = Roster::default();
+    let simulation = Simulation { length: Length::Rounds(3), eat_time: secs(1) };
+
+    for &kind in Kind::SAFE.iter() {
+        let memory = Arc::new(Memory::new());
+        let table = virtual_table(roster.len()).with_events(memory.clone());
+        dine(&simulation, &roster, &table, kind).unwrap();
+
+        for seat in 0..roster.len() {
+            let mut held = 0;
+            for event in memory.events().iter().filter(|event| event.philosopher == seat) {
+                match event.kind {
+                    events::Kind::PickedUp { .. } => held += 1,
+                    events::Kind::PutDown { .. } => held -= 1,
+                    events::Kind::Eating => assert_eq!(held, 2, "{}", kind),
+                    _ => {}
+                }
+            }
+            assert_eq!(held, 0, "{}", kind);
+        }
+    }
+}
@@ -0,0 +1,144 @@
+extern crate dining_philosophers;
+
+use std::sync::mpsc;
+use std::sync::{Arc, Barrier};
+use std::thread;
+use std::time::Duration;
+
+use dining_philosophers::{DiscreteClock, Fork, Kind, Length, Resource, Roster, Simulation, Strategy, Table};
+use dining_philosophers::strategy::Waiter;
+use dining_philosophers::watchdog::{self, Deadlock, Wait, Watchdog};
+
+#[test]
+fn forks_know_who_has_them() {
+    let fork = Arc::new(Fork::new());
+    assert_eq!(fork.holder(), None);
+
+    let guard = fork.lock(3).unwrap();
+    assert_eq!(fork.holder(), Some(3));
+    assert!(fork.try_lock(4).is_err());
+
+    let waiting = fork.clone();
+    let waiter = thread::spawn(move || {
+        let _guard = waiting.lock(4).unwrap();
+    });
+    while fork.waiting() != vec![4] {
+        thread::yield_now();
+    }
+
+    drop(guard);
+    waiter.join().unwrap();
+    assert_eq!(fork.holder(), None);
+    assert!(fork.waiting().is_empty());
+}
+
+#[test]
+fn forks_get_poisoned() {
+    let fork = Arc::new(Fork::new());
+
+    let poisoner = fork.clone();
+    let _ = thread::spawn(move || {
+        let _guard = poisoner.lock(0).unwrap();
+        panic!("dropped the fork");
+    }).join();
+
+    assert!(fork.lock(1).is_err());
+    assert_eq!(fork.holder(), None);
+}
+
+#[test]
+fn no_cycle_at_an_idle_table() {
+    assert_eq!(watchdog::find_cycle(&Table::new(5)), None);
+}
+
+#[test]
+fn catches_the_naive_strategy_deadlocking() {
+    let roster = Roster::generated(5);
+    let table = Arc::new(Table::new(roster.len()));
+    let strategy: Arc<dyn Strategy> = Arc::from(Kind::Naive.build(table.seats()));
+    let names = roster.guests.iter().map(|guest| guest.name.clone()).collect();
+
+    // Everybody reaches for their left fork at once
+    let barrier = Arc::new(Barrier::new(roster.len()));
+    for p in roster.seat() {
+        let (table, strategy, barrier) = (table.clone(), strategy.clone(), barrier.clone());
+        thread::spawn(move || {
+            barrier.wait();
+            p.eat(&table, &*strategy, || {});
+        });
+    }
+
+    let (sender, receiver) = mpsc::channel();
+    Watchdog::spawn(table, names, Duration::from_millis(10), move |deadlock| {
+        sender.send(deadlock).unwrap();
+    });
+
+    let deadlock = receiver.recv_timeout(Duration::from_secs(10)).expect("no deadlock noticed");
+    let expected: Vec<Wait> = (0..5)
+        .map(|seat| Wait { philosopher: seat, fork: (seat + 1) % 5, holder: (seat + 1) % 5 })
+        .collect();
+    assert_eq!(deadlock.cycle, expected);
+
+    let report = deadlock.to_string();
+    assert!(report.starts_with("deadlock: 5 philosophers are waiting on each other"));
+    assert!(report.contains(
+        "Philosopher 5 (seat 4) is waiting for fork 0, which Philosopher 1 (seat 0) is holding"));
+}
+
+#[test]
+fn a_stuck_scheduler_sets_it_off_without_a_cycle() {
+    // A waiter with no permits to hand out keeps everybody waiting, but
+    // nobody ever gets as far as a fork
+    let roster = Roster::generated(3);
+    let clock = Arc::new(DiscreteClock::new());
+    let table = Arc::new(Table::with_clock(roster.len(), clock.clone()).with_scheduler(clock));
+    let strategy: Arc<dyn Strategy> = Arc::new(Waiter::with_permits(0));
+    let names = roster.guests.iter().map(|guest| guest.name.clone()).collect();
+
+    let (sender, receiver) = mpsc::channel();
+    Watchdog::spawn(table.clone(), names, Duration::from_millis(10), move |deadlock| {
+        sender.send(deadlock).unwrap();
+    });
+    thread::spawn(move || Simulation::default().run(roster.seat(), table, strategy));
+
+    let deadlock = receiver.recv_timeout(Duration::from_secs(10)).expect("no deadlock noticed");
+    assert_eq!(deadlock.cycle, Vec::new());
+    assert!(deadlock.to_string().contains("not for each other's forks"));
+}
+
+#[test]
+fn a_deadlocked_cellar_talks_about_agents_and_bottles() {
+    let deadlock = Deadlock {
+        cycle: vec![Wait { philosopher: 0, fork: 1, holder: 1 }, Wait { philosopher: 1, fork: 0, holder: 0 }],
+        names: vec!["transfer".to_string(), "report".to_string()],
+        resource: Resource::Bottle,
+    };
+
+    let report = deadlock.to_string();
+    assert!(report.starts_with("deadlock: 2 agents are waiting on each other"));
+    assert!(report.contains("transfer (seat 0) is waiting for bottle 1, which report (seat 1) is holding"));
+    assert!(!report.contains("fork"));
+}
+
+#[test]
+fn safe_strategies_never_set_it_off() {
+    for &kind in Kind::SAFE.iter() {
+        let roster = Roster::generated(5);
+        let table = Arc::new(Table::new(roster.len()));
+        let strategy: Arc<dyn Strategy> = Arc::from(kind.build(table.seats()));
+        let names = roster.guests.iter().map(|guest| guest.name.clone()).collect();
+
+        let (sender, receiver) = mpsc::channel();
+        let watchdog = Watchdog::spawn(table.clone(), names, Duration::from_millis(1),
+                                       move |deadlock| sender.send(deadlock).unwrap());
+
+        let simulation = Simulation {
+            length: Length::Duration(Duration::from_millis(100)),
+            eat_time: Duration::from_millis(1),
+        };
+        simulation.run(roster.seat(), table, strategy);
+        watchdog.stop();
+
+        assert!(receiver.try_recv().is_err(), "{} deadlocked", kind);
+    }
+}
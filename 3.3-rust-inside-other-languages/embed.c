@@ -0,0 +1,43 @@
+#include <stdio.h>
+
+#include "embed.h"
+
+static void finished(uint32_t thread, uint64_t count, void *user_data) {
+    unsigned *reported = user_data;
+    *reported += 1;
+    printf("Thread %u finished with count=%llu\n", thread, (unsigned long long) count);
+}
+
+int main(void) {
+    unsigned reported = 0;
+    uint64_t results[10];
+    int32_t status;
+    size_t i;
+
+    if (embed_version() != EMBED_ABI_VERSION) {
+        fprintf(stderr, "built against version %d of embed.h, but the library is version %u\n",
+                EMBED_ABI_VERSION, embed_version());
+        return 1;
+    }
+
+    status = embed_process(10, 5000000, results, 10);
+    if (status != EMBED_OK) {
+        fprintf(stderr, "embed_process failed with %d: %s\n", status, embed_last_error_message());
+        return 1;
+    }
+    for (i = 0; i < 10; i++) {
+        printf("Thread finished with count=%llu\n", (unsigned long long) results[i]);
+    }
+
+    /* Or hear about each thread as it finishes */
+    status = embed_process_each(10, 5000000, finished, &reported);
+    if (status != EMBED_OK) {
+        fprintf(stderr, "embed_process_each failed with %d: %s\n", status,
+                embed_last_error_message());
+        return 1;
+    }
+    printf("%u threads reported back\n", reported);
+
+    puts("done!");
+    return 0;
+}
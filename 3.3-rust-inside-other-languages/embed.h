@@ -0,0 +1,137 @@
+/*
+ * Generated from src/lib.rs by tests/header.rs, so don't edit it by hand.
+ * Run `EMBED_BLESS=1 cargo test` to write it out again.
+ */
+
+#ifndef EMBED_H
+#define EMBED_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/*
+ * Bumped whenever anything in `embed.h` changes in a way that would break
+ * a caller built against the old one.
+ */
+#define EMBED_ABI_VERSION 1
+
+/*
+ * The most threads anything will count on at once. Asking for more is
+ * `EMBED_ERROR_TOO_MANY_THREADS`, rather than the library trying to make
+ * room for them all and bringing the whole process down when it can't.
+ */
+#define EMBED_MAX_THREADS 1024
+
+/*
+ * Everything went fine.
+ */
+#define EMBED_OK 0
+
+/*
+ * A pointer that can't be null was.
+ */
+#define EMBED_ERROR_NULL_POINTER 1
+
+/*
+ * There isn't room for every thread's result.
+ */
+#define EMBED_ERROR_BUFFER_TOO_SMALL 2
+
+/*
+ * A thread couldn't be started, or died before it finished.
+ */
+#define EMBED_ERROR_THREAD 3
+
+/*
+ * Something panicked on the thread that called in.
+ */
+#define EMBED_ERROR_PANIC 4
+
+/*
+ * More than `EMBED_MAX_THREADS` threads were asked for.
+ */
+#define EMBED_ERROR_TOO_MANY_THREADS 5
+
+/*
+ * Called as each thread finishes, with the thread's number (counting from
+ * 0), what it counted to, and the `user_data` given to
+ * `embed_process_each`.
+ */
+typedef void (*EmbedCallback)(uint32_t thread, uint64_t count, void *user_data);
+
+/*
+ * The `EMBED_ABI_VERSION` this library was built with.
+ */
+uint32_t embed_version(void);
+
+/*
+ * What went wrong with the last call into the library on this thread, or
+ * null if it went fine. The string belongs to the library, and only lasts
+ * until the next call on the same thread, so copy it to keep it.
+ */
+const char *embed_last_error_message(void);
+
+/*
+ * Counts to `iterations` on each of `threads` threads, and writes what each
+ * thread got to into `out_results`, which has room for `out_len` of them.
+ * Returns `EMBED_OK`, or one of the `EMBED_ERROR_` codes, in which case
+ * nothing has been written.
+ *
+ * # Safety
+ *
+ * `out_results` has to point to at least `out_len` writable `uint64_t`s.
+ */
+int32_t embed_process(uint32_t threads, uint64_t iterations, uint64_t *out_results, size_t out_len);
+
+/*
+ * Like `embed_process`, but the library makes room for the results. A
+ * pointer to them goes in `out_results`, and how many there are in
+ * `out_len`. Hand them back to `embed_results_free` when done with them.
+ *
+ * # Safety
+ *
+ * `out_results` and `out_len` have to be writable.
+ */
+int32_t embed_process_alloc(uint32_t threads, uint64_t iterations, uint64_t **out_results, size_t *out_len);
+
+/*
+ * Frees results from `embed_process_alloc`. Does nothing if `results` is
+ * null.
+ *
+ * # Safety
+ *
+ * `results` and `len` have to be exactly what `embed_process_alloc` gave
+ * out, and can only be freed once.
+ */
+void embed_results_free(uint64_t *results, size_t len);
+
+/*
+ * Like `embed_process`, but calls `callback` as each thread finishes,
+ * rather than writing anything out. `callback` is always called on the
+ * thread that called `embed_process_each`, which returns once every
+ * thread has been reported.
+ *
+ * # Safety
+ *
+ * `callback` has to be safe to call with `user_data`.
+ */
+int32_t embed_process_each(uint32_t threads, uint64_t iterations, EmbedCallback callback, void *user_data);
+
+/*
+ * The book's original: ten threads counting to fifty thousand each, with
+ * the results printed rather than handed back. Use one of the
+ * `embed_process` functions to keep the host's stdout to itself. If
+ * anything goes wrong, it stops, and `embed_last_error_message()` says
+ * why.
+ */
+void process(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* EMBED_H */
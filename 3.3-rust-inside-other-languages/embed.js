@@ -1,9 +1,88 @@
+var fs = require("fs");
+var path = require("path");
 var ffi = require("ffi");
+var ref = require("ref");
 
-var lib = ffi.Library("target/release/libembed", {
-    process: ["void", []]
+// Everything comes from embed.h: the `#define EMBED_...` numbers, the
+// callback typedef and every function, one to a line.
+var header = fs.readFileSync(path.join(__dirname, "embed.h"), "utf8").replace(/\/\*[\s\S]*?\*\//g, "");
+var types = {
+    "void": "void", "uint32_t": "uint32", "uint64_t": "uint64", "int32_t": "int32",
+    "size_t": "size_t", "const char *": "string"
+};
+var callbacks = {};
+
+// `uint64_t *` or `EmbedCallback` to what ffi calls it
+function type(c) {
+    c = c.trim();
+    if (c in types) {
+        return types[c];
+    }
+    // Function pointers go over as plain pointers too
+    return /\*$/.test(c) || c in callbacks ? "pointer" : c;
+}
+
+// The types in `uint32_t threads, uint64_t *out_results`, less the names
+function params(list) {
+    if (list.trim() === "void") {
+        return [];
+    }
+    return list.split(",").map(function (param) {
+        return type(param.replace(/\**\w+\s*$/, function (name) {
+            return name.replace(/\w|\s/g, "");
+        }));
+    });
+}
+
+var embed = {};
+header.replace(/^#define (EMBED_\w+) (\d+)$/gm, function (_, name, value) {
+    embed[name] = Number(value);
+});
+header.replace(/^typedef (.+?) \(\*(\w+)\)\((.*)\);$/gm, function (_, returns, name, list) {
+    callbacks[name] = [type(returns), params(list)];
+});
+var functions = {};
+header.replace(/^(?!typedef)(.*?)(\w+)\((.*)\);$/gm, function (_, returns, name, list) {
+    functions[name] = [type(returns), params(list)];
+});
+
+var lib = ffi.Library(path.join(__dirname, "target/release/libembed"), functions);
+
+if (lib.embed_version() !== embed.EMBED_ABI_VERSION) {
+    throw new Error("libembed is version " + lib.embed_version() + ", expected " + embed.EMBED_ABI_VERSION);
+}
+
+function check(name, status) {
+    if (status !== embed.EMBED_OK) {
+        throw new Error(name + " failed with " + status + ": " + lib.embed_last_error_message());
+    }
+}
+
+// Room for ten uint64_t
+var results = Buffer.alloc(10 * 8);
+check("embed_process", lib.embed_process(10, 5000000, results, 10));
+
+for (var i = 0; i < 10; i++) {
+    console.log("Thread finished with count=" + ref.readUInt64LE(results, i * 8));
+}
+
+// Or let the library make room for them
+var allocated = ref.alloc(ref.refType("uint64"));
+var length = ref.alloc("size_t");
+check("embed_process_alloc", lib.embed_process_alloc(10, 5000000, allocated, length));
+var counts = ref.reinterpret(allocated.deref(), length.deref() * 8);
+console.log(length.deref() + " threads counted, the first to " + ref.readUInt64LE(counts, 0));
+lib.embed_results_free(allocated.deref(), length.deref());
+
+// Or hear about each thread as it finishes
+var callback = callbacks.EmbedCallback;
+var finished = ffi.Callback(callback[0], callback[1], function (thread, count) {
+    console.log("Thread " + thread + " finished with count=" + count);
 });
+check("embed_process_each", lib.embed_process_each(10, 5000000, finished, null));
 
-lib.process();
+// And when something goes wrong, the library says what
+var status = lib.embed_process_alloc(embed.EMBED_MAX_THREADS + 1, 10, allocated, length);
+console.log("Asking for too many threads gives " + status + ": " + lib.embed_last_error_message());
 
 console.log("done!");
@@ -1,7 +1,55 @@
-from ctypes import cdll
+import os
+import sys
 
-lib = cdll.LoadLibrary("target/release/libembed.dylib");
+from cffi import FFI
 
-lib.process();
+here = os.path.dirname(os.path.abspath(__file__))
+
+# Everything comes from embed.h, less the lines meant for a C preprocessor,
+# which cffi doesn't have. Its `#define EMBED_...` numbers are kept.
+declarations = []
+with open(os.path.join(here, "embed.h")) as header:
+    cplusplus = False
+    for line in header:
+        if line.startswith("#ifdef __cplusplus"):
+            cplusplus = True
+        elif cplusplus:
+            cplusplus = not line.startswith("#endif")
+        elif not line.startswith("#") or line.startswith("#define EMBED_") and len(line.split()) == 3:
+            declarations.append(line)
+
+ffi = FFI()
+ffi.cdef("".join(declarations))
+lib = ffi.dlopen(os.path.join(here, "target/release/libembed" + (".dylib" if sys.platform == "darwin" else ".so")))
+
+if lib.embed_version() != lib.EMBED_ABI_VERSION:
+    raise RuntimeError("libembed is version %d, expected %d" % (lib.embed_version(), lib.EMBED_ABI_VERSION))
+
+def check(name, status):
+    if status != lib.EMBED_OK:
+        raise RuntimeError("%s failed with %d: %s" % (name, status, ffi.string(lib.embed_last_error_message()).decode()))
+
+results = ffi.new("uint64_t[]", 10)
+check("embed_process", lib.embed_process(10, 5000000, results, len(results)))
+
+for count in results:
+    print("Thread finished with count=%d" % count)
+
+# Or let the library make room for them
+results, length = ffi.new("uint64_t **"), ffi.new("size_t *")
+check("embed_process_alloc", lib.embed_process_alloc(10, 5000000, results, length))
+print("%d threads counted to %d in all" % (length[0], sum(results[0][0:length[0]])))
+lib.embed_results_free(results[0], length[0])
+
+# Or hear about each thread as it finishes
+@ffi.callback("EmbedCallback")
+def finished(thread, count, user_data):
+    print("Thread %d finished with count=%d" % (thread, count))
+
+check("embed_process_each", lib.embed_process_each(10, 5000000, finished, ffi.NULL))
+
+# And when something goes wrong, the library says what
+status = lib.embed_process_alloc(lib.EMBED_MAX_THREADS + 1, 10, results, length)
+print("Asking for too many threads gives %d: %s" % (status, ffi.string(lib.embed_last_error_message()).decode()))
 
 print("done!");
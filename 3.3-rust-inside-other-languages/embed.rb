@@ -1,11 +1,67 @@
 require 'ffi'
 
-module Hello
+# Everything comes from embed.h: the `#define EMBED_...` numbers, the
+# callback typedef and every function, one to a line.
+module Embed
     extend FFI::Library
-      ffi_lib 'target/release/libembed.dylib'
-        attach_function :process, [], :void
+    ffi_lib File.join(__dir__, "target/release/libembed.#{FFI::Platform::LIBSUFFIX}")
+
+    HEADER = File.read(File.join(__dir__, 'embed.h')).gsub(%r{/\*.*?\*/}m, '')
+    TYPES = { 'void' => :void, 'uint32_t' => :uint32, 'uint64_t' => :uint64, 'int32_t' => :int32,
+              'size_t' => :size_t, 'const char *' => :string }
+
+    # `uint64_t *` or `EmbedCallback` to what FFI calls it
+    def self.type(c)
+        c = c.strip
+        return TYPES[c] if TYPES.key?(c)
+        return :pointer if c.end_with?('*')
+        find_type(c.to_sym)
+    end
+
+    # The types in `uint32_t threads, uint64_t *out_results`, less the names
+    def self.params(list)
+        return [] if list.strip == 'void'
+        list.split(',').map { |param| type(param.sub(/\**\w+\s*\z/) { |name| name.delete('^*') }) }
+    end
+
+    HEADER.scan(/^#define (EMBED_\w+) (\d+)$/) { |name, value| const_set(name, Integer(value)) }
+    HEADER.scan(/^typedef (.+?) \(\*(\w+)\)\((.*)\);$/) do |returns, name, list|
+        callback name.to_sym, params(list), type(returns)
+    end
+    HEADER.scan(/^(?!typedef)(.*?)(\w+)\((.*)\);$/) do |returns, name, list|
+        attach_function name.to_sym, params(list), type(returns)
+    end
 end
 
-Hello.process
+if Embed.embed_version != Embed::EMBED_ABI_VERSION
+    raise "libembed is version #{Embed.embed_version}, expected #{Embed::EMBED_ABI_VERSION}"
+end
+
+def check(name, status)
+    return if status == Embed::EMBED_OK
+    raise "#{name} failed with #{status}: #{Embed.embed_last_error_message}"
+end
+
+results = FFI::MemoryPointer.new(:uint64, 10)
+check('embed_process', Embed.embed_process(10, 5000000, results, 10))
+
+results.read_array_of_uint64(10).each do |count|
+    puts "Thread finished with count=#{count}"
+end
+
+# Or let the library make room for them
+results, length = FFI::MemoryPointer.new(:pointer), FFI::MemoryPointer.new(:size_t)
+check('embed_process_alloc', Embed.embed_process_alloc(10, 5000000, results, length))
+counts = results.read_pointer.read_array_of_uint64(length.read(:size_t))
+puts "#{counts.length} threads counted to #{counts.sum} in all"
+Embed.embed_results_free(results.read_pointer, length.read(:size_t))
+
+# Or hear about each thread as it finishes
+finished = proc { |thread, count, _user_data| puts "Thread #{thread} finished with count=#{count}" }
+check('embed_process_each', Embed.embed_process_each(10, 5000000, finished, nil))
+
+# And when something goes wrong, the library says what
+status = Embed.embed_process_alloc(Embed::EMBED_MAX_THREADS + 1, 10, results, length)
+puts "Asking for too many threads gives #{status}: #{Embed.embed_last_error_message}"
 
 puts 'done!'
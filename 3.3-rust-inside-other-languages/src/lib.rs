@@ -1,22 +1,333 @@
+//! A little Rust to call from C, Ruby, Python or anything else that can
+//! load a shared library.
+//!
+//! Everything callable from outside is declared in `embed.h`, which is
+//! generated from this file by `tests/header.rs`. Callers should check
+//! `embed_version()` against the `EMBED_ABI_VERSION` in the header they
+//! were built with before calling anything else.
+//!
+//! Nothing is allowed to unwind out into the caller, which would be
+//! undefined behaviour at best. Every exported function runs inside
+//! `guard`, which turns a panic into `EMBED_ERROR_PANIC`, and leaves a
+//! message behind for `embed_last_error_message()` whenever a call fails.
+
+use std::any::Any;
+#[cfg(feature = "fumble")]
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::error;
+use std::ffi::CString;
+use std::fmt;
+use std::io;
+use std::os::raw::{c_char, c_void};
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::slice;
+use std::sync::mpsc;
 use std::thread;
 
-#[no_mangle] // an attribute "no_mangle" that says don't change the name of the function
-             // in the compiled output.
-// `pub` means this function should be callable from outside the module
-// `extern` means that this should be able to be called from C.
-pub extern fn process() {
-    let handles: Vec<_> = (0..10).map(|_| {
-        thread::spawn(|| {
+/// Bumped whenever anything in `embed.h` changes in a way that would break
+/// a caller built against the old one.
+pub const EMBED_ABI_VERSION: u32 = 1;
+
+/// The most threads anything will count on at once. Asking for more is
+/// `EMBED_ERROR_TOO_MANY_THREADS`, rather than the library trying to make
+/// room for them all and bringing the whole process down when it can't.
+pub const EMBED_MAX_THREADS: u32 = 1024;
+
+/// Everything went fine.
+pub const EMBED_OK: i32 = 0;
+/// A pointer that can't be null was.
+pub const EMBED_ERROR_NULL_POINTER: i32 = 1;
+/// There isn't room for every thread's result.
+pub const EMBED_ERROR_BUFFER_TOO_SMALL: i32 = 2;
+/// A thread couldn't be started, or died before it finished.
+pub const EMBED_ERROR_THREAD: i32 = 3;
+/// Something panicked on the thread that called in.
+pub const EMBED_ERROR_PANIC: i32 = 4;
+/// More than `EMBED_MAX_THREADS` threads were asked for.
+pub const EMBED_ERROR_TOO_MANY_THREADS: i32 = 5;
+
+/// What can go wrong, on the Rust side of things.
+#[derive(Debug)]
+pub enum Error {
+    /// The named argument was null.
+    NullPointer(&'static str),
+    /// Room for `len` results, with `threads` to write.
+    BufferTooSmall { threads: u32, len: usize },
+    /// More than `EMBED_MAX_THREADS` of them.
+    TooManyThreads(u32),
+    Spawn(io::Error),
+    /// A thread panicked, with this, instead of giving back its count.
+    Join(String),
+    /// The call itself panicked, with this.
+    Panic(String),
+}
+
+impl Error {
+    /// The `EMBED_ERROR_` code it goes back to C as.
+    pub fn code(&self) -> i32 {
+        match *self {
+            Error::NullPointer(_) => EMBED_ERROR_NULL_POINTER,
+            Error::BufferTooSmall { .. } => EMBED_ERROR_BUFFER_TOO_SMALL,
+            Error::TooManyThreads(_) => EMBED_ERROR_TOO_MANY_THREADS,
+            Error::Spawn(_) | Error::Join(_) => EMBED_ERROR_THREAD,
+            Error::Panic(_) => EMBED_ERROR_PANIC,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::NullPointer(name) => write!(f, "`{}` is null", name),
+            Error::BufferTooSmall { threads, len } => {
+                write!(f, "there's only room for {} results, but {} threads", len, threads)
+            }
+            Error::TooManyThreads(threads) => {
+                write!(f, "can't count on {} threads, only {} at most", threads, EMBED_MAX_THREADS)
+            }
+            Error::Spawn(ref err) => write!(f, "couldn't start a thread: {}", err),
+            Error::Join(ref message) => write!(f, "a thread panicked: {}", message),
+            Error::Panic(ref message) => write!(f, "panicked: {}", message),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+/// Counts to `iterations` on `threads` threads at once, and gives back what
+/// each of them got to.
+pub fn count(threads: u32, iterations: u64) -> Result<Vec<u64>, Error> {
+    if threads > EMBED_MAX_THREADS {
+        return Err(Error::TooManyThreads(threads));
+    }
+    let mut counts = vec![0; threads as usize];
+    count_each(threads, iterations, |thread, count| counts[thread as usize] = count)?;
+    Ok(counts)
+}
+
+/// Like `count`, but calls `each` with every thread's number and count as
+/// soon as it finishes, on the calling thread.
+pub fn count_each<F: FnMut(u32, u64)>(threads: u32, iterations: u64, mut each: F) -> Result<(), Error> {
+    if threads > EMBED_MAX_THREADS {
+        return Err(Error::TooManyThreads(threads));
+    }
+
+    #[cfg(feature = "fumble")]
+    let fumble = FUMBLE.with(Cell::get);
+    let (sender, finished) = mpsc::channel();
+    let mut handles = Vec::with_capacity(threads as usize);
+    for thread in 0..threads {
+        let sender = sender.clone();
+        let spawned = thread::Builder::new().spawn(move || {
+            #[cfg(feature = "fumble")]
+            {
+                if fumble == Some(thread) {
+                    panic!("fumbled the count on thread {}", thread);
+                }
+            }
             let mut x = 0;
-            for _ in (0..5_00_00) {
+            for _ in 0..iterations {
                 x += 1
             }
-            x
-        })
-    }).collect();
+            // Nobody's listening if another thread couldn't be started
+            let _ = sender.send((thread, x));
+        });
+        match spawned {
+            Ok(handle) => handles.push(handle),
+            Err(err) => {
+                // Don't leave the ones that did start running behind our back
+                for h in handles {
+                    let _ = h.join();
+                }
+                return Err(Error::Spawn(err));
+            }
+        }
+    }
+    // Or the loop below would wait for it forever
+    drop(sender);
 
+    // In whatever order they finish
+    for (thread, x) in finished {
+        each(thread, x);
+    }
     for h in handles {
-        println!("Thread finished with count={}",
-            h.join().map_err(|_| "Could not join a thread!").unwrap());
+        h.join().map_err(|payload| Error::Join(message(&*payload)))?;
+    }
+    Ok(())
+}
+
+/// What a panic said, if it said anything.
+fn message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "something went wrong".to_string()
     }
 }
+
+thread_local! {
+    // Kept as a `CString` so there's something to hand C a pointer to
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+#[cfg(feature = "fumble")]
+thread_local! {
+    static FUMBLE: Cell<Option<u32>> = const { Cell::new(None) };
+}
+
+/// Makes worker `thread` panic in everything started from this thread,
+/// until it's called again with `None`. Only built with the `fumble`
+/// feature, so the tests can see what happens when a worker dies.
+#[cfg(feature = "fumble")]
+pub fn fumble(thread: Option<u32>) {
+    FUMBLE.with(|fumble| fumble.set(thread));
+}
+
+/// Runs the body of an exported function, catching any panic, and gives
+/// back the `EMBED_` code for how it went. Remembers what went wrong, if
+/// anything did, for `embed_last_error_message()` on this thread.
+pub fn guard<F: FnOnce() -> Result<(), Error>>(body: F) -> i32 {
+    let result = panic::catch_unwind(AssertUnwindSafe(body))
+        .unwrap_or_else(|payload| Err(Error::Panic(message(&*payload))));
+    let (code, last) = match result {
+        Ok(()) => (EMBED_OK, None),
+        Err(err) => {
+            // C would stop reading at a nul in the middle
+            let message = err.to_string().replace('\0', "\\0");
+            (err.code(), CString::new(message).ok())
+        }
+    };
+    // Only fails once the thread is on its way out, when nobody can ask
+    let _ = LAST_ERROR.try_with(|error| *error.borrow_mut() = last);
+    code
+}
+
+/// Called as each thread finishes, with the thread's number (counting from
+/// 0), what it counted to, and the `user_data` given to
+/// `embed_process_each`.
+pub type EmbedCallback = Option<unsafe extern "C" fn(thread: u32, count: u64, user_data: *mut c_void)>;
+
+/// The `EMBED_ABI_VERSION` this library was built with.
+#[no_mangle]
+pub extern "C" fn embed_version() -> u32 {
+    // Nothing here can panic, so there's nothing to guard
+    EMBED_ABI_VERSION
+}
+
+/// What went wrong with the last call into the library on this thread, or
+/// null if it went fine. The string belongs to the library, and only lasts
+/// until the next call on the same thread, so copy it to keep it.
+#[no_mangle]
+pub extern "C" fn embed_last_error_message() -> *const c_char {
+    LAST_ERROR.try_with(|error| error.borrow().as_ref().map_or(ptr::null(), |message| message.as_ptr()))
+        .unwrap_or(ptr::null())
+}
+
+/// Counts to `iterations` on each of `threads` threads, and writes what each
+/// thread got to into `out_results`, which has room for `out_len` of them.
+/// Returns `EMBED_OK`, or one of the `EMBED_ERROR_` codes, in which case
+/// nothing has been written.
+///
+/// # Safety
+///
+/// `out_results` has to point to at least `out_len` writable `uint64_t`s.
+#[no_mangle]
+pub unsafe extern "C" fn embed_process(threads: u32, iterations: u64, out_results: *mut u64,
+                                       out_len: usize) -> i32 {
+    guard(|| {
+        if out_results.is_null() && threads > 0 {
+            return Err(Error::NullPointer("out_results"));
+        }
+        if out_len < threads as usize {
+            return Err(Error::BufferTooSmall { threads, len: out_len });
+        }
+
+        let counts = count(threads, iterations)?;
+        if !counts.is_empty() {
+            slice::from_raw_parts_mut(out_results, counts.len()).copy_from_slice(&counts);
+        }
+        Ok(())
+    })
+}
+
+/// Like `embed_process`, but the library makes room for the results. A
+/// pointer to them goes in `out_results`, and how many there are in
+/// `out_len`. Hand them back to `embed_results_free` when done with them.
+///
+/// # Safety
+///
+/// `out_results` and `out_len` have to be writable.
+#[no_mangle]
+pub unsafe extern "C" fn embed_process_alloc(threads: u32, iterations: u64, out_results: *mut *mut u64,
+                                             out_len: *mut usize) -> i32 {
+    guard(|| {
+        if out_results.is_null() {
+            return Err(Error::NullPointer("out_results"));
+        }
+        if out_len.is_null() {
+            return Err(Error::NullPointer("out_len"));
+        }
+
+        let counts = count(threads, iterations)?.into_boxed_slice();
+        *out_len = counts.len();
+        *out_results = Box::into_raw(counts) as *mut u64;
+        Ok(())
+    })
+}
+
+/// Frees results from `embed_process_alloc`. Does nothing if `results` is
+/// null.
+///
+/// # Safety
+///
+/// `results` and `len` have to be exactly what `embed_process_alloc` gave
+/// out, and can only be freed once.
+#[no_mangle]
+pub unsafe extern "C" fn embed_results_free(results: *mut u64, len: usize) {
+    guard(|| {
+        if !results.is_null() {
+            drop(Box::from_raw(ptr::slice_from_raw_parts_mut(results, len)));
+        }
+        Ok(())
+    });
+}
+
+/// Like `embed_process`, but calls `callback` as each thread finishes,
+/// rather than writing anything out. `callback` is always called on the
+/// thread that called `embed_process_each`, which returns once every
+/// thread has been reported.
+///
+/// # Safety
+///
+/// `callback` has to be safe to call with `user_data`.
+#[no_mangle]
+pub unsafe extern "C" fn embed_process_each(threads: u32, iterations: u64, callback: EmbedCallback,
+                                            user_data: *mut c_void) -> i32 {
+    guard(|| {
+        let callback = callback.ok_or(Error::NullPointer("callback"))?;
+        count_each(threads, iterations, |thread, count| callback(thread, count, user_data))
+    })
+}
+
+#[no_mangle] // an attribute "no_mangle" that says don't change the name of the function
+             // in the compiled output.
+// `pub` means this function should be callable from outside the module
+// `extern` means that this should be able to be called from C.
+//
+/// The book's original: ten threads counting to fifty thousand each, with
+/// the results printed rather than handed back. Use one of the
+/// `embed_process` functions to keep the host's stdout to itself. If
+/// anything goes wrong, it stops, and `embed_last_error_message()` says
+/// why.
+pub extern "C" fn process() {
+    guard(|| {
+        for x in count(10, 5_00_00)? {
+            println!("Thread finished with count={}", x);
+        }
+        Ok(())
+    });
+}
@@ -0,0 +1,175 @@
+extern crate embed;
+
+use std::ffi::CStr;
+use std::os::raw::c_void;
+use std::ptr;
+use std::slice;
+use std::thread;
+
+use embed::{EMBED_ABI_VERSION, EMBED_ERROR_BUFFER_TOO_SMALL, EMBED_ERROR_NULL_POINTER, EMBED_ERROR_PANIC,
+            EMBED_ERROR_THREAD, EMBED_ERROR_TOO_MANY_THREADS, EMBED_MAX_THREADS, EMBED_OK};
+
+/// What `embed_last_error_message` has to say, if anything.
+fn last_error() -> Option<String> {
+    let message = embed::embed_last_error_message();
+    if message.is_null() {
+        None
+    } else {
+        Some(unsafe { CStr::from_ptr(message) }.to_str().unwrap().to_string())
+    }
+}
+
+#[test]
+fn version() {
+    assert_eq!(embed::embed_version(), EMBED_ABI_VERSION);
+}
+
+#[test]
+fn counts_on_every_thread() {
+    let mut results = [0; 4];
+    let status = unsafe { embed::embed_process(4, 1000, results.as_mut_ptr(), results.len()) };
+
+    assert_eq!(status, EMBED_OK);
+    assert_eq!(results, [1000; 4]);
+}
+
+#[test]
+fn leaves_the_rest_of_the_buffer_alone() {
+    let mut results = [7; 5];
+    let status = unsafe { embed::embed_process(3, 10, results.as_mut_ptr(), results.len()) };
+
+    assert_eq!(status, EMBED_OK);
+    assert_eq!(results, [10, 10, 10, 7, 7]);
+}
+
+#[test]
+fn wont_write_past_the_end() {
+    let mut results = [7; 2];
+    let status = unsafe { embed::embed_process(3, 10, results.as_mut_ptr(), results.len()) };
+
+    assert_eq!(status, EMBED_ERROR_BUFFER_TOO_SMALL);
+    assert_eq!(results, [7, 7]);
+}
+
+#[test]
+fn needs_somewhere_to_write() {
+    assert_eq!(unsafe { embed::embed_process(2, 10, ptr::null_mut(), 2) }, EMBED_ERROR_NULL_POINTER);
+    // Unless there's nothing to write
+    assert_eq!(unsafe { embed::embed_process(0, 10, ptr::null_mut(), 0) }, EMBED_OK);
+}
+
+#[test]
+fn hands_over_results_it_allocated() {
+    let (mut results, mut len) = (ptr::null_mut(), 0);
+    let status = unsafe { embed::embed_process_alloc(3, 25, &mut results, &mut len) };
+
+    assert_eq!(status, EMBED_OK);
+    assert_eq!(unsafe { slice::from_raw_parts(results, len) }, &[25, 25, 25]);
+    unsafe { embed::embed_results_free(results, len) };
+}
+
+#[test]
+fn allocates_nothing_for_no_threads() {
+    let (mut results, mut len) = (ptr::null_mut(), 99);
+    assert_eq!(unsafe { embed::embed_process_alloc(0, 25, &mut results, &mut len) }, EMBED_OK);
+    assert_eq!(len, 0);
+    unsafe { embed::embed_results_free(results, len) };
+
+    // Freeing nothing at all is fine too
+    unsafe { embed::embed_results_free(ptr::null_mut(), 0) };
+}
+
+#[test]
+fn needs_somewhere_to_put_what_it_allocated() {
+    let mut len = 0;
+    let status = unsafe { embed::embed_process_alloc(1, 25, ptr::null_mut(), &mut len) };
+    assert_eq!(status, EMBED_ERROR_NULL_POINTER);
+}
+
+#[test]
+fn wont_start_any_number_of_threads() {
+    let (mut results, mut len) = (ptr::null_mut(), 99);
+    let status = unsafe { embed::embed_process_alloc(u32::MAX, 25, &mut results, &mut len) };
+
+    assert_eq!(status, EMBED_ERROR_TOO_MANY_THREADS);
+    assert!(results.is_null());
+    assert_eq!(len, 99);
+    assert_eq!(last_error().unwrap(), "can't count on 4294967295 threads, only 1024 at most");
+
+    let status = unsafe { embed::embed_process_each(EMBED_MAX_THREADS + 1, 25, Some(collect), ptr::null_mut()) };
+    assert_eq!(status, EMBED_ERROR_TOO_MANY_THREADS);
+}
+
+unsafe extern "C" fn collect(thread: u32, count: u64, user_data: *mut c_void) {
+    let finished = &mut *(user_data as *mut Vec<(u32, u64)>);
+    finished.push((thread, count));
+}
+
+#[test]
+fn calls_back_for_every_thread() {
+    let mut finished: Vec<(u32, u64)> = Vec::new();
+    let user_data = &mut finished as *mut Vec<(u32, u64)> as *mut c_void;
+    let status = unsafe { embed::embed_process_each(4, 50, Some(collect), user_data) };
+
+    assert_eq!(status, EMBED_OK);
+    // They can finish in any order
+    finished.sort();
+    assert_eq!(finished, vec![(0, 50), (1, 50), (2, 50), (3, 50)]);
+}
+
+#[test]
+fn needs_something_to_call_back() {
+    assert_eq!(unsafe { embed::embed_process_each(1, 50, None, ptr::null_mut()) }, EMBED_ERROR_NULL_POINTER);
+}
+
+#[test]
+fn says_what_went_wrong() {
+    let mut results = [0; 2];
+    let status = unsafe { embed::embed_process(3, 10, results.as_mut_ptr(), results.len()) };
+    assert_eq!(status, EMBED_ERROR_BUFFER_TOO_SMALL);
+    assert_eq!(last_error().unwrap(), "there's only room for 2 results, but 3 threads");
+
+    assert_eq!(unsafe { embed::embed_process_each(1, 50, None, ptr::null_mut()) }, EMBED_ERROR_NULL_POINTER);
+    assert_eq!(last_error().unwrap(), "`callback` is null");
+
+    // And forgets about it once a call goes fine
+    assert_eq!(unsafe { embed::embed_process(2, 10, results.as_mut_ptr(), results.len()) }, EMBED_OK);
+    assert_eq!(last_error(), None);
+}
+
+#[test]
+fn keeps_errors_to_their_own_thread() {
+    assert_eq!(unsafe { embed::embed_process(1, 10, ptr::null_mut(), 1) }, EMBED_ERROR_NULL_POINTER);
+
+    assert_eq!(thread::spawn(last_error).join().unwrap(), None);
+    assert_eq!(last_error().unwrap(), "`out_results` is null");
+}
+
+#[test]
+fn catches_panics() {
+    assert_eq!(embed::guard(|| panic!("not on my watch")), EMBED_ERROR_PANIC);
+    assert_eq!(last_error().unwrap(), "panicked: not on my watch");
+}
+
+#[test]
+fn passes_on_threads_that_panicked() {
+    embed::fumble(Some(1));
+
+    let mut results = [7; 3];
+    let status = unsafe { embed::embed_process(3, 10, results.as_mut_ptr(), results.len()) };
+    assert_eq!(status, EMBED_ERROR_THREAD);
+    assert_eq!(last_error().unwrap(), "a thread panicked: fumbled the count on thread 1");
+    assert_eq!(results, [7, 7, 7]);
+
+    // Everybody else is still reported
+    let mut finished: Vec<(u32, u64)> = Vec::new();
+    let user_data = &mut finished as *mut Vec<(u32, u64)> as *mut c_void;
+    let status = unsafe { embed::embed_process_each(3, 10, Some(collect), user_data) };
+    assert_eq!(status, EMBED_ERROR_THREAD);
+    assert_eq!(last_error().unwrap(), "a thread panicked: fumbled the count on thread 1");
+    finished.sort();
+    assert_eq!(finished, vec![(0, 10), (2, 10)]);
+
+    embed::fumble(None);
+    assert_eq!(unsafe { embed::embed_process(3, 10, results.as_mut_ptr(), results.len()) }, EMBED_OK);
+}
@@ -0,0 +1,209 @@
+//! `embed.h` is generated from `src/lib.rs` by this test, which fails if the
+//! header in the repository has fallen behind. Run
+//!
+//! ```sh
+//! $ EMBED_BLESS=1 cargo test
+//! ```
+//!
+//! to write it out again after changing anything that's exported.
+//!
+//! There's no `cbindgen` to hand, so this only understands what the
+//! library actually uses: `pub const EMBED_` numbers, `pub type`s for
+//! callbacks, and `pub extern "C" fn`s taking integers and pointers, along
+//! with their doc comments.
+
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+const PREAMBLE: &str = "\
+/*
+ * Generated from src/lib.rs by tests/header.rs, so don't edit it by hand.
+ * Run `EMBED_BLESS=1 cargo test` to write it out again.
+ */
+
+#ifndef EMBED_H
+#define EMBED_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern \"C\" {
+#endif
+";
+
+const POSTAMBLE: &str = "\
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* EMBED_H */
+";
+
+/// The C for a Rust type, given the names of the `pub type`s seen so far.
+fn c_type(rust: &str, aliases: &[String]) -> String {
+    let rust = rust.trim();
+    for &(prefix, qualifier) in &[("*mut ", ""), ("*const ", "const ")] {
+        if let Some(pointee) = rust.strip_prefix(prefix) {
+            let pointee = c_type(pointee, aliases);
+            return if pointee.ends_with('*') {
+                format!("{}{}*", qualifier, pointee)
+            } else {
+                format!("{}{} *", qualifier, pointee)
+            };
+        }
+    }
+
+    match rust {
+        "" | "()" => "void".to_string(),
+        "u8" => "uint8_t".to_string(),
+        "u32" => "uint32_t".to_string(),
+        "u64" => "uint64_t".to_string(),
+        "i32" => "int32_t".to_string(),
+        "usize" => "size_t".to_string(),
+        "c_char" => "char".to_string(),
+        "c_void" => "void".to_string(),
+        alias if aliases.iter().any(|known| known == alias) => alias.to_string(),
+        other => panic!("tests/header.rs doesn't know what `{}` is in C", other),
+    }
+}
+
+/// `type name`, or `type *name` for pointers.
+fn declare(c_type: &str, name: &str) -> String {
+    if c_type.ends_with('*') {
+        format!("{}{}", c_type, name)
+    } else {
+        format!("{} {}", c_type, name)
+    }
+}
+
+/// Doc comments as a C comment.
+fn comment(docs: &[String]) -> String {
+    if docs.is_empty() {
+        return String::new();
+    }
+    let mut comment = "/*\n".to_string();
+    for line in docs {
+        if line.is_empty() {
+            comment.push_str(" *\n");
+        } else {
+            comment.push_str(&format!(" * {}\n", line));
+        }
+    }
+    comment.push_str(" */\n");
+    comment
+}
+
+/// Splits `name(params) -> ret` into its name, its parameters and what it
+/// returns.
+fn signature(text: &str) -> (String, Vec<(String, String)>, String) {
+    let open = text.find('(').expect("a function without parameters");
+    let close = text.rfind(')').expect("a function without parameters");
+    let name = text[..open].trim().to_string();
+    let params = text[open + 1..close].split(',')
+        .map(|param| param.trim())
+        .filter(|param| !param.is_empty())
+        .map(|param| {
+            let colon = param.find(':').expect("a parameter without a type");
+            (param[..colon].trim().to_string(), param[colon + 1..].trim().to_string())
+        })
+        .collect();
+    let returns = text[close + 1..].trim().trim_start_matches("->").trim().to_string();
+    (name, params, returns)
+}
+
+/// The contents `embed.h` ought to have.
+fn generate(source: &str) -> String {
+    let mut header = PREAMBLE.to_string();
+    let mut docs: Vec<String> = Vec::new();
+    let mut aliases: Vec<String> = Vec::new();
+    let mut lines = source.lines();
+
+    while let Some(line) = lines.next() {
+        let line = line.trim();
+
+        if let Some(doc) = line.strip_prefix("///") {
+            docs.push(doc.strip_prefix(' ').unwrap_or(doc).to_string());
+            continue;
+        }
+        if line.starts_with("//") || line.starts_with("#[") {
+            continue;
+        }
+
+        if let Some(constant) = line.strip_prefix("pub const EMBED_") {
+            let name = &constant[..constant.find(':').unwrap()];
+            let value = constant[constant.find('=').unwrap() + 1..].trim().trim_end_matches(';');
+            header.push_str(&format!("\n{}#define EMBED_{} {}\n", comment(&docs), name, value));
+        } else if let Some(alias) = line.strip_prefix("pub type ") {
+            // Only callbacks, as `Option<unsafe extern "C" fn(...)>`
+            let equals = alias.find('=').unwrap();
+            let name = alias[..equals].trim().to_string();
+            let function = alias[equals + 1..].trim().trim_end_matches(';');
+            let function = function.strip_prefix("Option<").and_then(|f| f.strip_suffix('>'))
+                .expect("only `Option<extern fn>` types can go in the header");
+            let function = &function[function.find("fn").unwrap() + 2..];
+            let (_, params, returns) = signature(function);
+
+            let params: Vec<String> = params.iter()
+                .map(|(name, rust)| declare(&c_type(rust, &aliases), name))
+                .collect();
+            header.push_str(&format!("\n{}typedef {} (*{})({});\n", comment(&docs),
+                                     c_type(&returns, &aliases), name, params.join(", ")));
+            aliases.push(name);
+        } else if line.starts_with("pub ") && line.contains("extern \"C\" fn ") {
+            // Everything up to the body, however many lines that takes
+            let mut text = line.to_string();
+            while !text.contains('{') {
+                text.push(' ');
+                text.push_str(lines.next().expect("a function without a body").trim());
+            }
+            let text = &text[text.find("fn ").unwrap() + 3..text.find('{').unwrap()];
+            let (name, params, returns) = signature(text);
+
+            let params: Vec<String> = params.iter()
+                .map(|(name, rust)| declare(&c_type(rust, &aliases), name))
+                .collect();
+            let params = if params.is_empty() { "void".to_string() } else { params.join(", ") };
+            header.push_str(&format!("\n{}{}({});\n", comment(&docs),
+                                     declare(&c_type(&returns, &aliases), &name), params));
+        }
+        docs.clear();
+    }
+
+    header.push('\n');
+    header.push_str(POSTAMBLE);
+    header
+}
+
+#[test]
+fn header_is_up_to_date() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let source = fs::read_to_string(root.join("src/lib.rs")).unwrap();
+    let generated = generate(&source);
+    let path = root.join("embed.h");
+
+    if env::var_os("EMBED_BLESS").is_some() {
+        fs::write(&path, &generated).unwrap();
+        return;
+    }
+    let existing = fs::read_to_string(&path).unwrap_or_default();
+    assert!(existing == generated,
+            "embed.h is out of date, run `EMBED_BLESS=1 cargo test` to write it out again");
+}
+
+#[test]
+fn header_compiles() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let status = Command::new("cc")
+        .args(["-fsyntax-only", "-std=c99", "-Wall", "-Wextra", "-Werror", "-pedantic", "-x", "c"])
+        .arg(root.join("embed.h"))
+        .status();
+
+    match status {
+        Ok(status) => assert!(status.success(), "embed.h doesn't compile"),
+        // No C compiler, so nothing to check with
+        Err(err) => eprintln!("skipping, couldn't run cc: {}", err),
+    }
+}